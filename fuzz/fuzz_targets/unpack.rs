@@ -0,0 +1,44 @@
+#![no_main]
+
+use funds4good_solana::{instruction::Funds4GoodInstruction, state};
+use libfuzzer_sys::fuzz_target;
+use solana_program::program_pack::Pack;
+
+// every unpacker must return an error for malformed input, never panic
+fuzz_target!(|data: &[u8]| {
+    if let Ok(instruction) = Funds4GoodInstruction::unpack(data) {
+        // anything accepted must encode back to the exact same bytes
+        assert_eq!(instruction.pack(), data);
+    }
+
+    let _ = state::BorrowerAccount::unpack_versioned(data);
+    let _ = state::GuarantorAccount::unpack_versioned(data);
+    let _ = state::Funds4GoodConfigAccount::unpack(data);
+    let _ = state::MintVaultRegistryAccount::unpack(data);
+    let _ = state::ReservePoolAccount::unpack(data);
+    let _ = state::unpack_to_loan_info_header(data);
+    let _ = state::unpack_unchecked_to_loan_info_header(data);
+    let _ = state::unpack_to_loan_info_acc_lender_data(data);
+    let _ = state::unpack_to_loan_info_acc_repayment_data(data);
+    let _ = state::unpack_to_loan_info_acc_amendment_data(data);
+    let _ = state::unpack_to_lender_account_data(data);
+    let _ = state::unpack_to_lender_withdrawal_history_header(data);
+    let _ = state::unpack_to_lender_withdrawal_record(data);
+
+    // loan info view is read slot by slot, including one past every region's capacity
+    let mut loan_info_data = data.to_vec();
+    loan_info_data.resize(state::LOAN_INFO_ACC_DATA_SIZE, 0);
+    if let Ok(loan_info) = state::LoanInfoAccount::new(&mut loan_info_data) {
+        let _ = loan_info.header();
+        let _ = loan_info.header_unchecked();
+        for i in 0..=state::LOAN_INFO_MAX_LENDERS {
+            let _ = loan_info.lender(i);
+        }
+        for i in 0..=state::LOAN_INFO_MAX_REPAYMENTS {
+            let _ = loan_info.repayment(i);
+        }
+        for i in 0..=state::LOAN_INFO_MAX_AMENDMENTS {
+            let _ = loan_info.amendment(i);
+        }
+    }
+});
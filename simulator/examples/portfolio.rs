@@ -0,0 +1,28 @@
+use funds4good_solana_simulator::{SimulationConfig, run_simulation};
+use std::env;
+
+// usage: cargo run --release --example portfolio -- [seed] [num_borrowers] [num_lenders]
+#[tokio::main]
+async fn main() {
+    let mut args = env::args().skip(1);
+    let mut config = SimulationConfig::default();
+    if let Some(seed) = args.next() {
+        config.seed = seed.parse().expect("seed must be a number");
+    }
+    if let Some(num_borrowers) = args.next() {
+        config.num_borrowers = num_borrowers
+            .parse()
+            .expect("num_borrowers must be a number");
+    }
+    if let Some(num_lenders) = args.next() {
+        config.num_lenders = num_lenders.parse().expect("num_lenders must be a number");
+    }
+
+    match run_simulation(&config).await {
+        Ok(report) => print!("{}", report),
+        Err(err) => {
+            eprintln!("simulation failed: {}", err);
+            std::process::exit(1);
+        }
+    }
+}
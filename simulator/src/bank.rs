@@ -0,0 +1,204 @@
+use crate::error::SimulationError;
+use funds4good_solana::processor::Processor;
+use rand::Rng;
+use rand_chacha::ChaCha8Rng;
+use solana_program::{
+    bpf_loader_upgradeable::{self, UpgradeableLoaderState},
+    instruction::Instruction,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    rent::Rent,
+    system_instruction,
+    sysvar::clock::Clock,
+};
+use solana_program_test::{ProgramTest, ProgramTestBanksClientExt, ProgramTestContext, processor};
+use solana_sdk::{
+    account::{Account, AccountSharedData},
+    signature::{Keypair, Signer, keypair_from_seed},
+    transaction::Transaction,
+};
+use spl_token::state::{Account as TokenAccount, Mint};
+
+const UPGRADEABLE_PROGRAM_DATA_METADATA_SIZE: usize = 45;
+
+// local program-test bank running the program natively through Processor::process
+pub struct SimulationBank {
+    context: ProgramTestContext,
+    rent: Rent,
+}
+
+impl SimulationBank {
+    pub async fn start(program_id: Pubkey) -> Result<Self, SimulationError> {
+        let mut context = ProgramTest::new(
+            "funds4good_solana",
+            program_id,
+            processor!(Processor::process),
+        )
+        .start_with_context()
+        .await;
+        let rent = context.banks_client.get_rent().await?;
+
+        Ok(SimulationBank { context, rent })
+    }
+
+    pub fn payer_pubkey(&self) -> Pubkey {
+        self.context.payer.pubkey()
+    }
+
+    pub fn rent(&self) -> &Rent {
+        &self.rent
+    }
+
+    pub async fn process(
+        &mut self,
+        instructions: &[Instruction],
+        signers: &[&Keypair],
+    ) -> Result<(), SimulationError> {
+        let recent_blockhash = self.context.banks_client.get_latest_blockhash().await?;
+
+        let mut all_signers = vec![&self.context.payer];
+        all_signers.extend_from_slice(signers);
+        let transaction = Transaction::new_signed_with_payer(
+            instructions,
+            Some(&self.context.payer.pubkey()),
+            &all_signers,
+            recent_blockhash,
+        );
+
+        self.context
+            .banks_client
+            .process_transaction(transaction)
+            .await?;
+        Ok(())
+    }
+
+    // waits for a blockhash newer than the last one used, so a transaction identical to an earlier one isn't deduplicated
+    pub async fn refresh_blockhash(&mut self) -> Result<(), SimulationError> {
+        let recent_blockhash = self.context.banks_client.get_latest_blockhash().await?;
+        self.context
+            .banks_client
+            .get_new_latest_blockhash(&recent_blockhash)
+            .await?;
+        Ok(())
+    }
+
+    // program runs natively without being deployed, so its program data account is set up the way
+    // upgradeable loader leaves it, with authority allowed to create config and become admin
+    pub fn set_upgrade_authority(&mut self, program_id: &Pubkey, authority: &Pubkey) {
+        let (program_data, _) =
+            Pubkey::find_program_address(&[program_id.as_ref()], &bpf_loader_upgradeable::id());
+        let program_data_account = Account::new_data(
+            self.rent
+                .minimum_balance(UPGRADEABLE_PROGRAM_DATA_METADATA_SIZE),
+            &UpgradeableLoaderState::ProgramData {
+                slot: 0,
+                upgrade_authority_address: Some(*authority),
+            },
+            &bpf_loader_upgradeable::id(),
+        )
+        .expect("program data state always serializes");
+        self.context.set_account(
+            &program_data,
+            &AccountSharedData::from(program_data_account),
+        );
+    }
+
+    pub async fn set_unix_timestamp(&mut self, unix_timestamp: i64) -> Result<(), SimulationError> {
+        let mut clock = self.context.banks_client.get_sysvar::<Clock>().await?;
+        clock.unix_timestamp = unix_timestamp;
+        self.context.set_sysvar(&clock);
+        Ok(())
+    }
+
+    pub async fn account_data(&mut self, pubkey: &Pubkey) -> Result<Vec<u8>, SimulationError> {
+        self.context
+            .banks_client
+            .get_account(*pubkey)
+            .await?
+            .map(|account| account.data)
+            .ok_or(SimulationError::AccountNotFound)
+    }
+
+    pub async fn token_balance(&mut self, token_account: &Pubkey) -> Result<u64, SimulationError> {
+        let data = self.account_data(token_account).await?;
+        Ok(TokenAccount::unpack(&data)?.amount)
+    }
+
+    pub fn create_program_account_ix(
+        &self,
+        program_id: &Pubkey,
+        account_pubkey: &Pubkey,
+        data_len: usize,
+    ) -> Instruction {
+        system_instruction::create_account(
+            &self.payer_pubkey(),
+            account_pubkey,
+            self.rent.minimum_balance(data_len),
+            data_len as u64,
+            program_id,
+        )
+    }
+
+    pub async fn create_mint(
+        &mut self,
+        mint: &Keypair,
+        authority: &Pubkey,
+        decimals: u8,
+    ) -> Result<(), SimulationError> {
+        let instructions = [
+            self.create_program_account_ix(&spl_token::id(), &mint.pubkey(), Mint::LEN),
+            spl_token::instruction::initialize_mint(
+                &spl_token::id(),
+                &mint.pubkey(),
+                authority,
+                None,
+                decimals,
+            )?,
+        ];
+        self.process(&instructions, &[mint]).await
+    }
+
+    // creates a token account and optionally mints coins into it in the same transaction
+    pub async fn create_token_account(
+        &mut self,
+        token_account: &Keypair,
+        mint: &Pubkey,
+        owner: &Pubkey,
+        mint_authority: &Keypair,
+        amount: u64,
+    ) -> Result<(), SimulationError> {
+        let mut instructions = vec![
+            self.create_program_account_ix(
+                &spl_token::id(),
+                &token_account.pubkey(),
+                TokenAccount::LEN,
+            ),
+            spl_token::instruction::initialize_account(
+                &spl_token::id(),
+                &token_account.pubkey(),
+                mint,
+                owner,
+            )?,
+        ];
+        if amount == 0 {
+            return self.process(&instructions, &[token_account]).await;
+        }
+
+        instructions.push(spl_token::instruction::mint_to(
+            &spl_token::id(),
+            mint,
+            &token_account.pubkey(),
+            &mint_authority.pubkey(),
+            &[],
+            amount,
+        )?);
+        self.process(&instructions, &[token_account, mint_authority])
+            .await
+    }
+}
+
+// keypairs are derived from simulation rng, so every run with the same seed uses the same addresses
+pub fn new_keypair(rng: &mut ChaCha8Rng) -> Keypair {
+    let seed: [u8; 32] = rng.r#gen();
+    keypair_from_seed(&seed).expect("32 byte seed is always a valid keypair seed")
+}
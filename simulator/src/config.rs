@@ -0,0 +1,142 @@
+use crate::error::SimulationError;
+use funds4good_solana::{
+    processor::{
+        BPS_DENOMINATOR, EMI_INTERVAL_DAYS, LENDING_COINS_MULTIPLE, LOAN_COINS_MULTIPLE,
+        MAX_LATE_FEE_DAILY_BPS, MAX_LOAN_COINS, MAX_PLATFORM_FEE_BPS, MAX_RESERVE_FEE_BPS,
+        MIN_LOAN_COINS,
+    },
+    state,
+};
+
+// every parameter of a simulation run, same config and seed always give the same report
+#[derive(Clone, Debug)]
+pub struct SimulationConfig {
+    pub seed: u64,
+    pub num_borrowers: usize,
+    pub num_lenders: usize,
+    // coins minted to every lender before fundraising starts
+    pub lender_budget_coins: u64,
+    // loan amounts are drawn uniformly in multiples of 100 coins
+    pub min_loan_coins: u64,
+    pub max_loan_coins: u64,
+    // every lend is a multiple of 10 coins up to this amount
+    pub max_lending_ticket_coins: u64,
+    pub num_emis: u16,
+    pub num_days_left_for_first_repayment: u16,
+    // chance that a borrower stops paying at a random installment and the loan gets defaulted
+    pub default_probability: f64,
+    // chance that an installment is paid late, by 1 to max_days_late days
+    pub late_probability: f64,
+    pub max_days_late: u16,
+    pub origination_fee_bps: u16,
+    pub servicing_fee_bps: u16,
+    pub reserve_fee_bps: u16,
+    pub late_fee_flat_coins: u64,
+    pub late_fee_daily_bps: u16,
+    pub late_fee_lenders_share_bps: u16,
+    // clock is set to this unix timestamp before any loan is created
+    pub start_timestamp: i64,
+}
+
+impl Default for SimulationConfig {
+    fn default() -> Self {
+        SimulationConfig {
+            seed: 0,
+            num_borrowers: 1_000,
+            num_lenders: 2_000,
+            lender_budget_coins: 5_000,
+            min_loan_coins: MIN_LOAN_COINS,
+            max_loan_coins: MAX_LOAN_COINS,
+            max_lending_ticket_coins: 200,
+            num_emis: 6,
+            num_days_left_for_first_repayment: 30,
+            default_probability: 0.05,
+            late_probability: 0.15,
+            max_days_late: 20,
+            origination_fee_bps: 100,
+            servicing_fee_bps: 100,
+            reserve_fee_bps: 200,
+            late_fee_flat_coins: 1,
+            late_fee_daily_bps: 10,
+            late_fee_lenders_share_bps: 5_000,
+            start_timestamp: 1_700_000_000,
+        }
+    }
+}
+
+impl SimulationConfig {
+    pub fn validate(&self) -> Result<(), SimulationError> {
+        if self.num_borrowers == 0 {
+            return Err(SimulationError::InvalidConfig(
+                "num_borrowers must be above 0",
+            ));
+        }
+        if self.num_lenders == 0 || self.num_lenders > state::LENDERS_STORAGE_MAX_LENDERS {
+            return Err(SimulationError::InvalidConfig(
+                "num_lenders must fit in one lenders storage account",
+            ));
+        }
+        if self.min_loan_coins < MIN_LOAN_COINS
+            || self.max_loan_coins > MAX_LOAN_COINS
+            || self.min_loan_coins > self.max_loan_coins
+            || !self.min_loan_coins.is_multiple_of(LOAN_COINS_MULTIPLE)
+            || !self.max_loan_coins.is_multiple_of(LOAN_COINS_MULTIPLE)
+        {
+            return Err(SimulationError::InvalidConfig(
+                "loan amounts must be multiples of 100 coins between 200 and 2000",
+            ));
+        }
+        if self.max_lending_ticket_coins < LENDING_COINS_MULTIPLE
+            || !self
+                .max_lending_ticket_coins
+                .is_multiple_of(LENDING_COINS_MULTIPLE)
+        {
+            return Err(SimulationError::InvalidConfig(
+                "max_lending_ticket_coins must be a multiple of 10 coins",
+            ));
+        }
+        // a lender lends at most once to a loan, so a loan needs enough distinct lenders
+        if (self.num_lenders as u64) * self.max_lending_ticket_coins < self.max_loan_coins {
+            return Err(SimulationError::InvalidConfig(
+                "num_lenders can't fund the largest loan with max_lending_ticket_coins",
+            ));
+        }
+        if (self.num_lenders as u64) * self.lender_budget_coins
+            < (self.num_borrowers as u64) * self.max_loan_coins
+        {
+            return Err(SimulationError::InvalidConfig(
+                "lenders budget can't fund every loan at max_loan_coins",
+            ));
+        }
+        if self.num_emis == 0 || self.num_emis as usize > state::LOAN_INFO_MAX_REPAYMENTS {
+            return Err(SimulationError::InvalidConfig(
+                "num_emis must fit in loan info repayments region",
+            ));
+        }
+        // late payments of an installment must happen before the next installment is due
+        if self.max_days_late == 0 || self.max_days_late as u64 >= EMI_INTERVAL_DAYS {
+            return Err(SimulationError::InvalidConfig(
+                "max_days_late must be between 1 and 29",
+            ));
+        }
+        if self.origination_fee_bps > MAX_PLATFORM_FEE_BPS
+            || self.servicing_fee_bps > MAX_PLATFORM_FEE_BPS
+            || self.reserve_fee_bps > MAX_RESERVE_FEE_BPS
+            || self.late_fee_daily_bps > MAX_LATE_FEE_DAILY_BPS
+            || self.late_fee_lenders_share_bps as u64 > BPS_DENOMINATOR
+        {
+            return Err(SimulationError::InvalidConfig(
+                "fees must be within the caps of the program",
+            ));
+        }
+        if !(0.0..=1.0).contains(&self.default_probability)
+            || !(0.0..=1.0).contains(&self.late_probability)
+        {
+            return Err(SimulationError::InvalidConfig(
+                "probabilities must be between 0 and 1",
+            ));
+        }
+
+        Ok(())
+    }
+}
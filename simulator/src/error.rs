@@ -0,0 +1,24 @@
+use solana_program_test::BanksClientError;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum SimulationError {
+    /// Simulation config can't be run against the program limits
+    #[error("Invalid Simulation Config: {0}")]
+    InvalidConfig(&'static str),
+    /// Lenders don't have enough free coins left to fund a loan
+    #[error("Lenders Ran Out Of Funds")]
+    LendersRanOutOfFunds,
+    /// Transaction sent to the local bank failed
+    #[error("Banks Client Error: {0}")]
+    BanksClient(#[from] BanksClientError),
+    /// Local bank did not produce a new blockhash
+    #[error("Io Error: {0}")]
+    Io(#[from] std::io::Error),
+    /// Account expected to exist in the local bank is missing
+    #[error("Account Not Found")]
+    AccountNotFound,
+    /// Account data could not be read with program state layouts
+    #[error("Invalid Account Data: {0}")]
+    InvalidAccountData(#[from] solana_program::program_error::ProgramError),
+}
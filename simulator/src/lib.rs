@@ -0,0 +1,12 @@
+// deterministic loan portfolio simulator, it runs the program natively in a local program-test bank
+pub mod bank;
+pub mod config;
+pub mod error;
+pub mod platform;
+pub mod report;
+pub mod simulation;
+
+pub use config::SimulationConfig;
+pub use error::SimulationError;
+pub use report::SimulationReport;
+pub use simulation::run_simulation;
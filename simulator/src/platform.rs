@@ -0,0 +1,154 @@
+use crate::{
+    bank::{SimulationBank, new_keypair},
+    config::SimulationConfig,
+    error::SimulationError,
+};
+use funds4good_solana::{instruction, state};
+use rand_chacha::ChaCha8Rng;
+use solana_program::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+
+pub const MINT_DECIMALS: u8 = 9;
+pub const COIN: u64 = 1_000_000_000;
+
+// program accounts of the simulated mint, set up the way an admin deploys the platform
+pub struct Platform {
+    pub program_id: Pubkey,
+    pub admin: Keypair,
+    pub mint: Pubkey,
+    pub vault: Pubkey,
+    pub vault_authority: Pubkey,
+    pub config: Pubkey,
+    pub mint_vault_registry: Pubkey,
+    pub lenders_storage: Pubkey,
+    pub treasury: Pubkey,
+    pub reserve_pool: Pubkey,
+    pub reserve_vault: Pubkey,
+}
+
+impl Platform {
+    pub async fn deploy(
+        bank: &mut SimulationBank,
+        rng: &mut ChaCha8Rng,
+        program_id: Pubkey,
+        config: &SimulationConfig,
+    ) -> Result<Self, SimulationError> {
+        // admin mints every coin of the simulation, payer covers rent and fees
+        let admin = new_keypair(rng);
+        bank.set_upgrade_authority(&program_id, &admin.pubkey());
+        let payer_pubkey = bank.payer_pubkey();
+        bank.process(
+            &[solana_program::system_instruction::transfer(
+                &payer_pubkey,
+                &admin.pubkey(),
+                10 * COIN,
+            )],
+            &[],
+        )
+        .await?;
+
+        let mint = new_keypair(rng);
+        bank.create_mint(&mint, &admin.pubkey(), MINT_DECIMALS)
+            .await?;
+        let mint = mint.pubkey();
+
+        let lenders_storage = new_keypair(rng);
+        let instructions = [
+            bank.create_program_account_ix(
+                &program_id,
+                &lenders_storage.pubkey(),
+                state::LENDERS_STORAGE_ACC_DATA_SIZE,
+            ),
+            instruction::initialize_lenders_storage_account(
+                &program_id,
+                &admin.pubkey(),
+                &lenders_storage.pubkey(),
+            ),
+        ];
+        bank.process(&instructions, &[&lenders_storage, &admin])
+            .await?;
+        let lenders_storage = lenders_storage.pubkey();
+
+        let (vault_authority, _) =
+            Pubkey::find_program_address(&[b"Funds4GoodFinance"], &program_id);
+        let (config_pubkey, _) =
+            Pubkey::find_program_address(&[b"Funds4GoodFinanceConfig"], &program_id);
+        let (mint_vault_registry, _) =
+            Pubkey::find_program_address(&[b"Funds4GoodFinanceVault", mint.as_ref()], &program_id);
+        let (reserve_pool, _) = Pubkey::find_program_address(
+            &[b"Funds4GoodFinanceReserve", mint.as_ref()],
+            &program_id,
+        );
+
+        let vault = new_keypair(rng);
+        let treasury = new_keypair(rng);
+        let reserve_vault = new_keypair(rng);
+        for token_account in [&vault, &treasury, &reserve_vault] {
+            bank.create_token_account(token_account, &mint, &admin.pubkey(), &admin, 0)
+                .await?;
+        }
+
+        bank.process(
+            &[
+                instruction::transfer_Funds4Good_vault_account_ownership(
+                    &program_id,
+                    &admin.pubkey(),
+                    &vault.pubkey(),
+                    &config_pubkey,
+                    &mint,
+                    &mint_vault_registry,
+                    &lenders_storage,
+                ),
+                instruction::transfer_treasury_account_ownership(
+                    &program_id,
+                    &admin.pubkey(),
+                    &treasury.pubkey(),
+                    &config_pubkey,
+                    &mint_vault_registry,
+                ),
+                instruction::set_platform_fees(
+                    &program_id,
+                    &admin.pubkey(),
+                    &config_pubkey,
+                    &mint_vault_registry,
+                    config.origination_fee_bps,
+                    config.servicing_fee_bps,
+                ),
+                instruction::set_late_fees(
+                    &program_id,
+                    &admin.pubkey(),
+                    &config_pubkey,
+                    &mint_vault_registry,
+                    config.late_fee_flat_coins * COIN,
+                    config.late_fee_daily_bps,
+                    config.late_fee_lenders_share_bps,
+                ),
+                instruction::configure_reserve_pool(
+                    &program_id,
+                    &admin.pubkey(),
+                    &config_pubkey,
+                    &mint_vault_registry,
+                    &reserve_pool,
+                    &reserve_vault.pubkey(),
+                    config.reserve_fee_bps,
+                ),
+            ],
+            &[&admin],
+        )
+        .await?;
+
+        Ok(Platform {
+            program_id,
+            admin,
+            mint,
+            vault: vault.pubkey(),
+            vault_authority,
+            config: config_pubkey,
+            mint_vault_registry,
+            lenders_storage,
+            treasury: treasury.pubkey(),
+            reserve_pool,
+            reserve_vault: reserve_vault.pubkey(),
+        })
+    }
+}
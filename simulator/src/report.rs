@@ -0,0 +1,221 @@
+use crate::platform::COIN;
+use std::fmt;
+
+// credit scores are reported in whole points, initial score of a borrower is 500 points
+pub const SCORE_BUCKET_POINTS: u64 = 10;
+const BPS_DENOMINATOR: i128 = 10_000;
+
+pub struct SimulationReport {
+    pub num_loans: usize,
+    pub num_repaid_loans: usize,
+    pub num_defaulted_loans: usize,
+    pub num_on_time_installments: usize,
+    pub num_late_installments: usize,
+    pub total_principal: u64,
+    pub total_repaid: u64,
+    pub total_penalty_paid: u64,
+    pub lender_yields: YieldStats,
+    pub solvency: SolvencyReport,
+    pub reserve: ReserveReport,
+    pub treasury_balance: u64,
+    pub credit_scores: ScoreDistribution,
+}
+
+// yield of a lender is (free balance returned - amount lent) / amount lent, in bps
+pub struct YieldStats {
+    pub num_lenders: usize,
+    pub total_lent: u64,
+    pub total_returned: u64,
+    pub mean_yield_bps: i64,
+    pub min_yield_bps: i64,
+    pub median_yield_bps: i64,
+    pub max_yield_bps: i64,
+    pub num_lenders_with_loss: usize,
+}
+
+impl YieldStats {
+    // takes (lent amount, returned amount) of every lender who lent anything
+    pub fn from_lenders(lenders: &[(u64, u64)]) -> Self {
+        let mut yields_bps: Vec<i64> = lenders
+            .iter()
+            .map(|&(lent, returned)| {
+                ((returned as i128 - lent as i128) * BPS_DENOMINATOR / lent as i128) as i64
+            })
+            .collect();
+        yields_bps.sort_unstable();
+
+        let num_lenders = yields_bps.len();
+        let total_lent = lenders.iter().map(|&(lent, _)| lent).sum();
+        let total_returned = lenders.iter().map(|&(_, returned)| returned).sum();
+        let mean_yield_bps = if num_lenders == 0 {
+            0
+        } else {
+            yields_bps.iter().sum::<i64>() / num_lenders as i64
+        };
+
+        YieldStats {
+            num_lenders,
+            total_lent,
+            total_returned,
+            mean_yield_bps,
+            min_yield_bps: yields_bps.first().copied().unwrap_or(0),
+            median_yield_bps: yields_bps.get(num_lenders / 2).copied().unwrap_or(0),
+            max_yield_bps: yields_bps.last().copied().unwrap_or(0),
+            num_lenders_with_loss: yields_bps
+                .iter()
+                .filter(|&&yield_bps| yield_bps < 0)
+                .count(),
+        }
+    }
+}
+
+// every coin in vault is owed to lenders as free balance once all loans are disbursed,
+// vault is solvent when it holds at least what lenders can withdraw
+pub struct SolvencyReport {
+    pub vault_balance: u64,
+    pub lender_free_balances: u64,
+}
+
+impl SolvencyReport {
+    pub fn surplus(&self) -> i128 {
+        self.vault_balance as i128 - self.lender_free_balances as i128
+    }
+
+    pub fn is_solvent(&self) -> bool {
+        self.surplus() >= 0
+    }
+}
+
+pub struct ReserveReport {
+    pub reserve_vault_balance: u64,
+    pub pool_balance: u64,
+    pub total_contributed_amount: u64,
+    pub total_covered_amount: u64,
+    pub coverage_ratio_bps: u64,
+}
+
+pub struct ScoreBucket {
+    pub lower_bound_points: u64,
+    pub num_borrowers: usize,
+}
+
+pub struct ScoreDistribution {
+    pub min_points: u64,
+    pub max_points: u64,
+    pub mean_points: u64,
+    pub buckets: Vec<ScoreBucket>,
+}
+
+impl ScoreDistribution {
+    pub fn from_scores(credit_scores: &[u64]) -> Self {
+        let points: Vec<u64> = credit_scores.iter().map(|score| score / COIN).collect();
+        let min_points = points.iter().copied().min().unwrap_or(0);
+        let max_points = points.iter().copied().max().unwrap_or(0);
+        let mean_points = if points.is_empty() {
+            0
+        } else {
+            points.iter().sum::<u64>() / points.len() as u64
+        };
+
+        let mut buckets: Vec<ScoreBucket> = vec![];
+        let mut lower_bound_points = min_points / SCORE_BUCKET_POINTS * SCORE_BUCKET_POINTS;
+        while !points.is_empty() && lower_bound_points <= max_points {
+            let num_borrowers = points
+                .iter()
+                .filter(|&&p| {
+                    p >= lower_bound_points && p < lower_bound_points + SCORE_BUCKET_POINTS
+                })
+                .count();
+            buckets.push(ScoreBucket {
+                lower_bound_points,
+                num_borrowers,
+            });
+            lower_bound_points += SCORE_BUCKET_POINTS;
+        }
+
+        ScoreDistribution {
+            min_points,
+            max_points,
+            mean_points,
+            buckets,
+        }
+    }
+}
+
+fn coins(amount: u64) -> f64 {
+    amount as f64 / COIN as f64
+}
+
+impl fmt::Display for SimulationReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "loans: {} ({} repaid, {} defaulted), installments: {} on time, {} late",
+            self.num_loans,
+            self.num_repaid_loans,
+            self.num_defaulted_loans,
+            self.num_on_time_installments,
+            self.num_late_installments
+        )?;
+        writeln!(
+            f,
+            "principal: {:.2}, repaid: {:.2}, late fees paid: {:.2}",
+            coins(self.total_principal),
+            coins(self.total_repaid),
+            coins(self.total_penalty_paid)
+        )?;
+
+        let yields = &self.lender_yields;
+        writeln!(
+            f,
+            "lenders: {}, lent: {:.2}, returned: {:.2}, with loss: {}",
+            yields.num_lenders,
+            coins(yields.total_lent),
+            coins(yields.total_returned),
+            yields.num_lenders_with_loss
+        )?;
+        writeln!(
+            f,
+            "lender yield bps: mean {}, min {}, median {}, max {}",
+            yields.mean_yield_bps,
+            yields.min_yield_bps,
+            yields.median_yield_bps,
+            yields.max_yield_bps
+        )?;
+
+        writeln!(
+            f,
+            "vault: {:.2}, lender free balances: {:.2}, solvent: {}",
+            coins(self.solvency.vault_balance),
+            coins(self.solvency.lender_free_balances),
+            self.solvency.is_solvent()
+        )?;
+        writeln!(
+            f,
+            "reserve vault: {:.2}, contributed: {:.2}, covered: {:.2}, coverage ratio bps: {}",
+            coins(self.reserve.reserve_vault_balance),
+            coins(self.reserve.total_contributed_amount),
+            coins(self.reserve.total_covered_amount),
+            self.reserve.coverage_ratio_bps
+        )?;
+        writeln!(f, "treasury: {:.2}", coins(self.treasury_balance))?;
+
+        let scores = &self.credit_scores;
+        writeln!(
+            f,
+            "credit score points: mean {}, min {}, max {}",
+            scores.mean_points, scores.min_points, scores.max_points
+        )?;
+        for bucket in &scores.buckets {
+            writeln!(
+                f,
+                "  {:>4}-{:<4} {}",
+                bucket.lower_bound_points,
+                bucket.lower_bound_points + SCORE_BUCKET_POINTS - 1,
+                bucket.num_borrowers
+            )?;
+        }
+
+        Ok(())
+    }
+}
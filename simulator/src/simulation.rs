@@ -0,0 +1,553 @@
+use crate::{
+    bank::{SimulationBank, new_keypair},
+    config::SimulationConfig,
+    error::SimulationError,
+    platform::{COIN, Platform},
+    report::{ReserveReport, ScoreDistribution, SimulationReport, SolvencyReport, YieldStats},
+};
+use funds4good_solana::{
+    instruction,
+    processor::{
+        BPS_DENOMINATOR, EMI_INTERVAL_DAYS, LENDING_COINS_MULTIPLE, LOAN_COINS_MULTIPLE,
+        LOAN_DEFAULT_GRACE_PERIOD_DAYS,
+    },
+    state::{
+        self, BorrowerAccount, LoanInfoAccDataHeader, LoanInfoAccount, LoanStatus,
+        ReservePoolAccount,
+    },
+};
+use rand::{Rng, SeedableRng, seq::SliceRandom};
+use rand_chacha::ChaCha8Rng;
+use solana_program::{program_pack::Pack, pubkey::Pubkey};
+use solana_sdk::signature::{Keypair, Signer};
+use std::collections::HashSet;
+
+// clock of the bank is an i64 unix timestamp
+const NUM_SECONDS_IN_ONE_DAY: i64 = funds4good_solana::processor::NUM_SECONDS_IN_ONE_DAY as i64;
+const NUM_DAYS_FOR_FUNDRAISING: u16 = 10;
+// a loan gives up on finding lenders after this many lenders without free coins in a row
+const MAX_FUNDING_ATTEMPTS_PER_LOAN: usize = 10_000;
+
+struct SimulatedLender {
+    keypair: Keypair,
+    token_account: Pubkey,
+    lender_id: u32,
+    available_amount: u64,
+    lent_amount: u64,
+}
+
+struct SimulatedLoan {
+    borrower: Keypair,
+    borrower_token_account: Pubkey,
+    borrower_storage: Pubkey,
+    loan_info: Pubkey,
+    total_loan_amount: u64,
+    first_repayment_last_date_timestamp: i64,
+    // borrower pays every installment before this index, None when the loan is repaid in full
+    stops_paying_at: Option<u16>,
+}
+
+#[derive(Default)]
+struct InstallmentCounts {
+    on_time: usize,
+    late: usize,
+}
+
+// runs a whole portfolio against the program in a local bank, same config always gives the same report
+pub async fn run_simulation(
+    config: &SimulationConfig,
+) -> Result<SimulationReport, SimulationError> {
+    config.validate()?;
+
+    let mut rng = ChaCha8Rng::seed_from_u64(config.seed);
+    let program_id = Pubkey::new_from_array(rng.r#gen());
+    let mut bank = SimulationBank::start(program_id).await?;
+    bank.set_unix_timestamp(config.start_timestamp).await?;
+
+    let platform = Platform::deploy(&mut bank, &mut rng, program_id, config).await?;
+    let mut lenders = create_lenders(&mut bank, &mut rng, &platform, config).await?;
+
+    let mut loans = Vec::with_capacity(config.num_borrowers);
+    for _ in 0..config.num_borrowers {
+        let loan = originate_loan(&mut bank, &mut rng, &platform, config).await?;
+        fund_loan(&mut bank, &mut rng, &platform, config, &loan, &mut lenders).await?;
+        disburse_loan(&mut bank, &platform, &loan).await?;
+        loans.push(loan);
+    }
+
+    let mut installment_counts = InstallmentCounts::default();
+    for installment_index in 0..config.num_emis {
+        // every round repeats the transactions of the round before, they need a newer blockhash
+        bank.refresh_blockhash().await?;
+
+        let mut payments: Vec<(i64, usize)> = vec![];
+        for (loan_index, loan) in loans.iter().enumerate() {
+            if loan
+                .stops_paying_at
+                .is_some_and(|stops_paying_at| installment_index >= stops_paying_at)
+            {
+                continue;
+            }
+            let due_timestamp = loan.first_repayment_last_date_timestamp
+                + installment_index as i64 * EMI_INTERVAL_DAYS as i64 * NUM_SECONDS_IN_ONE_DAY;
+            let num_days_late = if rng.gen_bool(config.late_probability) {
+                rng.gen_range(1, config.max_days_late as i64 + 1)
+            } else {
+                -1
+            };
+            payments.push((
+                due_timestamp + num_days_late * NUM_SECONDS_IN_ONE_DAY,
+                loan_index,
+            ));
+        }
+        payments.sort_unstable();
+
+        let mut current_timestamp = None;
+        for (payment_timestamp, loan_index) in payments {
+            if current_timestamp != Some(payment_timestamp) {
+                bank.set_unix_timestamp(payment_timestamp).await?;
+                current_timestamp = Some(payment_timestamp);
+            }
+            let is_on_time = pay_installment(
+                &mut bank,
+                &platform,
+                config,
+                &loans[loan_index],
+                payment_timestamp,
+            )
+            .await?;
+            if is_on_time {
+                installment_counts.on_time += 1;
+            } else {
+                installment_counts.late += 1;
+            }
+        }
+    }
+
+    default_unpaid_loans(&mut bank, &platform, config, &loans).await?;
+
+    build_report(&mut bank, &platform, &loans, &lenders, installment_counts).await
+}
+
+async fn create_lenders(
+    bank: &mut SimulationBank,
+    rng: &mut ChaCha8Rng,
+    platform: &Platform,
+    config: &SimulationConfig,
+) -> Result<Vec<SimulatedLender>, SimulationError> {
+    let mut lenders = Vec::with_capacity(config.num_lenders);
+    for lender_id in 0..config.num_lenders {
+        let keypair = new_keypair(rng);
+        let token_account = new_keypair(rng);
+        let budget_amount = config.lender_budget_coins * COIN;
+        bank.create_token_account(
+            &token_account,
+            &platform.mint,
+            &keypair.pubkey(),
+            &platform.admin,
+            budget_amount,
+        )
+        .await?;
+
+        lenders.push(SimulatedLender {
+            keypair,
+            token_account: token_account.pubkey(),
+            lender_id: lender_id as u32,
+            available_amount: budget_amount,
+            lent_amount: 0,
+        });
+    }
+
+    Ok(lenders)
+}
+
+async fn originate_loan(
+    bank: &mut SimulationBank,
+    rng: &mut ChaCha8Rng,
+    platform: &Platform,
+    config: &SimulationConfig,
+) -> Result<SimulatedLoan, SimulationError> {
+    let borrower = new_keypair(rng);
+    let guarantor = new_keypair(rng);
+    let borrower_storage = new_keypair(rng);
+    let guarantor_storage = new_keypair(rng);
+    let loan_info = new_keypair(rng);
+    let borrower_token_account = new_keypair(rng);
+
+    let total_loan_amount = rng.gen_range(
+        config.min_loan_coins / LOAN_COINS_MULTIPLE,
+        config.max_loan_coins / LOAN_COINS_MULTIPLE + 1,
+    ) * LOAN_COINS_MULTIPLE
+        * COIN;
+    let stops_paying_at = if rng.gen_bool(config.default_probability) {
+        Some(rng.gen_range(0, config.num_emis))
+    } else {
+        None
+    };
+
+    bank.create_token_account(
+        &borrower_token_account,
+        &platform.mint,
+        &borrower.pubkey(),
+        &platform.admin,
+        0,
+    )
+    .await?;
+
+    let instructions = [
+        bank.create_program_account_ix(
+            &platform.program_id,
+            &borrower_storage.pubkey(),
+            BorrowerAccount::LEN,
+        ),
+        instruction::initialize_borrower_account(
+            &platform.program_id,
+            &borrower.pubkey(),
+            &borrower_storage.pubkey(),
+        ),
+        bank.create_program_account_ix(
+            &platform.program_id,
+            &guarantor_storage.pubkey(),
+            state::GuarantorAccount::LEN,
+        ),
+        instruction::initialize_guarantor_account(
+            &platform.program_id,
+            &guarantor.pubkey(),
+            &guarantor_storage.pubkey(),
+        ),
+        bank.create_program_account_ix(
+            &platform.program_id,
+            &loan_info.pubkey(),
+            state::LOAN_INFO_ACC_DATA_SIZE,
+        ),
+        instruction::initialize_loan_info_account(
+            &platform.program_id,
+            &guarantor.pubkey(),
+            &borrower.pubkey(),
+            &platform.mint_vault_registry,
+            &loan_info.pubkey(),
+            &borrower_storage.pubkey(),
+            config.num_days_left_for_first_repayment,
+            config.num_emis,
+            NUM_DAYS_FOR_FUNDRAISING,
+            total_loan_amount,
+            0,
+        ),
+    ];
+    bank.process(
+        &instructions,
+        &[
+            &borrower_storage,
+            &borrower,
+            &guarantor_storage,
+            &guarantor,
+            &loan_info,
+        ],
+    )
+    .await?;
+
+    let header = loan_info_header(bank, &loan_info.pubkey()).await?;
+
+    Ok(SimulatedLoan {
+        borrower,
+        borrower_token_account: borrower_token_account.pubkey(),
+        borrower_storage: borrower_storage.pubkey(),
+        loan_info: loan_info.pubkey(),
+        total_loan_amount,
+        first_repayment_last_date_timestamp: header.first_repayment_last_date_timestamp as i64,
+        stops_paying_at,
+    })
+}
+
+// random lenders lend random multiples of 10 coins until the loan is fully funded, a lender lends at most once to a loan
+async fn fund_loan(
+    bank: &mut SimulationBank,
+    rng: &mut ChaCha8Rng,
+    platform: &Platform,
+    config: &SimulationConfig,
+    loan: &SimulatedLoan,
+    lenders: &mut [SimulatedLender],
+) -> Result<(), SimulationError> {
+    let lending_multiple = LENDING_COINS_MULTIPLE * COIN;
+    let mut remaining_amount = loan.total_loan_amount;
+    let mut loan_lenders = HashSet::new();
+    let mut num_attempts = 0;
+
+    while remaining_amount > 0 {
+        if num_attempts == MAX_FUNDING_ATTEMPTS_PER_LOAN {
+            return Err(SimulationError::LendersRanOutOfFunds);
+        }
+        num_attempts += 1;
+
+        let lender = lenders
+            .choose_mut(rng)
+            .expect("validated config has at least one lender");
+        let available_amount = lender.available_amount / lending_multiple * lending_multiple;
+        if available_amount == 0 || loan_lenders.contains(&lender.lender_id) {
+            continue;
+        }
+
+        let ticket_amount = rng.gen_range(
+            1,
+            config.max_lending_ticket_coins / LENDING_COINS_MULTIPLE + 1,
+        ) * lending_multiple;
+        let amount_to_lend = ticket_amount.min(remaining_amount).min(available_amount);
+
+        bank.process(
+            &[instruction::lend_to_borrower(
+                &platform.program_id,
+                &lender.keypair.pubkey(),
+                &lender.token_account,
+                &platform.vault,
+                &platform.mint_vault_registry,
+                &loan.loan_info,
+                &platform.lenders_storage,
+                amount_to_lend,
+                lender.lender_id,
+            )],
+            &[&lender.keypair],
+        )
+        .await?;
+
+        loan_lenders.insert(lender.lender_id);
+        lender.available_amount -= amount_to_lend;
+        lender.lent_amount += amount_to_lend;
+        remaining_amount -= amount_to_lend;
+        num_attempts = 0;
+    }
+
+    Ok(())
+}
+
+async fn disburse_loan(
+    bank: &mut SimulationBank,
+    platform: &Platform,
+    loan: &SimulatedLoan,
+) -> Result<(), SimulationError> {
+    bank.process(
+        &[instruction::withdraw_collected_loan_funds(
+            &platform.program_id,
+            &loan.borrower.pubkey(),
+            &loan.borrower_token_account,
+            &platform.vault,
+            &platform.mint_vault_registry,
+            &loan.loan_info,
+            &platform.vault_authority,
+            &platform.reserve_pool,
+            &platform.treasury,
+        )],
+        &[&loan.borrower],
+    )
+    .await
+}
+
+// pays the first unpaid installment of a loan with its late fee, admin mints whatever borrower is short of,
+// returns whether the installment was paid before it was due
+async fn pay_installment(
+    bank: &mut SimulationBank,
+    platform: &Platform,
+    config: &SimulationConfig,
+    loan: &SimulatedLoan,
+    now: i64,
+) -> Result<bool, SimulationError> {
+    let header = loan_info_header(bank, &loan.loan_info).await?;
+    let (emi_amount, due_timestamp) = emi_amount_due(&header, config, now as u64);
+    let balance = bank.token_balance(&loan.borrower_token_account).await?;
+
+    let mut instructions = vec![];
+    let mut signers = vec![&loan.borrower];
+    if emi_amount > balance {
+        signers.push(&platform.admin);
+        instructions.push(spl_token::instruction::mint_to(
+            &spl_token::id(),
+            &platform.mint,
+            &loan.borrower_token_account,
+            &platform.admin.pubkey(),
+            &[],
+            emi_amount - balance,
+        )?);
+    }
+    instructions.push(instruction::pay_emi_for_loan(
+        &platform.program_id,
+        &loan.borrower.pubkey(),
+        &loan.borrower_token_account,
+        &platform.vault,
+        &platform.mint_vault_registry,
+        &loan.borrower_storage,
+        &platform.reserve_pool,
+        &platform.reserve_vault,
+        &loan.loan_info,
+        &platform.lenders_storage,
+        &platform.treasury,
+        emi_amount,
+    ));
+    bank.process(&instructions, &signers).await?;
+
+    Ok(now as u64 <= due_timestamp)
+}
+
+// mirrors how the program prices the first unpaid installment, the last installment also clears the remainder
+// left when the loan amount is not divisible by the number of emis, returns emi amount and installment due timestamp
+fn emi_amount_due(
+    header: &LoanInfoAccDataHeader,
+    config: &SimulationConfig,
+    now: u64,
+) -> (u64, u64) {
+    let num_emis = header.number_of_emis_needed_to_repay_the_loan as u64;
+    let installment_amount = header
+        .total_loan_amount
+        .saturating_sub(header.schedule_repaid_amount_base)
+        / num_emis;
+    let outstanding_amount = header
+        .total_loan_amount
+        .saturating_sub(header.repaid_amount_by_borrower);
+    let installment_index = header
+        .repaid_amount_by_borrower
+        .saturating_sub(header.schedule_repaid_amount_base)
+        / installment_amount;
+    let due_timestamp = header.first_repayment_last_date_timestamp
+        + installment_index * EMI_INTERVAL_DAYS * NUM_SECONDS_IN_ONE_DAY as u64;
+
+    let amount_to_repay = if installment_index + 1 >= num_emis {
+        outstanding_amount
+    } else {
+        installment_amount.min(outstanding_amount)
+    };
+
+    let late_fee_amount = if now <= due_timestamp {
+        0
+    } else {
+        let day = NUM_SECONDS_IN_ONE_DAY as u64;
+        let num_days_late = (now - due_timestamp).div_ceil(day);
+        let daily_late_fee_amount = (installment_amount as u128 * config.late_fee_daily_bps as u128
+            / BPS_DENOMINATOR as u128) as u64;
+        (daily_late_fee_amount * num_days_late + config.late_fee_flat_coins * COIN)
+            .min(installment_amount)
+    };
+
+    (amount_to_repay + late_fee_amount, due_timestamp)
+}
+
+// once the grace period after the last emi is over, every loan still active is defaulted
+async fn default_unpaid_loans(
+    bank: &mut SimulationBank,
+    platform: &Platform,
+    config: &SimulationConfig,
+    loans: &[SimulatedLoan],
+) -> Result<(), SimulationError> {
+    let Some(last_first_repayment_timestamp) = loans
+        .iter()
+        .map(|loan| loan.first_repayment_last_date_timestamp)
+        .max()
+    else {
+        return Ok(());
+    };
+    let last_due_timestamp = last_first_repayment_timestamp
+        + (config.num_emis as i64 - 1) * EMI_INTERVAL_DAYS as i64 * NUM_SECONDS_IN_ONE_DAY;
+    bank.set_unix_timestamp(
+        last_due_timestamp + (LOAN_DEFAULT_GRACE_PERIOD_DAYS as i64 + 1) * NUM_SECONDS_IN_ONE_DAY,
+    )
+    .await?;
+
+    for loan in loans {
+        let header = loan_info_header(bank, &loan.loan_info).await?;
+        if header.loan_status != LoanStatus::Active as u8 {
+            continue;
+        }
+        bank.process(
+            &[instruction::mark_loan_defaulted(
+                &platform.program_id,
+                &platform.admin.pubkey(),
+                &loan.loan_info,
+                &platform.vault,
+                &platform.mint_vault_registry,
+                &platform.reserve_pool,
+                &platform.reserve_vault,
+                &platform.lenders_storage,
+            )],
+            &[&platform.admin],
+        )
+        .await?;
+    }
+
+    Ok(())
+}
+
+async fn loan_info_header(
+    bank: &mut SimulationBank,
+    loan_info: &Pubkey,
+) -> Result<LoanInfoAccDataHeader, SimulationError> {
+    let mut data = bank.account_data(loan_info).await?;
+    Ok(LoanInfoAccount::new(&mut data)?.header()?)
+}
+
+async fn build_report(
+    bank: &mut SimulationBank,
+    platform: &Platform,
+    loans: &[SimulatedLoan],
+    lenders: &[SimulatedLender],
+    installment_counts: InstallmentCounts,
+) -> Result<SimulationReport, SimulationError> {
+    let mut num_repaid_loans = 0;
+    let mut num_defaulted_loans = 0;
+    let mut total_principal = 0;
+    let mut total_repaid = 0;
+    let mut total_penalty_paid = 0;
+    let mut credit_scores = Vec::with_capacity(loans.len());
+    for loan in loans {
+        let header = loan_info_header(bank, &loan.loan_info).await?;
+        if header.loan_status == LoanStatus::Repaid as u8 {
+            num_repaid_loans += 1;
+        } else if header.loan_status == LoanStatus::Defaulted as u8 {
+            num_defaulted_loans += 1;
+        }
+        total_principal += header.total_loan_amount;
+        total_repaid += header.repaid_amount_by_borrower;
+        total_penalty_paid += header.penalty_paid_amount;
+
+        let borrower_data = bank.account_data(&loan.borrower_storage).await?;
+        credit_scores.push(BorrowerAccount::unpack_versioned(&borrower_data)?.credit_score);
+    }
+
+    let mut lenders_storage_data = bank.account_data(&platform.lenders_storage).await?;
+    let lenders_storage = state::LendersStorageAccount::new(&mut lenders_storage_data)?;
+    let mut lender_free_balances = 0;
+    let mut lender_returns = vec![];
+    for lender in lenders {
+        let free_balance = lenders_storage
+            .lender(lender.lender_id)?
+            .amount_to_withdraw_or_lend;
+        lender_free_balances += free_balance;
+        if lender.lent_amount > 0 {
+            lender_returns.push((lender.lent_amount, free_balance));
+        }
+    }
+
+    let reserve_pool_data = bank.account_data(&platform.reserve_pool).await?;
+    let reserve_pool = ReservePoolAccount::unpack(&reserve_pool_data)?;
+
+    Ok(SimulationReport {
+        num_loans: loans.len(),
+        num_repaid_loans,
+        num_defaulted_loans,
+        num_on_time_installments: installment_counts.on_time,
+        num_late_installments: installment_counts.late,
+        total_principal,
+        total_repaid,
+        total_penalty_paid,
+        lender_yields: YieldStats::from_lenders(&lender_returns),
+        solvency: SolvencyReport {
+            vault_balance: bank.token_balance(&platform.vault).await?,
+            lender_free_balances,
+        },
+        reserve: ReserveReport {
+            reserve_vault_balance: bank.token_balance(&platform.reserve_vault).await?,
+            pool_balance: reserve_pool.pool_balance,
+            total_contributed_amount: reserve_pool.total_contributed_amount,
+            total_covered_amount: reserve_pool.total_covered_amount,
+            coverage_ratio_bps: reserve_pool.coverage_ratio_bps,
+        },
+        treasury_balance: bank.token_balance(&platform.treasury).await?,
+        credit_scores: ScoreDistribution::from_scores(&credit_scores),
+    })
+}
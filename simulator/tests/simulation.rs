@@ -0,0 +1,60 @@
+use funds4good_solana::processor::MAX_PLATFORM_FEE_BPS;
+use funds4good_solana_simulator::{SimulationConfig, SimulationError, run_simulation};
+
+fn small_config(seed: u64) -> SimulationConfig {
+    SimulationConfig {
+        seed,
+        num_borrowers: 8,
+        num_lenders: 30,
+        ..SimulationConfig::default()
+    }
+}
+
+#[tokio::test]
+async fn test_same_seed_gives_same_report() {
+    let config = small_config(7);
+
+    let first_report = run_simulation(&config).await.unwrap();
+    let second_report = run_simulation(&config).await.unwrap();
+
+    assert_eq!(first_report.to_string(), second_report.to_string());
+}
+
+// whatever borrowers do, vault holds every coin lenders can withdraw
+// and reserve vault holds every coin recorded in reserve pool
+#[tokio::test]
+async fn test_vault_stays_solvent() {
+    for seed in 0..3 {
+        let config = SimulationConfig {
+            default_probability: 0.3,
+            late_probability: 0.5,
+            ..small_config(seed)
+        };
+
+        let report = run_simulation(&config).await.unwrap();
+
+        assert!(report.solvency.is_solvent(), "seed {}", seed);
+        assert!(
+            report.reserve.reserve_vault_balance >= report.reserve.pool_balance,
+            "seed {}",
+            seed
+        );
+        assert_eq!(
+            report.num_loans,
+            report.num_repaid_loans + report.num_defaulted_loans
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_fees_above_program_caps_are_rejected() {
+    let config = SimulationConfig {
+        servicing_fee_bps: MAX_PLATFORM_FEE_BPS + 1,
+        ..small_config(0)
+    };
+
+    assert!(matches!(
+        run_simulation(&config).await,
+        Err(SimulationError::InvalidConfig(_))
+    ));
+}
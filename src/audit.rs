@@ -0,0 +1,207 @@
+use crate::{
+    error::Funds4GoodError,
+    state::{
+        AccTypes, LendersStorageAccount, LoanInfoAccDataHeader, LoanInfoAccount, LoanStatus,
+        MintVaultRegistryAccount, ReservePoolAccount,
+    },
+};
+use solana_program::{
+    entrypoint::ProgramResult, msg, program_error::ProgramError, program_pack::Pack, pubkey::Pubkey,
+};
+use spl_token::state::Account as TokenAccount;
+
+// Vault of a mint owes lenders their free balances and funds lent to loans still in fundraising, registry of the mint
+// keeps their running total in vault_liabilities, funds of disbursed loans have left the vault and cancelled loans
+// moved theirs back to free balances. Reserve pool funds are held in the separate reserve vault, so they are reconciled
+// against pool_balance on their own, reserve balances are None for a mint without reserve pool.
+// A surplus is not a failure, anyone can send coins to a vault.
+// ledger_liabilities is only known off-chain, where lenders storage and every loan info of the mint are summed up
+pub struct VaultAuditReport {
+    pub vault_balance: u64,
+    pub vault_liabilities: u64,
+    pub ledger_liabilities: Option<u64>,
+    pub reserve_vault_balance: Option<u64>,
+    pub reserve_pool_balance: Option<u64>,
+}
+
+impl VaultAuditReport {
+    // positive is surplus and negative is shortfall of vault balance against its liabilities
+    pub fn vault_discrepancy(&self) -> i128 {
+        self.vault_balance as i128 - self.vault_liabilities as i128
+    }
+
+    // positive is surplus and negative is shortfall of reserve vault balance against pool balance
+    pub fn reserve_discrepancy(&self) -> Option<i128> {
+        match (self.reserve_vault_balance, self.reserve_pool_balance) {
+            (Some(reserve_vault_balance), Some(reserve_pool_balance)) => {
+                Some(reserve_vault_balance as i128 - reserve_pool_balance as i128)
+            }
+            _ => None,
+        }
+    }
+
+    pub fn log(&self) {
+        msg!(
+            "vault audit: vault balance {}, vault liabilities {}, discrepancy {}",
+            self.vault_balance,
+            self.vault_liabilities,
+            self.vault_discrepancy()
+        );
+        if let Some(ledger_liabilities) = self.ledger_liabilities {
+            msg!(
+                "vault audit: lenders free balance and undisbursed loan amount {}",
+                ledger_liabilities
+            );
+        }
+        if let (
+            Some(reserve_vault_balance),
+            Some(reserve_pool_balance),
+            Some(reserve_discrepancy),
+        ) = (
+            self.reserve_vault_balance,
+            self.reserve_pool_balance,
+            self.reserve_discrepancy(),
+        ) {
+            msg!(
+                "vault audit: reserve vault balance {}, reserve pool balance {}, discrepancy {}",
+                reserve_vault_balance,
+                reserve_pool_balance,
+                reserve_discrepancy
+            );
+        }
+    }
+
+    // fails when vault or reserve vault holds less than what is recorded against it,
+    // or when ledgers were summed up and running total of registry drifted from them
+    pub fn verify(&self) -> ProgramResult {
+        if self.vault_discrepancy() < 0 {
+            return Err(Funds4GoodError::VaultBalanceBelowLiabilities.into());
+        }
+        if let Some(reserve_discrepancy) = self.reserve_discrepancy() {
+            if reserve_discrepancy < 0 {
+                return Err(Funds4GoodError::ReserveVaultBalanceBelowPoolBalance.into());
+            }
+        }
+        if let Some(ledger_liabilities) = self.ledger_liabilities {
+            if ledger_liabilities != self.vault_liabilities {
+                return Err(Funds4GoodError::VaultLiabilitiesDoNotMatchLedgers.into());
+            }
+        }
+        Ok(())
+    }
+}
+
+// checks vault against running total of registry, so it reads a fixed number of accounts however many lenders and loans a mint has.
+// reserve vault is only reconciled when reserve pool of the mint is configured, its balance has to be passed along then
+pub fn audit_vault(
+    mint_vault_registry_data: &MintVaultRegistryAccount,
+    vault_balance: u64,
+    reserve_pool_data: Option<&ReservePoolAccount>,
+    reserve_vault_balance: Option<u64>,
+) -> Result<VaultAuditReport, ProgramError> {
+    let (reserve_vault_balance, reserve_pool_balance) = match reserve_pool_data {
+        Some(reserve_pool_data) => {
+            if reserve_pool_data.mint_pubkey != mint_vault_registry_data.mint_pubkey {
+                return Err(Funds4GoodError::ReservePoolAccountDoesNotMatched.into());
+            }
+            let reserve_vault_balance =
+                reserve_vault_balance.ok_or(Funds4GoodError::ReserveVaultAccountDoesNotMatched)?;
+            (
+                Some(reserve_vault_balance),
+                Some(reserve_pool_data.pool_balance),
+            )
+        }
+        None => (None, None),
+    };
+
+    Ok(VaultAuditReport {
+        vault_balance,
+        vault_liabilities: mint_vault_registry_data.vault_liabilities,
+        ledger_liabilities: None,
+        reserve_vault_balance,
+        reserve_pool_balance,
+    })
+}
+
+// sums lenders free balances and funds lent to loans still in fundraising, undisbursed funds are only counted
+// for loans in loan_info_headers, so every loan of the mint still in fundraising has to be passed for the sum to be complete
+pub fn ledger_liabilities(
+    mint_pubkey: &Pubkey,
+    lenders_storage: &LendersStorageAccount,
+    loan_info_headers: &[LoanInfoAccDataHeader],
+) -> Result<u64, ProgramError> {
+    let mut ledger_liabilities = lenders_storage.total_free_balance()?;
+    for loan_info_header_data in loan_info_headers {
+        if loan_info_header_data.acc_type != AccTypes::LoanInfoAcc as u8 {
+            return Err(Funds4GoodError::ExpectedAccountTypeMismatched.into());
+        }
+        if loan_info_header_data.loan_mint_pubkey != *mint_pubkey {
+            return Err(Funds4GoodError::LoanMintDoesNotMatched.into());
+        }
+        if loan_info_header_data.loan_status == LoanStatus::Fundraising as u8 {
+            ledger_liabilities = ledger_liabilities
+                .checked_add(loan_info_header_data.total_amount_lended)
+                .ok_or(Funds4GoodError::AmountOverflow)?;
+        }
+    }
+
+    Ok(ledger_liabilities)
+}
+
+// off-chain equivalent of AuditVault over raw account data that also reconciles running total of registry
+// with the ledgers it summarizes, every loan info of the mint has to be passed once.
+// It returns the report even when there is a shortfall or drift, call verify on it to fail the same way AuditVault does.
+// reserve_pool_data and reserve_vault_data are None for a mint without reserve pool
+pub fn audit_vault_accounts<'a>(
+    vault_data: &[u8],
+    mint_vault_registry_data: &[u8],
+    lenders_storage_data: &mut [u8],
+    reserve_pool_data: Option<&[u8]>,
+    reserve_vault_data: Option<&[u8]>,
+    loan_infos_data: impl IntoIterator<Item = &'a mut [u8]>,
+) -> Result<VaultAuditReport, ProgramError> {
+    let mint_vault_registry_data = MintVaultRegistryAccount::unpack(mint_vault_registry_data)?;
+    if mint_vault_registry_data.acc_type != AccTypes::MintVaultRegistryAcc as u8 {
+        return Err(Funds4GoodError::ExpectedAccountTypeMismatched.into());
+    }
+
+    let reserve_pool_data = reserve_pool_data
+        .map(ReservePoolAccount::unpack)
+        .transpose()?;
+    if let Some(reserve_pool_data) = &reserve_pool_data {
+        if reserve_pool_data.acc_type != AccTypes::ReservePoolAcc as u8 {
+            return Err(Funds4GoodError::ExpectedAccountTypeMismatched.into());
+        }
+    }
+
+    let vault_data = TokenAccount::unpack(vault_data)?;
+    if vault_data.mint != mint_vault_registry_data.mint_pubkey {
+        return Err(Funds4GoodError::TokenAccountMintDoesNotMatched.into());
+    }
+    let reserve_vault_data = reserve_vault_data.map(TokenAccount::unpack).transpose()?;
+    if let Some(reserve_vault_data) = &reserve_vault_data {
+        if reserve_vault_data.mint != mint_vault_registry_data.mint_pubkey {
+            return Err(Funds4GoodError::TokenAccountMintDoesNotMatched.into());
+        }
+    }
+
+    let lenders_storage = LendersStorageAccount::new(lenders_storage_data)?;
+    let loan_info_headers = loan_infos_data
+        .into_iter()
+        .map(|loan_info_data| LoanInfoAccount::new(loan_info_data)?.header())
+        .collect::<Result<Vec<LoanInfoAccDataHeader>, ProgramError>>()?;
+
+    let mut vault_audit_report = audit_vault(
+        &mint_vault_registry_data,
+        vault_data.amount,
+        reserve_pool_data.as_ref(),
+        reserve_vault_data.map(|reserve_vault_data| reserve_vault_data.amount),
+    )?;
+    vault_audit_report.ledger_liabilities = Some(ledger_liabilities(
+        &mint_vault_registry_data.mint_pubkey,
+        &lenders_storage,
+        &loan_info_headers,
+    )?);
+
+    Ok(vault_audit_report)
+}
@@ -82,6 +82,129 @@ pub enum Funds4GoodError {
     ///Data Size Not Matched
     #[error("Data Size Does Not Matched")]
     DataSizeNotMatched,
+    /// Withdraw amount is zero
+    #[error("Invalid Withdraw Amount")]
+    InvalidWithdrawAmount,
+    /// Withdraw amount is more than lender free balance
+    #[error("Insufficient Free Balance")]
+    InsufficientFreeBalance,
+    /// Lending amount is not a multiple of 10 Funds4GoodCoin
+    #[error("Lending Amount Not In Allowed Multiple")]
+    LendingAmountNotInAllowedMultiple,
+    /// No free slot left to store lender data in loan info account
+    #[error("Loan Lenders Capacity Exceeded")]
+    LoanLendersCapacityExceeded,
+    /// No free slot left to store repayment data in loan info account
+    #[error("Loan Repayments Capacity Exceeded")]
+    LoanRepaymentsCapacityExceeded,
+    /// Loan amount is out of allowed range or not in allowed multiple
+    #[error("Invalid Loan Amount")]
+    InvalidLoanAmount,
+    /// Number of emis is zero or more than repayment slots
+    #[error("Invalid Number Of Emis")]
+    InvalidNumberOfEmis,
+    /// Passed config account is not the Funds4Good config PDA
+    #[error("Config Account Does Not Matched")]
+    ConfigAccountDoesNotMatched,
+    /// Vault address is already recorded in config account
+    #[error("Vault Already Recorded")]
+    VaultAlreadyRecorded,
+    /// Token account mint is not the recorded Funds4Good coin mint
+    #[error("Token Account Mint Does Not Matched")]
+    TokenAccountMintDoesNotMatched,
+    /// Airdrop Vault Account Does Not Matched
+    #[error("Airdrop Vault Account Does Not Matched")]
+    AirdropVaultAccountDoesNotMatched,
+    /// Token account is not owned by token program
+    #[error("Invalid Token Account")]
+    InvalidTokenAccount,
+    /// Token account owner is not the user main account
+    #[error("Token Account Owner Does Not Matched")]
+    TokenAccountOwnerDoesNotMatched,
+    /// Passed registry account is not the vault registry PDA of its mint
+    #[error("Mint Vault Registry Account Does Not Matched")]
+    MintVaultRegistryAccountDoesNotMatched,
+    /// Lenders storage account is not the one registered for the mint
+    #[error("Lenders Storage Account Does Not Matched")]
+    LendersStorageAccountDoesNotMatched,
+    /// Loan is denominated in a different mint
+    #[error("Loan Mint Does Not Matched")]
+    LoanMintDoesNotMatched,
+    /// Mint decimals are too large to represent loan amounts
+    #[error("Unsupported Mint Decimals")]
+    UnsupportedMintDecimals,
+    /// Fee basis points are more than allowed maximum
+    #[error("Invalid Fee Bps")]
+    InvalidFeeBps,
+    /// Treasury account is not registered for the mint
+    #[error("Treasury Account Not Registered")]
+    TreasuryAccountNotRegistered,
+    /// Treasury Account Does Not Matched
+    #[error("Treasury Account Does Not Matched")]
+    TreasuryAccountDoesNotMatched,
+    /// Passed reserve pool account is not the reserve pool PDA of loan mint
+    #[error("Reserve Pool Account Does Not Matched")]
+    ReservePoolAccountDoesNotMatched,
+    /// Reserve Vault Account Does Not Matched
+    #[error("Reserve Vault Account Does Not Matched")]
+    ReserveVaultAccountDoesNotMatched,
+    /// Loan is not in the status required by instruction
+    #[error("Loan Status Does Not Matched")]
+    LoanStatusDoesNotMatched,
+    /// Loan can't be marked defaulted before last emi date and grace period are over
+    #[error("Loan Not Yet Defaulted")]
+    LoanNotYetDefaulted,
+    /// Payoff amount is more than maximum amount borrower agreed to pay
+    #[error("Payoff Amount Exceeds Maximum")]
+    PayoffAmountExceedsMaximum,
+    /// Loan Amendments Capacity Exceeded
+    #[error("Loan Amendments Capacity Exceeded")]
+    LoanAmendmentsCapacityExceeded,
+    /// Guarantor can only pay an installment which is overdue
+    #[error("Loan Installment Not Overdue")]
+    LoanInstallmentNotOverdue,
+    /// Amount is zero or more than what borrower owes guarantor
+    #[error("Invalid Guarantor Claim Amount")]
+    InvalidGuarantorClaimAmount,
+    /// Invalid Min Funding Amount
+    #[error("Invalid Min Funding Amount")]
+    InvalidMinFundingAmount,
+    /// Account layout version is newer than this program understands
+    #[error("Unsupported Layout Version")]
+    UnsupportedLayoutVersion,
+    /// Account is already on the current layout version
+    #[error("Account Already Migrated")]
+    AccountAlreadyMigrated,
+    /// Subtraction of amounts would go below zero
+    #[error("Amount Underflow")]
+    AmountUnderflow,
+    /// Loan has no lenders to distribute an amount to
+    #[error("Loan Has No Lenders")]
+    LoanHasNoLenders,
+    /// Vault holds less than lenders free balances and undisbursed loan funds
+    #[error("Vault Balance Below Liabilities")]
+    VaultBalanceBelowLiabilities,
+    /// Reserve vault holds less than pool balance recorded in reserve pool
+    #[error("Reserve Vault Balance Below Pool Balance")]
+    ReserveVaultBalanceBelowPoolBalance,
+    /// Same account was passed more than once
+    #[error("Duplicate Account Passed")]
+    DuplicateAccountPassed,
+    /// Log data is not an event of the current schema
+    #[error("Invalid Event Data")]
+    InvalidEventData,
+    /// Program Data Account Does Not Matched
+    #[error("Program Data Account Does Not Matched")]
+    ProgramDataAccountDoesNotMatched,
+    /// Only upgrade authority of the program can create config and become admin
+    #[error("Upgrade Authority Does Not Matched")]
+    UpgradeAuthorityDoesNotMatched,
+    /// Loan was cancelled by borrower before disbursement
+    #[error("Loan Cancelled")]
+    LoanCancelled,
+    /// Vault liabilities recorded in registry differ from lenders free balances and undisbursed loan funds
+    #[error("Vault Liabilities Do Not Match Ledgers")]
+    VaultLiabilitiesDoNotMatchLedgers,
 }
 
 impl From<Funds4GoodError> for ProgramError {
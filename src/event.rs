@@ -0,0 +1,152 @@
+use crate::error::Funds4GoodError::InvalidEventData;
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{log::sol_log_data, program_error::ProgramError, pubkey::Pubkey};
+
+// every event is logged with sol_log_data as one field [EVENT_SCHEMA_VERSION, borsh encoded Funds4GoodEvent],
+// borsh encodes variant index as first byte, so variants must stay in this order and new ones only go at the end,
+// fields of an existing event only change together with EVENT_SCHEMA_VERSION
+pub const EVENT_SCHEMA_VERSION: u8 = 1;
+// runtime logs every sol_log_data call as this prefix followed by its base64 encoded fields
+pub const PROGRAM_DATA_LOG_PREFIX: &str = "Program data: ";
+// runtime logs every msg! call as this prefix followed by the message
+pub const PROGRAM_LOG_PREFIX: &str = "Program log: ";
+
+// amounts are in base units of loan mint, timestamps are unix timestamps
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub enum Funds4GoodEvent {
+    // 0
+    LoanCreated {
+        loan_info_pubkey: Pubkey,
+        borrower_pubkey: Pubkey,
+        guarantor_pubkey: Pubkey,
+        mint_pubkey: Pubkey,
+        total_loan_amount: u64,
+        min_funding_amount: u64,
+        number_of_emis: u8,
+        fundraising_period_ending_timestamp: u64,
+        first_repayment_last_date_timestamp: u64,
+    },
+    // 1
+    // excess_amount is what lender sent above remaining loan amount, it is credited to lender free balance
+    Lent {
+        loan_info_pubkey: Pubkey,
+        lender_pubkey: Pubkey,
+        lender_id: u32,
+        lent_amount: u64,
+        excess_amount: u64,
+        total_amount_lended: u64,
+    },
+    // 2
+    Disbursed {
+        loan_info_pubkey: Pubkey,
+        borrower_pubkey: Pubkey,
+        disbursed_amount: u64,
+        origination_fee_amount: u64,
+    },
+    // 3
+    // payer is borrower, guarantor or whoever paid on behalf of borrower,
+    // a payoff is logged as one with whole payoff amount as installment_amount and loan_status Repaid
+    EmiPaid {
+        loan_info_pubkey: Pubkey,
+        payer_pubkey: Pubkey,
+        installment_amount: u64,
+        late_fee_amount: u64,
+        repaid_amount_by_borrower: u64,
+        is_on_time: bool,
+        loan_status: u8,
+    },
+    // 4
+    Withdrawn {
+        lender_pubkey: Pubkey,
+        lender_id: u32,
+        amount: u64,
+        free_balance_after_withdrawal: u64,
+    },
+    // 5
+    // one event for every lender refunded to free balance when a loan is cancelled
+    Refunded {
+        loan_info_pubkey: Pubkey,
+        lender_id: u32,
+        amount: u64,
+    },
+    // 6
+    Defaulted {
+        loan_info_pubkey: Pubkey,
+        shortfall_amount: u64,
+        covered_amount: u64,
+    },
+    // 7
+    Airdropped {
+        user_pubkey: Pubkey,
+        amount: u64,
+        total_airdropped_amount: u64,
+    },
+}
+
+impl Funds4GoodEvent {
+    pub fn pack(&self) -> Vec<u8> {
+        let mut buf = vec![EVENT_SCHEMA_VERSION];
+        // writing to a Vec can't fail
+        self.serialize(&mut buf).unwrap();
+        buf
+    }
+
+    pub fn unpack(input: &[u8]) -> Result<Self, ProgramError> {
+        let (schema_version, event_data) = input.split_first().ok_or(InvalidEventData)?;
+
+        if *schema_version != EVENT_SCHEMA_VERSION {
+            return Err(InvalidEventData.into());
+        }
+
+        Self::try_from_slice(event_data).map_err(|_| InvalidEventData.into())
+    }
+
+    pub fn emit(&self) {
+        sol_log_data(&[&self.pack()]);
+    }
+}
+
+// decodes events of program_id from log messages of one transaction, "Program data" lines logged while
+// another program is executing are skipped, so events logged by other programs in the same transaction are never decoded
+pub fn decode_events_from_logs<S: AsRef<str>>(
+    program_id: &Pubkey,
+    log_messages: &[S],
+) -> Result<Vec<Funds4GoodEvent>, ProgramError> {
+    let program_id = program_id.to_string();
+    let mut invoked_programs: Vec<&str> = vec![];
+    let mut events = vec![];
+
+    for log_message in log_messages {
+        let log_message = log_message.as_ref();
+
+        if let Some(encoded_event) = log_message.strip_prefix(PROGRAM_DATA_LOG_PREFIX) {
+            if invoked_programs.last() == Some(&program_id.as_str()) {
+                let event_data = base64::decode(encoded_event).map_err(|_| InvalidEventData)?;
+                events.push(Funds4GoodEvent::unpack(&event_data)?);
+            }
+            continue;
+        }
+
+        // message of a program log can start with anything, including words of the runtime lines below
+        if log_message.starts_with(PROGRAM_LOG_PREFIX) {
+            continue;
+        }
+
+        // runtime logs "Program <id> invoke [<depth>]" when a program starts and "Program <id> success"
+        // or "Program <id> failed: <error>" when it returns
+        let mut words = log_message.split(' ');
+        if let (Some("Program"), Some(invoked_program_id), Some(status)) =
+            (words.next(), words.next(), words.next())
+        {
+            match status {
+                "invoke" => invoked_programs.push(invoked_program_id),
+                "success" | "failed:" => {
+                    invoked_programs.pop();
+                }
+                _ => {}
+            }
+        }
+    }
+
+    Ok(events)
+}
@@ -1,91 +1,1143 @@
 use crate::error::Funds4GoodError::InvalidInstruction;
-use solana_program::msg;
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::bpf_loader_upgradeable;
+use solana_program::instruction::{AccountMeta, Instruction};
 use solana_program::program_error::ProgramError;
-use std::convert::TryInto;
+use solana_program::pubkey::Pubkey;
+use solana_program::system_program;
 
+// every instruction is sent as [INSTRUCTION_WIRE_VERSION, borsh encoded Funds4GoodInstruction],
+// borsh encodes variant index as first byte, so variants must stay in this order and new ones only go at the end
+pub const INSTRUCTION_WIRE_VERSION: u8 = 1;
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
 pub enum Funds4GoodInstruction {
+    // 0
     LendToBorrower {
         amount_to_lend_input: u64,
         lender_id_input: u32,
     },
-
-    WithdrawLenderFreeWalletFunds { lender_id_input: u32 },
+    // 1
+    WithdrawLenderFreeWalletFunds {
+        lender_id_input: u32,
+        amount_to_withdraw_input: u64,
+    },
+    // 2
     WithdrawCollectedLoanFunds {},
+    // 3
     TransferFunds4GoodVaultAccountOwnership {},
+    // 4
     InitializeLendersStorageAccount {},
-    InitializeGuarantorAccount {},
+    // 5
     InitializeBorrowerAccount {},
+    // 6
+    InitializeGuarantorAccount {},
+    // 7
     PayEMIforLoan { emi_amount_to_pay_input: u64 },
+    // 8
     InitializeLoanInfoAccount {
         num_days_left_for_first_repayment_input: u16,
         num_emis_needed_to_repay_the_loan_input: u16,
         num_days_for_fundraising_input: u16,
         total_loan_amount_input: u64,
+        min_funding_amount_input: u64,
     },
+    // 9
     AirdropUsersWithFunds4GoodTestCoins {},
+    // 10
     TransferAirdropVaultAccountOwnership {},
+    // 11
     ReturnFundsToLenders { num_accounts_input: u16 },
+    // 12
     CloseLoanInfoAccount {},
+    // 13
+    SetPlatformFees {
+        origination_fee_bps_input: u16,
+        servicing_fee_bps_input: u16,
+    },
+    // 14
+    TransferTreasuryAccountOwnership {},
+    // 15
+    WithdrawTreasury { amount_to_withdraw_input: u64 },
+    // 16
+    ConfigureReservePool { reserve_fee_bps_input: u16 },
+    // 17
+    MarkLoanDefaulted {},
+    // 18
+    SetLateFees {
+        late_fee_flat_amount_input: u64,
+        late_fee_daily_bps_input: u16,
+        late_fee_lenders_share_bps_input: u16,
+    },
+    // 19
+    PayOffLoan { max_amount_to_pay_input: u64 },
+    // 20
+    SetPrepaymentDiscount { prepayment_discount_bps_input: u16 },
+    // 21
+    RestructureLoan {
+        new_number_of_emis_input: u16,
+        num_days_left_for_next_repayment_input: u16,
+    },
+    // 22
+    PayEMIOnBehalf { emi_amount_to_pay_input: u64 },
+    // 23
+    GuarantorPayOverdueEMI { emi_amount_to_pay_input: u64 },
+    // 24
+    RepayGuarantor { amount_to_repay_input: u64 },
+    // 25
+    CancelLoan {},
+    // 26
+    MigrateAccount {},
+    // 27
+    AuditVault {},
+    // 28
+    SetInterestRate { interest_rate_bps_input: u16 },
 }
 
 impl Funds4GoodInstruction {
     pub fn unpack(input: &[u8]) -> Result<Self, ProgramError> {
-        msg!("In {}", input.len());
-
-        Ok(match input[0] {
-            0 => Self::LendToBorrower {
-                amount_to_lend_input: Self::unpack_to_u64(&input[1..9])?,
-                lender_id_input: Self::unpack_to_u32(&input[9..13]),
-            },
-            1 => Self::WithdrawLenderFreeWalletFunds {
-                lender_id_input: Self::unpack_to_u32(&input[1..5]),
-            },
-            2 => Self::WithdrawCollectedLoanFunds {},
-            3 => Self::TransferFunds4GoodVaultAccountOwnership {},
-            4 => Self::InitializeLendersStorageAccount {},
-            5 => Self::InitializeBorrowerAccount {},
-            6 => Self::InitializeGuarantorAccount {},
-            7 => Self::PayEMIforLoan {
-                emi_amount_to_pay_input: Self::unpack_to_u64(&input[1..9])?,
-            },
-            8 => Self::InitializeLoanInfoAccount {
-                num_days_left_for_first_repayment_input: Self::unpack_to_u16(&input[1..3]),
-                num_emis_needed_to_repay_the_loan_input: Self::unpack_to_u16(&input[3..5]),
-                num_days_for_fundraising_input: Self::unpack_to_u16(&input[5..7]),
-                total_loan_amount_input: Self::unpack_to_u64(&input[7..15])?,
-            },
-            9 => Self::AirdropUsersWithFunds4Good
-    TestCoins {},
-            10 => Self::TransferAirdropVaultAccountOwnership {},
-            11 => Self::ReturnFundsToLenders {
-                num_accounts_input: Self::unpack_to_u16(&input[1..3]),
-            },
-            12 => Self::CloseLoanInfoAccount {},
-            _ => return Err(InvalidInstruction.into()),
-        })
-    }
-
-    fn unpack_to_u64(input: &[u8]) -> Result<u64, ProgramError> {
-        msg!("in unpack");
-        let value = input
-            .get(..8)
-            .and_then(|slice| slice.try_into().ok())
-            .map(u64::from_le_bytes)
-            .ok_or(InvalidInstruction)?;
-        msg!("unpack value {}", value);
-        Ok(value)
-    }
-
-    fn unpack_to_u16(input: &[u8]) -> u16 {
-        (input[0] as u16) | (input[1] as u16) << 8
-    }
-
-    pub fn unpack_to_u32(input: &[u8]) -> u32 {
-        let amount = (input[0] as u32)
-            | (input[1] as u32) << 8
-            | (input[2] as u32) << 16
-            | (input[3] as u32) << 24;
-        msg!("u32 unpack amount {}", amount);
-        return amount;
+        let (version, instruction_data) = input.split_first().ok_or(InvalidInstruction)?;
+
+        if *version != INSTRUCTION_WIRE_VERSION {
+            return Err(InvalidInstruction.into());
+        }
+
+        Self::try_from_slice(instruction_data).map_err(|_| InvalidInstruction.into())
+    }
+
+    pub fn pack(&self) -> Vec<u8> {
+        let mut buf = vec![INSTRUCTION_WIRE_VERSION];
+        // writing to a Vec can't fail
+        self.serialize(&mut buf).unwrap();
+        buf
+    }
+}
+
+// builders below list accounts in the exact order the processor reads them with next_account_info,
+// every pda (vault authority, config, registry, reserve pool) is passed by the caller, derive them with find_program_address
+
+/// Creates a `LendToBorrower` instruction.
+#[allow(clippy::too_many_arguments)]
+pub fn lend_to_borrower(
+    program_id: &Pubkey,
+    lender_pubkey: &Pubkey,
+    lender_token_account_pubkey: &Pubkey,
+    vault_pubkey: &Pubkey,
+    mint_vault_registry_pubkey: &Pubkey,
+    loan_info_pubkey: &Pubkey,
+    lenders_storage_pubkey: &Pubkey,
+    amount_to_lend: u64,
+    lender_id: u32,
+) -> Instruction {
+    let data = Funds4GoodInstruction::LendToBorrower {
+        amount_to_lend_input: amount_to_lend,
+        lender_id_input: lender_id,
+    }
+    .pack();
+
+    let accounts = vec![
+        AccountMeta::new_readonly(*lender_pubkey, true),
+        AccountMeta::new(*lender_token_account_pubkey, false),
+        AccountMeta::new(*vault_pubkey, false),
+        AccountMeta::new(*mint_vault_registry_pubkey, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new(*loan_info_pubkey, false),
+        AccountMeta::new(*lenders_storage_pubkey, false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Creates a `WithdrawLenderFreeWalletFunds` instruction.
+/// The withdrawal is only recorded in `lender_withdrawal_history_pubkey` once the lender has created that account, it is always passed here.
+#[allow(clippy::too_many_arguments)]
+pub fn withdraw_lender_free_wallet_funds(
+    program_id: &Pubkey,
+    lender_pubkey: &Pubkey,
+    lender_token_account_pubkey: &Pubkey,
+    vault_pubkey: &Pubkey,
+    mint_vault_registry_pubkey: &Pubkey,
+    lenders_storage_pubkey: &Pubkey,
+    vault_authority_pubkey: &Pubkey,
+    lender_withdrawal_history_pubkey: &Pubkey,
+    lender_id: u32,
+    amount_to_withdraw: u64,
+) -> Instruction {
+    let data = Funds4GoodInstruction::WithdrawLenderFreeWalletFunds {
+        lender_id_input: lender_id,
+        amount_to_withdraw_input: amount_to_withdraw,
+    }
+    .pack();
+
+    let accounts = vec![
+        AccountMeta::new_readonly(*lender_pubkey, true),
+        AccountMeta::new(*lender_token_account_pubkey, false),
+        AccountMeta::new(*vault_pubkey, false),
+        AccountMeta::new(*mint_vault_registry_pubkey, false),
+        AccountMeta::new(*lenders_storage_pubkey, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new_readonly(*vault_authority_pubkey, false),
+        AccountMeta::new(*lender_withdrawal_history_pubkey, false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Creates a `WithdrawCollectedLoanFunds` instruction.
+/// `treasury_pubkey` is only read by the program when an origination fee is charged, it is always passed here.
+/// The loan is covered by the reserve pool only if `reserve_pool_pubkey` is configured at disbursement.
+#[allow(clippy::too_many_arguments)]
+pub fn withdraw_collected_loan_funds(
+    program_id: &Pubkey,
+    borrower_pubkey: &Pubkey,
+    borrower_token_account_pubkey: &Pubkey,
+    vault_pubkey: &Pubkey,
+    mint_vault_registry_pubkey: &Pubkey,
+    loan_info_pubkey: &Pubkey,
+    vault_authority_pubkey: &Pubkey,
+    reserve_pool_pubkey: &Pubkey,
+    treasury_pubkey: &Pubkey,
+) -> Instruction {
+    let data = Funds4GoodInstruction::WithdrawCollectedLoanFunds {}.pack();
+
+    let accounts = vec![
+        AccountMeta::new_readonly(*borrower_pubkey, true),
+        AccountMeta::new(*borrower_token_account_pubkey, false),
+        AccountMeta::new(*vault_pubkey, false),
+        AccountMeta::new(*mint_vault_registry_pubkey, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new(*loan_info_pubkey, false),
+        AccountMeta::new_readonly(*vault_authority_pubkey, false),
+        AccountMeta::new(*reserve_pool_pubkey, false),
+        AccountMeta::new(*treasury_pubkey, false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Creates a `TransferFunds4GoodVaultAccountOwnership` instruction.
+/// The initializer pays for the config and registry pdas when they don't exist yet,
+/// creating config needs the initializer to be upgrade authority of the program.
+/// Program data account of the program is only read when config is created, it is always passed here.
+#[allow(clippy::too_many_arguments)]
+pub fn transfer_Funds4Good_vault_account_ownership(
+    program_id: &Pubkey,
+    initializer_pubkey: &Pubkey,
+    vault_pubkey: &Pubkey,
+    config_pubkey: &Pubkey,
+    mint_pubkey: &Pubkey,
+    mint_vault_registry_pubkey: &Pubkey,
+    lenders_storage_pubkey: &Pubkey,
+) -> Instruction {
+    let data = Funds4GoodInstruction::TransferFunds4GoodVaultAccountOwnership {}.pack();
+
+    let accounts = vec![
+        AccountMeta::new(*initializer_pubkey, true),
+        AccountMeta::new(*vault_pubkey, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new(*config_pubkey, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        // program data account holds upgrade authority of the program
+        AccountMeta::new_readonly(
+            Pubkey::find_program_address(&[program_id.as_ref()], &bpf_loader_upgradeable::id()).0,
+            false,
+        ),
+        AccountMeta::new_readonly(*mint_pubkey, false),
+        AccountMeta::new(*mint_vault_registry_pubkey, false),
+        AccountMeta::new_readonly(*lenders_storage_pubkey, false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Creates an `InitializeLendersStorageAccount` instruction.
+pub fn initialize_lenders_storage_account(
+    program_id: &Pubkey,
+    initializer_pubkey: &Pubkey,
+    lenders_storage_pubkey: &Pubkey,
+) -> Instruction {
+    let data = Funds4GoodInstruction::InitializeLendersStorageAccount {}.pack();
+
+    let accounts = vec![
+        AccountMeta::new_readonly(*initializer_pubkey, true),
+        AccountMeta::new(*lenders_storage_pubkey, false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Creates an `InitializeBorrowerAccount` instruction.
+pub fn initialize_borrower_account(
+    program_id: &Pubkey,
+    borrower_pubkey: &Pubkey,
+    borrower_storage_pubkey: &Pubkey,
+) -> Instruction {
+    let data = Funds4GoodInstruction::InitializeBorrowerAccount {}.pack();
+
+    let accounts = vec![
+        AccountMeta::new_readonly(*borrower_pubkey, true),
+        AccountMeta::new(*borrower_storage_pubkey, false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Creates an `InitializeGuarantorAccount` instruction.
+pub fn initialize_guarantor_account(
+    program_id: &Pubkey,
+    guarantor_pubkey: &Pubkey,
+    guarantor_storage_pubkey: &Pubkey,
+) -> Instruction {
+    let data = Funds4GoodInstruction::InitializeGuarantorAccount {}.pack();
+
+    let accounts = vec![
+        AccountMeta::new_readonly(*guarantor_pubkey, true),
+        AccountMeta::new(*guarantor_storage_pubkey, false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+// PayEMIforLoan, PayEMIOnBehalf and GuarantorPayOverdueEMI share process_pay_emi and so the same accounts,
+// only who has to sign as payer differs. Reserve accounts are not read for a loan not covered by reserve pool
+#[allow(clippy::too_many_arguments)]
+fn pay_emi_accounts(
+    payer_pubkey: &Pubkey,
+    payer_token_account_pubkey: &Pubkey,
+    vault_pubkey: &Pubkey,
+    mint_vault_registry_pubkey: &Pubkey,
+    borrower_storage_pubkey: &Pubkey,
+    reserve_pool_pubkey: &Pubkey,
+    reserve_vault_pubkey: &Pubkey,
+    loan_info_pubkey: &Pubkey,
+    lenders_storage_pubkey: &Pubkey,
+    treasury_pubkey: &Pubkey,
+) -> Vec<AccountMeta> {
+    vec![
+        AccountMeta::new_readonly(*payer_pubkey, true),
+        AccountMeta::new(*payer_token_account_pubkey, false),
+        AccountMeta::new(*vault_pubkey, false),
+        AccountMeta::new(*mint_vault_registry_pubkey, false),
+        AccountMeta::new(*borrower_storage_pubkey, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new(*reserve_pool_pubkey, false),
+        AccountMeta::new(*reserve_vault_pubkey, false),
+        AccountMeta::new(*loan_info_pubkey, false),
+        AccountMeta::new(*lenders_storage_pubkey, false),
+        AccountMeta::new(*treasury_pubkey, false),
+    ]
+}
+
+/// Creates a `PayEMIforLoan` instruction, `borrower_pubkey` must be the borrower of the loan.
+#[allow(clippy::too_many_arguments)]
+pub fn pay_emi_for_loan(
+    program_id: &Pubkey,
+    borrower_pubkey: &Pubkey,
+    borrower_token_account_pubkey: &Pubkey,
+    vault_pubkey: &Pubkey,
+    mint_vault_registry_pubkey: &Pubkey,
+    borrower_storage_pubkey: &Pubkey,
+    reserve_pool_pubkey: &Pubkey,
+    reserve_vault_pubkey: &Pubkey,
+    loan_info_pubkey: &Pubkey,
+    lenders_storage_pubkey: &Pubkey,
+    treasury_pubkey: &Pubkey,
+    emi_amount_to_pay: u64,
+) -> Instruction {
+    let data = Funds4GoodInstruction::PayEMIforLoan {
+        emi_amount_to_pay_input: emi_amount_to_pay,
+    }
+    .pack();
+
+    Instruction {
+        program_id: *program_id,
+        accounts: pay_emi_accounts(
+            borrower_pubkey,
+            borrower_token_account_pubkey,
+            vault_pubkey,
+            mint_vault_registry_pubkey,
+            borrower_storage_pubkey,
+            reserve_pool_pubkey,
+            reserve_vault_pubkey,
+            loan_info_pubkey,
+            lenders_storage_pubkey,
+            treasury_pubkey,
+        ),
+        data,
+    }
+}
+
+/// Creates a `PayEMIOnBehalf` instruction, any `payer_pubkey` can pay for the borrower.
+#[allow(clippy::too_many_arguments)]
+pub fn pay_emi_on_behalf(
+    program_id: &Pubkey,
+    payer_pubkey: &Pubkey,
+    payer_token_account_pubkey: &Pubkey,
+    vault_pubkey: &Pubkey,
+    mint_vault_registry_pubkey: &Pubkey,
+    borrower_storage_pubkey: &Pubkey,
+    reserve_pool_pubkey: &Pubkey,
+    reserve_vault_pubkey: &Pubkey,
+    loan_info_pubkey: &Pubkey,
+    lenders_storage_pubkey: &Pubkey,
+    treasury_pubkey: &Pubkey,
+    emi_amount_to_pay: u64,
+) -> Instruction {
+    let data = Funds4GoodInstruction::PayEMIOnBehalf {
+        emi_amount_to_pay_input: emi_amount_to_pay,
+    }
+    .pack();
+
+    Instruction {
+        program_id: *program_id,
+        accounts: pay_emi_accounts(
+            payer_pubkey,
+            payer_token_account_pubkey,
+            vault_pubkey,
+            mint_vault_registry_pubkey,
+            borrower_storage_pubkey,
+            reserve_pool_pubkey,
+            reserve_vault_pubkey,
+            loan_info_pubkey,
+            lenders_storage_pubkey,
+            treasury_pubkey,
+        ),
+        data,
+    }
+}
+
+/// Creates a `GuarantorPayOverdueEMI` instruction, `guarantor_pubkey` must be the guarantor of the loan.
+#[allow(clippy::too_many_arguments)]
+pub fn guarantor_pay_overdue_emi(
+    program_id: &Pubkey,
+    guarantor_pubkey: &Pubkey,
+    guarantor_token_account_pubkey: &Pubkey,
+    vault_pubkey: &Pubkey,
+    mint_vault_registry_pubkey: &Pubkey,
+    borrower_storage_pubkey: &Pubkey,
+    reserve_pool_pubkey: &Pubkey,
+    reserve_vault_pubkey: &Pubkey,
+    loan_info_pubkey: &Pubkey,
+    lenders_storage_pubkey: &Pubkey,
+    treasury_pubkey: &Pubkey,
+    emi_amount_to_pay: u64,
+) -> Instruction {
+    let data = Funds4GoodInstruction::GuarantorPayOverdueEMI {
+        emi_amount_to_pay_input: emi_amount_to_pay,
+    }
+    .pack();
+
+    Instruction {
+        program_id: *program_id,
+        accounts: pay_emi_accounts(
+            guarantor_pubkey,
+            guarantor_token_account_pubkey,
+            vault_pubkey,
+            mint_vault_registry_pubkey,
+            borrower_storage_pubkey,
+            reserve_pool_pubkey,
+            reserve_vault_pubkey,
+            loan_info_pubkey,
+            lenders_storage_pubkey,
+            treasury_pubkey,
+        ),
+        data,
+    }
+}
+
+/// Creates an `InitializeLoanInfoAccount` instruction.
+/// `min_funding_amount` of 0 means the loan has to be fully funded.
+#[allow(clippy::too_many_arguments)]
+pub fn initialize_loan_info_account(
+    program_id: &Pubkey,
+    guarantor_pubkey: &Pubkey,
+    borrower_pubkey: &Pubkey,
+    mint_vault_registry_pubkey: &Pubkey,
+    loan_info_pubkey: &Pubkey,
+    borrower_storage_pubkey: &Pubkey,
+    num_days_left_for_first_repayment: u16,
+    num_emis_needed_to_repay_the_loan: u16,
+    num_days_for_fundraising: u16,
+    total_loan_amount: u64,
+    min_funding_amount: u64,
+) -> Instruction {
+    let data = Funds4GoodInstruction::InitializeLoanInfoAccount {
+        num_days_left_for_first_repayment_input: num_days_left_for_first_repayment,
+        num_emis_needed_to_repay_the_loan_input: num_emis_needed_to_repay_the_loan,
+        num_days_for_fundraising_input: num_days_for_fundraising,
+        total_loan_amount_input: total_loan_amount,
+        min_funding_amount_input: min_funding_amount,
+    }
+    .pack();
+
+    let accounts = vec![
+        AccountMeta::new_readonly(*guarantor_pubkey, true),
+        AccountMeta::new_readonly(*borrower_pubkey, false),
+        AccountMeta::new_readonly(*mint_vault_registry_pubkey, false),
+        AccountMeta::new(*loan_info_pubkey, false),
+        AccountMeta::new(*borrower_storage_pubkey, false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Creates an `AirdropUsersWithFunds4GoodTestCoins` instruction.
+pub fn airdrop_users_with_Funds4Good_test_coins(
+    program_id: &Pubkey,
+    user_pubkey: &Pubkey,
+    airdrop_user_storage_pubkey: &Pubkey,
+    user_token_account_pubkey: &Pubkey,
+    airdrop_vault_pubkey: &Pubkey,
+    config_pubkey: &Pubkey,
+    airdrop_vault_authority_pubkey: &Pubkey,
+) -> Instruction {
+    let data = Funds4GoodInstruction::AirdropUsersWithFunds4GoodTestCoins {}.pack();
+
+    let accounts = vec![
+        AccountMeta::new_readonly(*user_pubkey, true),
+        AccountMeta::new(*airdrop_user_storage_pubkey, false),
+        AccountMeta::new(*user_token_account_pubkey, false),
+        AccountMeta::new(*airdrop_vault_pubkey, false),
+        AccountMeta::new_readonly(*config_pubkey, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new_readonly(*airdrop_vault_authority_pubkey, false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Creates a `TransferAirdropVaultAccountOwnership` instruction.
+pub fn transfer_airdrop_vault_account_ownership(
+    program_id: &Pubkey,
+    initializer_pubkey: &Pubkey,
+    airdrop_vault_pubkey: &Pubkey,
+    config_pubkey: &Pubkey,
+) -> Instruction {
+    let data = Funds4GoodInstruction::TransferAirdropVaultAccountOwnership {}.pack();
+
+    let accounts = vec![
+        AccountMeta::new_readonly(*initializer_pubkey, true),
+        AccountMeta::new(*airdrop_vault_pubkey, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new(*config_pubkey, false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Creates a `ReturnFundsToLenders` instruction.
+/// It is not supported and always fails with `InvalidInstruction`, lenders get their funds back through `CancelLoan`.
+pub fn return_funds_to_lenders(program_id: &Pubkey, num_accounts: u16) -> Instruction {
+    let data = Funds4GoodInstruction::ReturnFundsToLenders {
+        num_accounts_input: num_accounts,
+    }
+    .pack();
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![],
+        data,
+    }
+}
+
+/// Creates a `CloseLoanInfoAccount` instruction.
+/// It is not supported yet and always fails with `InvalidInstruction`.
+pub fn close_loan_info_account(program_id: &Pubkey) -> Instruction {
+    let data = Funds4GoodInstruction::CloseLoanInfoAccount {}.pack();
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![],
+        data,
+    }
+}
+
+/// Creates a `SetPlatformFees` instruction.
+pub fn set_platform_fees(
+    program_id: &Pubkey,
+    admin_pubkey: &Pubkey,
+    config_pubkey: &Pubkey,
+    mint_vault_registry_pubkey: &Pubkey,
+    origination_fee_bps: u16,
+    servicing_fee_bps: u16,
+) -> Instruction {
+    let data = Funds4GoodInstruction::SetPlatformFees {
+        origination_fee_bps_input: origination_fee_bps,
+        servicing_fee_bps_input: servicing_fee_bps,
+    }
+    .pack();
+
+    let accounts = vec![
+        AccountMeta::new_readonly(*admin_pubkey, true),
+        AccountMeta::new_readonly(*config_pubkey, false),
+        AccountMeta::new(*mint_vault_registry_pubkey, false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Creates a `TransferTreasuryAccountOwnership` instruction.
+pub fn transfer_treasury_account_ownership(
+    program_id: &Pubkey,
+    admin_pubkey: &Pubkey,
+    treasury_pubkey: &Pubkey,
+    config_pubkey: &Pubkey,
+    mint_vault_registry_pubkey: &Pubkey,
+) -> Instruction {
+    let data = Funds4GoodInstruction::TransferTreasuryAccountOwnership {}.pack();
+
+    let accounts = vec![
+        AccountMeta::new_readonly(*admin_pubkey, true),
+        AccountMeta::new(*treasury_pubkey, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new_readonly(*config_pubkey, false),
+        AccountMeta::new(*mint_vault_registry_pubkey, false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Creates a `WithdrawTreasury` instruction.
+#[allow(clippy::too_many_arguments)]
+pub fn withdraw_treasury(
+    program_id: &Pubkey,
+    admin_pubkey: &Pubkey,
+    treasury_pubkey: &Pubkey,
+    admin_token_account_pubkey: &Pubkey,
+    config_pubkey: &Pubkey,
+    mint_vault_registry_pubkey: &Pubkey,
+    treasury_authority_pubkey: &Pubkey,
+    amount_to_withdraw: u64,
+) -> Instruction {
+    let data = Funds4GoodInstruction::WithdrawTreasury {
+        amount_to_withdraw_input: amount_to_withdraw,
+    }
+    .pack();
+
+    let accounts = vec![
+        AccountMeta::new_readonly(*admin_pubkey, true),
+        AccountMeta::new(*treasury_pubkey, false),
+        AccountMeta::new(*admin_token_account_pubkey, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new_readonly(*config_pubkey, false),
+        AccountMeta::new_readonly(*mint_vault_registry_pubkey, false),
+        AccountMeta::new_readonly(*treasury_authority_pubkey, false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Creates a `ConfigureReservePool` instruction.
+/// The admin pays for the reserve pool pda the first time it is configured for a mint.
+pub fn configure_reserve_pool(
+    program_id: &Pubkey,
+    admin_pubkey: &Pubkey,
+    config_pubkey: &Pubkey,
+    mint_vault_registry_pubkey: &Pubkey,
+    reserve_pool_pubkey: &Pubkey,
+    reserve_vault_pubkey: &Pubkey,
+    reserve_fee_bps: u16,
+) -> Instruction {
+    let data = Funds4GoodInstruction::ConfigureReservePool {
+        reserve_fee_bps_input: reserve_fee_bps,
+    }
+    .pack();
+
+    let accounts = vec![
+        AccountMeta::new(*admin_pubkey, true),
+        AccountMeta::new_readonly(*config_pubkey, false),
+        AccountMeta::new_readonly(*mint_vault_registry_pubkey, false),
+        AccountMeta::new(*reserve_pool_pubkey, false),
+        AccountMeta::new(*reserve_vault_pubkey, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Creates a `MarkLoanDefaulted` instruction, any `caller_pubkey` can sign it once the grace period is over.
+/// Reserve accounts are only read when the loan is covered by the reserve pool.
+#[allow(clippy::too_many_arguments)]
+pub fn mark_loan_defaulted(
+    program_id: &Pubkey,
+    caller_pubkey: &Pubkey,
+    loan_info_pubkey: &Pubkey,
+    vault_pubkey: &Pubkey,
+    mint_vault_registry_pubkey: &Pubkey,
+    reserve_pool_pubkey: &Pubkey,
+    reserve_vault_pubkey: &Pubkey,
+    lenders_storage_pubkey: &Pubkey,
+) -> Instruction {
+    let data = Funds4GoodInstruction::MarkLoanDefaulted {}.pack();
+
+    let accounts = vec![
+        AccountMeta::new_readonly(*caller_pubkey, true),
+        AccountMeta::new(*loan_info_pubkey, false),
+        AccountMeta::new(*vault_pubkey, false),
+        AccountMeta::new(*mint_vault_registry_pubkey, false),
+        AccountMeta::new(*reserve_pool_pubkey, false),
+        AccountMeta::new(*reserve_vault_pubkey, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new(*lenders_storage_pubkey, false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Creates a `SetLateFees` instruction.
+pub fn set_late_fees(
+    program_id: &Pubkey,
+    admin_pubkey: &Pubkey,
+    config_pubkey: &Pubkey,
+    mint_vault_registry_pubkey: &Pubkey,
+    late_fee_flat_amount: u64,
+    late_fee_daily_bps: u16,
+    late_fee_lenders_share_bps: u16,
+) -> Instruction {
+    let data = Funds4GoodInstruction::SetLateFees {
+        late_fee_flat_amount_input: late_fee_flat_amount,
+        late_fee_daily_bps_input: late_fee_daily_bps,
+        late_fee_lenders_share_bps_input: late_fee_lenders_share_bps,
+    }
+    .pack();
+
+    let accounts = vec![
+        AccountMeta::new_readonly(*admin_pubkey, true),
+        AccountMeta::new_readonly(*config_pubkey, false),
+        AccountMeta::new(*mint_vault_registry_pubkey, false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Creates a `PayOffLoan` instruction.
+/// `treasury_pubkey` is only read by the program when fees are charged, it is always passed here.
+/// Reserve accounts are only read when the loan is covered by the reserve pool.
+#[allow(clippy::too_many_arguments)]
+pub fn pay_off_loan(
+    program_id: &Pubkey,
+    borrower_pubkey: &Pubkey,
+    borrower_token_account_pubkey: &Pubkey,
+    vault_pubkey: &Pubkey,
+    mint_vault_registry_pubkey: &Pubkey,
+    borrower_storage_pubkey: &Pubkey,
+    reserve_pool_pubkey: &Pubkey,
+    reserve_vault_pubkey: &Pubkey,
+    loan_info_pubkey: &Pubkey,
+    lenders_storage_pubkey: &Pubkey,
+    treasury_pubkey: &Pubkey,
+    max_amount_to_pay: u64,
+) -> Instruction {
+    let data = Funds4GoodInstruction::PayOffLoan {
+        max_amount_to_pay_input: max_amount_to_pay,
+    }
+    .pack();
+
+    // payoff reads the same accounts as an emi payment by the borrower
+    Instruction {
+        program_id: *program_id,
+        accounts: pay_emi_accounts(
+            borrower_pubkey,
+            borrower_token_account_pubkey,
+            vault_pubkey,
+            mint_vault_registry_pubkey,
+            borrower_storage_pubkey,
+            reserve_pool_pubkey,
+            reserve_vault_pubkey,
+            loan_info_pubkey,
+            lenders_storage_pubkey,
+            treasury_pubkey,
+        ),
+        data,
+    }
+}
+
+/// Creates a `SetPrepaymentDiscount` instruction.
+pub fn set_prepayment_discount(
+    program_id: &Pubkey,
+    admin_pubkey: &Pubkey,
+    config_pubkey: &Pubkey,
+    mint_vault_registry_pubkey: &Pubkey,
+    prepayment_discount_bps: u16,
+) -> Instruction {
+    let data = Funds4GoodInstruction::SetPrepaymentDiscount {
+        prepayment_discount_bps_input: prepayment_discount_bps,
+    }
+    .pack();
+
+    let accounts = vec![
+        AccountMeta::new_readonly(*admin_pubkey, true),
+        AccountMeta::new_readonly(*config_pubkey, false),
+        AccountMeta::new(*mint_vault_registry_pubkey, false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Creates a `SetInterestRate` instruction, `interest_rate_bps` is the yearly rate charged on outstanding principal.
+pub fn set_interest_rate(
+    program_id: &Pubkey,
+    admin_pubkey: &Pubkey,
+    config_pubkey: &Pubkey,
+    mint_vault_registry_pubkey: &Pubkey,
+    interest_rate_bps: u16,
+) -> Instruction {
+    let data = Funds4GoodInstruction::SetInterestRate {
+        interest_rate_bps_input: interest_rate_bps,
+    }
+    .pack();
+
+    let accounts = vec![
+        AccountMeta::new_readonly(*admin_pubkey, true),
+        AccountMeta::new_readonly(*config_pubkey, false),
+        AccountMeta::new(*mint_vault_registry_pubkey, false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Creates a `RestructureLoan` instruction, both guarantor and borrower have to sign.
+pub fn restructure_loan(
+    program_id: &Pubkey,
+    guarantor_pubkey: &Pubkey,
+    borrower_pubkey: &Pubkey,
+    loan_info_pubkey: &Pubkey,
+    borrower_storage_pubkey: &Pubkey,
+    new_number_of_emis: u16,
+    num_days_left_for_next_repayment: u16,
+) -> Instruction {
+    let data = Funds4GoodInstruction::RestructureLoan {
+        new_number_of_emis_input: new_number_of_emis,
+        num_days_left_for_next_repayment_input: num_days_left_for_next_repayment,
+    }
+    .pack();
+
+    let accounts = vec![
+        AccountMeta::new_readonly(*guarantor_pubkey, true),
+        AccountMeta::new_readonly(*borrower_pubkey, true),
+        AccountMeta::new(*loan_info_pubkey, false),
+        AccountMeta::new(*borrower_storage_pubkey, false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Creates a `RepayGuarantor` instruction.
+pub fn repay_guarantor(
+    program_id: &Pubkey,
+    borrower_pubkey: &Pubkey,
+    borrower_token_account_pubkey: &Pubkey,
+    guarantor_token_account_pubkey: &Pubkey,
+    mint_vault_registry_pubkey: &Pubkey,
+    loan_info_pubkey: &Pubkey,
+    amount_to_repay: u64,
+) -> Instruction {
+    let data = Funds4GoodInstruction::RepayGuarantor {
+        amount_to_repay_input: amount_to_repay,
+    }
+    .pack();
+
+    let accounts = vec![
+        AccountMeta::new_readonly(*borrower_pubkey, true),
+        AccountMeta::new(*borrower_token_account_pubkey, false),
+        AccountMeta::new(*guarantor_token_account_pubkey, false),
+        AccountMeta::new_readonly(*mint_vault_registry_pubkey, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new(*loan_info_pubkey, false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Creates a `CancelLoan` instruction.
+pub fn cancel_loan(
+    program_id: &Pubkey,
+    signer_pubkey: &Pubkey,
+    loan_info_pubkey: &Pubkey,
+    mint_vault_registry_pubkey: &Pubkey,
+    lenders_storage_pubkey: &Pubkey,
+) -> Instruction {
+    let data = Funds4GoodInstruction::CancelLoan {}.pack();
+
+    let accounts = vec![
+        AccountMeta::new_readonly(*signer_pubkey, true),
+        AccountMeta::new(*loan_info_pubkey, false),
+        AccountMeta::new_readonly(*mint_vault_registry_pubkey, false),
+        AccountMeta::new(*lenders_storage_pubkey, false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Creates a `MigrateAccount` instruction, `payer_pubkey` funds the rent of the grown account.
+pub fn migrate_account(
+    program_id: &Pubkey,
+    payer_pubkey: &Pubkey,
+    account_to_migrate_pubkey: &Pubkey,
+) -> Instruction {
+    let data = Funds4GoodInstruction::MigrateAccount {}.pack();
+
+    let accounts = vec![
+        AccountMeta::new(*payer_pubkey, true),
+        AccountMeta::new(*account_to_migrate_pubkey, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Creates a `MigrateAccount` instruction for a legacy loan info account, `admin_pubkey` funds the rent of the grown
+/// account and picks the mint the loan is denominated in through `mint_vault_registry_pubkey`.
+pub fn migrate_loan_info_account(
+    program_id: &Pubkey,
+    admin_pubkey: &Pubkey,
+    loan_info_pubkey: &Pubkey,
+    config_pubkey: &Pubkey,
+    mint_vault_registry_pubkey: &Pubkey,
+) -> Instruction {
+    let data = Funds4GoodInstruction::MigrateAccount {}.pack();
+
+    let accounts = vec![
+        AccountMeta::new(*admin_pubkey, true),
+        AccountMeta::new(*loan_info_pubkey, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(*config_pubkey, false),
+        AccountMeta::new_readonly(*mint_vault_registry_pubkey, false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Creates an `AuditVault` instruction, vault is checked against liabilities recorded in its registry.
+/// Reserve vault is only read when the reserve pool of the mint is configured, it is always passed here.
+pub fn audit_vault(
+    program_id: &Pubkey,
+    vault_pubkey: &Pubkey,
+    mint_vault_registry_pubkey: &Pubkey,
+    reserve_pool_pubkey: &Pubkey,
+    reserve_vault_pubkey: &Pubkey,
+) -> Instruction {
+    let data = Funds4GoodInstruction::AuditVault {}.pack();
+
+    let accounts = vec![
+        AccountMeta::new_readonly(*vault_pubkey, false),
+        AccountMeta::new_readonly(*mint_vault_registry_pubkey, false),
+        AccountMeta::new_readonly(*reserve_pool_pubkey, false),
+        AccountMeta::new_readonly(*reserve_vault_pubkey, false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // one instance of every variant, in wire order
+    fn all_instructions() -> Vec<Funds4GoodInstruction> {
+        vec![
+            Funds4GoodInstruction::LendToBorrower {
+                amount_to_lend_input: 100,
+                lender_id_input: 7,
+            },
+            Funds4GoodInstruction::WithdrawLenderFreeWalletFunds {
+                lender_id_input: 7,
+                amount_to_withdraw_input: 50,
+            },
+            Funds4GoodInstruction::WithdrawCollectedLoanFunds {},
+            Funds4GoodInstruction::TransferFunds4GoodVaultAccountOwnership {},
+            Funds4GoodInstruction::InitializeLendersStorageAccount {},
+            Funds4GoodInstruction::InitializeBorrowerAccount {},
+            Funds4GoodInstruction::InitializeGuarantorAccount {},
+            Funds4GoodInstruction::PayEMIforLoan {
+                emi_amount_to_pay_input: 10,
+            },
+            Funds4GoodInstruction::InitializeLoanInfoAccount {
+                num_days_left_for_first_repayment_input: 30,
+                num_emis_needed_to_repay_the_loan_input: 12,
+                num_days_for_fundraising_input: 10,
+                total_loan_amount_input: 1_000,
+                min_funding_amount_input: 500,
+            },
+            Funds4GoodInstruction::AirdropUsersWithFunds4GoodTestCoins {},
+            Funds4GoodInstruction::TransferAirdropVaultAccountOwnership {},
+            Funds4GoodInstruction::ReturnFundsToLenders {
+                num_accounts_input: 3,
+            },
+            Funds4GoodInstruction::CloseLoanInfoAccount {},
+            Funds4GoodInstruction::SetPlatformFees {
+                origination_fee_bps_input: 100,
+                servicing_fee_bps_input: 50,
+            },
+            Funds4GoodInstruction::TransferTreasuryAccountOwnership {},
+            Funds4GoodInstruction::WithdrawTreasury {
+                amount_to_withdraw_input: 20,
+            },
+            Funds4GoodInstruction::ConfigureReservePool {
+                reserve_fee_bps_input: 100,
+            },
+            Funds4GoodInstruction::MarkLoanDefaulted {},
+            Funds4GoodInstruction::SetLateFees {
+                late_fee_flat_amount_input: 5,
+                late_fee_daily_bps_input: 10,
+                late_fee_lenders_share_bps_input: 5_000,
+            },
+            Funds4GoodInstruction::PayOffLoan {
+                max_amount_to_pay_input: 1_000,
+            },
+            Funds4GoodInstruction::SetPrepaymentDiscount {
+                prepayment_discount_bps_input: 200,
+            },
+            Funds4GoodInstruction::RestructureLoan {
+                new_number_of_emis_input: 6,
+                num_days_left_for_next_repayment_input: 15,
+            },
+            Funds4GoodInstruction::PayEMIOnBehalf {
+                emi_amount_to_pay_input: 10,
+            },
+            Funds4GoodInstruction::GuarantorPayOverdueEMI {
+                emi_amount_to_pay_input: 10,
+            },
+            Funds4GoodInstruction::RepayGuarantor {
+                amount_to_repay_input: 10,
+            },
+            Funds4GoodInstruction::CancelLoan {},
+            Funds4GoodInstruction::MigrateAccount {},
+            Funds4GoodInstruction::AuditVault {},
+            Funds4GoodInstruction::SetInterestRate {
+                interest_rate_bps_input: 1_200,
+            },
+        ]
+    }
+
+    #[test]
+    fn test_pack_unpack_round_trip() {
+        for (index, instruction) in all_instructions().into_iter().enumerate() {
+            let packed = instruction.pack();
+            assert_eq!(packed[0], INSTRUCTION_WIRE_VERSION);
+            // variant index is part of the wire format
+            assert_eq!(packed[1] as usize, index);
+            assert_eq!(Funds4GoodInstruction::unpack(&packed).unwrap(), instruction);
+        }
+    }
+
+    #[test]
+    fn test_unpack_rejects_malformed_input() {
+        let mut packed = Funds4GoodInstruction::PayOffLoan {
+            max_amount_to_pay_input: 1_000,
+        }
+        .pack();
+
+        assert_eq!(
+            Funds4GoodInstruction::unpack(&[]),
+            Err(InvalidInstruction.into())
+        );
+        assert_eq!(
+            Funds4GoodInstruction::unpack(&packed[..packed.len() - 1]),
+            Err(InvalidInstruction.into())
+        );
+
+        packed[0] = INSTRUCTION_WIRE_VERSION + 1;
+        assert_eq!(
+            Funds4GoodInstruction::unpack(&packed),
+            Err(InvalidInstruction.into())
+        );
     }
 }
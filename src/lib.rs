@@ -1,4 +1,6 @@
+pub mod audit;
 pub mod error;
+pub mod event;
 pub mod instruction;
 pub mod processor;
 pub mod state;
@@ -1,10 +1,15 @@
 use crate::{
-    error::Funds4GoodError, instruction::Funds4GoodInstruction, state, state::AccTypes,
-    state::BorrowerAccount, state::GuarantorAccount, state::LenderAccountData,
-    state::LoanInfoAccDataHeader, state::LoanInfoAccLendersData,
+    audit, error::Funds4GoodError, event::Funds4GoodEvent, instruction::Funds4GoodInstruction,
+    state, state::AccTypes,
+    state::BorrowerAccount, state::Funds4GoodConfigAccount, state::GuarantorAccount,
+    state::LenderAccountData, state::LendersStorageAccount, state::LoanInfoAccDataHeader,
+    state::LoanInfoAccLendersData, state::LoanInfoAccRepaymentData, state::LoanInfoAccount,
+    state::LoanStatus, state::MintVaultRegistryAccount, state::ReservePoolAccount,
 };
+use arrayref::{array_ref, array_refs};
 use solana_program::{
     account_info::{next_account_info, AccountInfo},
+    bpf_loader_upgradeable,
     entrypoint::ProgramResult,
     msg,
     program::invoke,
@@ -12,14 +17,52 @@ use solana_program::{
     program_error::ProgramError,
     program_pack::{IsInitialized, Pack},
     pubkey::Pubkey,
+    system_instruction, system_program,
     sysvar::{clock::Clock, rent::Rent, Sysvar},
 };
 
-use spl_token::state::Account as TokenAccount;
+use spl_token::state::{Account as TokenAccount, Mint};
 use std::convert::TryInto;
 
-const Funds4Good_COIN_DECIMALS: u64 = 1000_000_000;
-const MIN_LENDING_AMOUNT: u64 = 10_000_000_000u64;
+// amounts below are in whole coins, they are scaled with mint_decimals stored in MintVaultRegistryAccount of loan mint.
+// Limits are public so off-chain clients like the simulator follow the program instead of copying them
+pub const MIN_LENDING_COINS: u64 = 10;
+pub const LENDING_COINS_MULTIPLE: u64 = 10;
+// For prototype borrowers can borrow a loan amount between 200 coins to 2000 coins in multiples of 100,
+// so with lending in multiples of 10 a loan never needs more than 200 lender slots
+pub const MIN_LOAN_COINS: u64 = 200;
+pub const MAX_LOAN_COINS: u64 = 2000;
+pub const LOAN_COINS_MULTIPLE: u64 = 100;
+pub const BPS_DENOMINATOR: u64 = 10_000;
+// platform fees can be at most 10%
+pub const MAX_PLATFORM_FEE_BPS: u16 = 1_000;
+// late fee can grow by at most 1% of the installment for every day it is late
+pub const MAX_LATE_FEE_DAILY_BPS: u16 = 100;
+// borrowers paying off early can get at most 5% of remaining principal waived
+pub const MAX_PREPAYMENT_DISCOUNT_BPS: u16 = 500;
+// interest can be at most 36% of outstanding principal a year
+pub const MAX_INTEREST_RATE_BPS: u16 = 3_600;
+pub const NUM_DAYS_IN_ONE_YEAR: u64 = 365;
+// restructuring a loan costs borrower 10% of the initial credit score
+pub const RESTRUCTURE_CREDIT_SCORE_PENALTY: u64 = 50_000_000_000;
+pub const ON_TIME_EMI_CREDIT_SCORE_REWARD: u64 = 5_000_000_000;
+pub const LATE_EMI_CREDIT_SCORE_PENALTY: u64 = 10_000_000_000;
+// reserve pool can take at most 10% of every emi
+pub const MAX_RESERVE_FEE_BPS: u16 = 1_000;
+pub const NUM_SECONDS_IN_ONE_DAY: u64 = 86400;
+pub const EMI_INTERVAL_DAYS: u64 = 30;
+// a loan can be marked defaulted when it is not fully repaid this many days after its last emi date
+pub const LOAN_DEFAULT_GRACE_PERIOD_DAYS: u64 = 30;
+const PROGRAM_DATA_STATE_INDEX: u32 = 3;
+const PROGRAM_DATA_METADATA_SIZE: usize = 45;
+// who is paying an emi, guarantor payments are recorded as a claim against borrower
+#[derive(PartialEq)]
+enum EmiPayer {
+    Borrower,
+    OnBehalf,
+    Guarantor,
+}
+
 pub struct Processor;
 impl Processor {
     pub fn process(
@@ -43,11 +86,15 @@ impl Processor {
                     program_id,
                 )
             }
-            Funds4GoodInstruction::WithdrawLenderFreeWalletFunds { lender_id_input } => {
+            Funds4GoodInstruction::WithdrawLenderFreeWalletFunds {
+                lender_id_input,
+                amount_to_withdraw_input,
+            } => {
                 msg!("Funds4GoodInstruction::WithdrawLenderFreeWalletFunds");
                 Self::process_withdraw_lender_free_wallet_funds(
                     accounts,
                     lender_id_input,
+                    amount_to_withdraw_input,
                     program_id,
                 )
             }
@@ -78,7 +125,65 @@ impl Processor {
                 emi_amount_to_pay_input,
             } => {
                 msg!("Funds4GoodInstruction::PayEMIforLoan");
-                Self::process_pay_emi(accounts, emi_amount_to_pay_input, program_id)
+                Self::process_pay_emi(
+                    accounts,
+                    emi_amount_to_pay_input,
+                    EmiPayer::Borrower,
+                    program_id,
+                )
+            }
+
+            Funds4GoodInstruction::PayEMIOnBehalf {
+                emi_amount_to_pay_input,
+            } => {
+                msg!("Funds4GoodInstruction::PayEMIOnBehalf");
+                Self::process_pay_emi(
+                    accounts,
+                    emi_amount_to_pay_input,
+                    EmiPayer::OnBehalf,
+                    program_id,
+                )
+            }
+
+            Funds4GoodInstruction::GuarantorPayOverdueEMI {
+                emi_amount_to_pay_input,
+            } => {
+                msg!("Funds4GoodInstruction::GuarantorPayOverdueEMI");
+                Self::process_pay_emi(
+                    accounts,
+                    emi_amount_to_pay_input,
+                    EmiPayer::Guarantor,
+                    program_id,
+                )
+            }
+
+            Funds4GoodInstruction::CancelLoan {} => {
+                msg!("Funds4GoodInstruction::CancelLoan");
+                Self::process_cancel_loan(accounts, program_id)
+            }
+
+            Funds4GoodInstruction::MigrateAccount {} => {
+                msg!("Funds4GoodInstruction::MigrateAccount");
+                Self::process_migrate_account(accounts, program_id)
+            }
+
+            Funds4GoodInstruction::AuditVault {} => {
+                msg!("Funds4GoodInstruction::AuditVault");
+                Self::process_audit_vault(accounts, program_id)
+            }
+
+            Funds4GoodInstruction::SetInterestRate {
+                interest_rate_bps_input,
+            } => {
+                msg!("Funds4GoodInstruction::SetInterestRate");
+                Self::process_set_interest_rate(accounts, interest_rate_bps_input, program_id)
+            }
+
+            Funds4GoodInstruction::RepayGuarantor {
+                amount_to_repay_input,
+            } => {
+                msg!("Funds4GoodInstruction::RepayGuarantor");
+                Self::process_repay_guarantor(accounts, amount_to_repay_input, program_id)
             }
 
             Funds4GoodInstruction::InitializeLoanInfoAccount {
@@ -86,6 +191,7 @@ impl Processor {
                 num_emis_needed_to_repay_the_loan_input,
                 num_days_for_fundraising_input,
                 total_loan_amount_input,
+                min_funding_amount_input,
             } => {
                 msg!("Funds4GoodInstruction::InitializeLoanInfoAccount");
                 Self::initialize_loan_info_account(
@@ -94,6 +200,7 @@ impl Processor {
                     num_emis_needed_to_repay_the_loan_input,
                     num_days_for_fundraising_input,
                     total_loan_amount_input,
+                    min_funding_amount_input,
                     program_id,
                 )
             }
@@ -108,14 +215,97 @@ impl Processor {
                 Self::process_transfer_airdrop_vault_account_ownership(accounts, program_id)
             }
 
-            Funds4GoodInstruction::ReturnFundsToLenders { num_accounts_input } => {
+            Funds4GoodInstruction::ReturnFundsToLenders { .. } => {
                 msg!("Funds4GoodInstruction::ReturnFundsToLenders");
-                Self::process_return_funds_to_lenders(accounts, num_accounts_input, program_id)
+                Self::process_return_funds_to_lenders()
             }
 
             Funds4GoodInstruction::CloseLoanInfoAccount {} => {
                 msg!("Funds4GoodInstruction::CloseLoanInfoAccount");
-                Self::process_close_loan_info_account(accounts)
+                Self::process_close_loan_info_account()
+            }
+
+            Funds4GoodInstruction::SetPlatformFees {
+                origination_fee_bps_input,
+                servicing_fee_bps_input,
+            } => {
+                msg!("Funds4GoodInstruction::SetPlatformFees");
+                Self::process_set_platform_fees(
+                    accounts,
+                    origination_fee_bps_input,
+                    servicing_fee_bps_input,
+                    program_id,
+                )
+            }
+
+            Funds4GoodInstruction::TransferTreasuryAccountOwnership {} => {
+                msg!("Funds4GoodInstruction::TransferTreasuryAccountOwnership");
+                Self::process_transfer_treasury_account_ownership(accounts, program_id)
+            }
+
+            Funds4GoodInstruction::WithdrawTreasury {
+                amount_to_withdraw_input,
+            } => {
+                msg!("Funds4GoodInstruction::WithdrawTreasury");
+                Self::process_withdraw_treasury(accounts, amount_to_withdraw_input, program_id)
+            }
+
+            Funds4GoodInstruction::ConfigureReservePool {
+                reserve_fee_bps_input,
+            } => {
+                msg!("Funds4GoodInstruction::ConfigureReservePool");
+                Self::process_configure_reserve_pool(accounts, reserve_fee_bps_input, program_id)
+            }
+
+            Funds4GoodInstruction::MarkLoanDefaulted {} => {
+                msg!("Funds4GoodInstruction::MarkLoanDefaulted");
+                Self::process_mark_loan_defaulted(accounts, program_id)
+            }
+
+            Funds4GoodInstruction::PayOffLoan {
+                max_amount_to_pay_input,
+            } => {
+                msg!("Funds4GoodInstruction::PayOffLoan");
+                Self::process_pay_off_loan(accounts, max_amount_to_pay_input, program_id)
+            }
+
+            Funds4GoodInstruction::SetPrepaymentDiscount {
+                prepayment_discount_bps_input,
+            } => {
+                msg!("Funds4GoodInstruction::SetPrepaymentDiscount");
+                Self::process_set_prepayment_discount(
+                    accounts,
+                    prepayment_discount_bps_input,
+                    program_id,
+                )
+            }
+
+            Funds4GoodInstruction::RestructureLoan {
+                new_number_of_emis_input,
+                num_days_left_for_next_repayment_input,
+            } => {
+                msg!("Funds4GoodInstruction::RestructureLoan");
+                Self::process_restructure_loan(
+                    accounts,
+                    new_number_of_emis_input,
+                    num_days_left_for_next_repayment_input,
+                    program_id,
+                )
+            }
+
+            Funds4GoodInstruction::SetLateFees {
+                late_fee_flat_amount_input,
+                late_fee_daily_bps_input,
+                late_fee_lenders_share_bps_input,
+            } => {
+                msg!("Funds4GoodInstruction::SetLateFees");
+                Self::process_set_late_fees(
+                    accounts,
+                    late_fee_flat_amount_input,
+                    late_fee_daily_bps_input,
+                    late_fee_lenders_share_bps_input,
+                    program_id,
+                )
             }
         }
     }
@@ -136,7 +326,16 @@ impl Processor {
         let lender_Funds4Good_coin_account_to_debit = next_account_info(account_info_iter)?;
         let Funds4Good_coin_vault_account = next_account_info(account_info_iter)?;
 
-        
+        let mint_vault_registry_account = next_account_info(account_info_iter)?;
+        let mut mint_vault_registry_data =
+            Self::verify_mint_vault_registry_account(mint_vault_registry_account, program_id)?;
+        Self::verify_vault_account(Funds4Good_coin_vault_account, &mint_vault_registry_data)?;
+        Self::verify_user_token_account(
+            lender_Funds4Good_coin_account_to_debit,
+            lender_main_account.key,
+            &mint_vault_registry_data.mint_pubkey,
+        )?;
+        let coin_unit = Self::coin_unit(mint_vault_registry_data.mint_decimals)?;
 
         let token_program = next_account_info(account_info_iter)?;
 
@@ -166,8 +365,8 @@ impl Processor {
 
         msg!("Calling the token program to transfer lending amount to vault...");
         msg!(
-            "amount of Funds4Good coin tokens to transfer {}, lender debit key {}",
-            (amount_to_lend_input as f64 / Funds4Good_COIN_DECIMALS as f64),
+            "amount of tokens to transfer {}, lender debit key {}",
+            (amount_to_lend_input as f64 / coin_unit as f64),
             lender_Funds4Good_coin_account_to_debit.key.to_string()
         );
 
@@ -190,88 +389,52 @@ impl Processor {
         );
         let vault_balance_increased = Funds4Good_coin_vault_balance_after
             .checked_sub(Funds4Good_coin_vault_balance_before)
-            .unwrap();
+            .ok_or(Funds4GoodError::AmountUnderflow)?;
 
-        if vault_balance_increased < MIN_LENDING_AMOUNT {
+        if vault_balance_increased < MIN_LENDING_COINS * coin_unit {
             return Err(Funds4GoodError::ExpectedAmountMismatch.into());
         }
-       
+
+        // lenders can lend in multiples of 10 coins for prototype
+        if !vault_balance_increased.is_multiple_of(LENDING_COINS_MULTIPLE * coin_unit) {
+            return Err(Funds4GoodError::LendingAmountNotInAllowedMultiple.into());
+        }
+
         let loan_info_storage_account = next_account_info(account_info_iter)?;
-       
+
         if loan_info_storage_account.owner != program_id {
             return Err(Funds4GoodError::WrongAccountPassed.into());
         }
-       
+
         let lenders_data_storage_account = next_account_info(account_info_iter)?;
-    
+
         if lenders_data_storage_account.owner != program_id {
             return Err(Funds4GoodError::WrongAccountPassed.into());
         }
-       
-        let mut lenders_storage_data_byte_array =
-            lenders_data_storage_account.try_borrow_mut_data()?;
-        
-        if lenders_storage_data_byte_array[0] != AccTypes::LendersAcc as u8 {
-            return Err(Funds4GoodError::ExpectedAccountTypeMismatched.into());
-        }
-      
-        if lenders_storage_data_byte_array[1] != 1u8 {
-            return Err(Funds4GoodError::ExpectedLendersAccNumNotMatched.into());
-        }   
-     
-        if lender_id_input > 49_999u32 {
-            return Err(Funds4GoodError::InvalidLenderIdInput.into());
-        }
-      
-        let lender_si_in_lenders_data_byte_array: usize = 2usize
-            + (lender_id_input as usize)
-                .checked_mul(state::LENDER_ACC_DATA_SIZE)
-                .unwrap();
-        let lender_ei_in_lenders_data_byte_array: usize =
-            lender_si_in_lenders_data_byte_array + state::LENDER_ACC_DATA_SIZE;
-        let mut lender_acc_data: LenderAccountData = state::unpack_to_lender_account_data(
-            &lenders_storage_data_byte_array
-                [lender_si_in_lenders_data_byte_array..lender_ei_in_lenders_data_byte_array],
-        )
-        .unwrap();
-      
-        if lender_acc_data.is_account_active != 1u8 {
-            lender_acc_data.is_account_active = 1u8;
-            lender_acc_data.lender_main_acc_pubkey = *lender_main_account.key;
-        } else {
-         
-            if lender_acc_data.lender_main_acc_pubkey != *lender_main_account.key {
-                return Err(Funds4GoodError::InvalidLenderIdInput.into());
-            }
+
+        if mint_vault_registry_data.lenders_storage_acc_pubkey != *lenders_data_storage_account.key {
+            return Err(Funds4GoodError::LendersStorageAccountDoesNotMatched.into());
         }
-       
-        lender_acc_data.total_lending_amount = lender_acc_data
-            .total_lending_amount
-            .checked_add(vault_balance_increased as u128)
-            .unwrap();
-        lender_acc_data.total_unique_lending_amount = lender_acc_data
-            .total_unique_lending_amount
-            .checked_add(vault_balance_increased)
-            .unwrap();
-         
-        state::pack_to_lender_account_data(
-            lender_acc_data,
-            &mut lenders_storage_data_byte_array
-                [lender_si_in_lenders_data_byte_array..lender_ei_in_lenders_data_byte_array],
-        )
-        .unwrap();
 
         let mut loan_info_data_byte_array = loan_info_storage_account.try_borrow_mut_data()?;
-        let mut loan_info_header_data: LoanInfoAccDataHeader = state::unpack_to_loan_info_header(
-            &loan_info_data_byte_array
-                [state::LOAN_INFO_HEADER_START_INDEX..state::LOAN_INFO_HEADER_END_INDEX],
-        )
-        .unwrap();
+        let mut loan_info = LoanInfoAccount::new(&mut loan_info_data_byte_array)?;
+        let mut loan_info_header_data: LoanInfoAccDataHeader = loan_info.header()?;
 
         if loan_info_header_data.acc_type != AccTypes::LoanInfoAcc as u8 {
             return Err(Funds4GoodError::ExpectedAccountTypeMismatched.into());
         }
 
+        if loan_info_header_data.loan_mint_pubkey != mint_vault_registry_data.mint_pubkey {
+            return Err(Funds4GoodError::LoanMintDoesNotMatched.into());
+        }
+
+        if loan_info_header_data.loan_status == LoanStatus::Cancelled as u8 {
+            return Err(Funds4GoodError::LoanCancelled.into());
+        }
+        if loan_info_header_data.loan_status != LoanStatus::Fundraising as u8 {
+            return Err(Funds4GoodError::BorrowerAlreadyFunded.into());
+        }
+
         let now = Clock::get()?.unix_timestamp as u64;
 
         if loan_info_header_data.fundraising_period_ending_timestamp < now
@@ -279,68 +442,132 @@ impl Processor {
         {
             return Err(Funds4GoodError::FundraisingPeriodExpired.into());
         }
-     
+
         if loan_info_header_data.total_amount_lended >= loan_info_header_data.total_loan_amount {
             return Err(Funds4GoodError::BorrowerAlreadyFunded.into());
         }
 
-        let loan_info_lender_data_si: usize = state::LOAN_INFO_HEADER_DATA_BYTES
-            + (loan_info_header_data.next_index_to_store_lender_data as usize)
-                * state::LOAN_INFO_ACC_LENDER_DATA_BYTES;
-        let loan_info_lender_data_ei: usize =
-            loan_info_lender_data_si + state::LOAN_INFO_ACC_LENDER_DATA_BYTES;
-        let mut loan_info_lender_data: LoanInfoAccLendersData =
-            state::unpack_to_loan_info_acc_lender_data(
-                &loan_info_data_byte_array[loan_info_lender_data_si..loan_info_lender_data_ei],
-            )
-            .unwrap();
+        // last lender can only fill the remaining shortfall, anything above that is credited back to lender free balance
+        let remaining_loan_amount = loan_info_header_data
+            .total_loan_amount
+            .checked_sub(loan_info_header_data.total_amount_lended)
+            .ok_or(Funds4GoodError::AmountUnderflow)?;
+        let accepted_lending_amount = if vault_balance_increased > remaining_loan_amount {
+            remaining_loan_amount
+        } else {
+            vault_balance_increased
+        };
+        let excess_lending_amount = vault_balance_increased
+            .checked_sub(accepted_lending_amount)
+            .ok_or(Funds4GoodError::AmountUnderflow)?;
+
+        let mut lenders_storage_data_byte_array =
+            lenders_data_storage_account.try_borrow_mut_data()?;
+        let mut lenders_storage = LendersStorageAccount::new(&mut lenders_storage_data_byte_array)?;
 
-        loan_info_lender_data.lender_main_acc_pubkey = *lender_main_account.key;
-        loan_info_lender_data.lenders_data_storage_acc_number = 1u8;
-        loan_info_lender_data.lender_id = lender_id_input;
-        loan_info_lender_data.lent_amount = vault_balance_increased;
+        let mut lender_acc_data: LenderAccountData = lenders_storage.lender(lender_id_input)?;
 
-        state::pack_to_loan_info_acc_lender_data(
-            loan_info_lender_data,
-            &mut loan_info_data_byte_array[loan_info_lender_data_si..loan_info_lender_data_ei],
-        )
-        .unwrap();
+        if lender_acc_data.is_account_active != 1u8 {
+            lender_acc_data.is_account_active = 1u8;
+            lender_acc_data.lender_main_acc_pubkey = *lender_main_account.key;
+        } else {
+            if lender_acc_data.lender_main_acc_pubkey != *lender_main_account.key {
+                return Err(Funds4GoodError::InvalidLenderIdInput.into());
+            }
+        }
+
+        lender_acc_data.total_lending_amount = lender_acc_data
+            .total_lending_amount
+            .checked_add(accepted_lending_amount as u128)
+            .ok_or(Funds4GoodError::AmountOverflow)?;
+        lender_acc_data.total_unique_lending_amount = lender_acc_data
+            .total_unique_lending_amount
+            .checked_add(vault_balance_increased)
+            .ok_or(Funds4GoodError::AmountOverflow)?;
+        if excess_lending_amount > 0 {
+            msg!(
+                "crediting excess lending amount {} back to lender free balance",
+                (excess_lending_amount as f64 / coin_unit as f64)
+            );
+            lender_acc_data.amount_to_withdraw_or_lend = lender_acc_data
+                .amount_to_withdraw_or_lend
+                .checked_add(excess_lending_amount)
+                .ok_or(Funds4GoodError::AmountOverflow)?;
+        }
+
+        lenders_storage.set_lender(lender_id_input, lender_acc_data)?;
+
+        loan_info.push_lender(
+            &mut loan_info_header_data,
+            LoanInfoAccLendersData {
+                lender_main_acc_pubkey: *lender_main_account.key,
+                lenders_data_storage_acc_number: 1u8,
+                lender_id: lender_id_input,
+                lent_amount: accepted_lending_amount,
+            },
+        )?;
 
-        loan_info_header_data.next_index_to_store_lender_data =
-            loan_info_header_data.next_index_to_store_lender_data + 1;
         loan_info_header_data.total_amount_lended = loan_info_header_data
             .total_amount_lended
+            .checked_add(accepted_lending_amount)
+            .ok_or(Funds4GoodError::AmountOverflow)?;
+
+        // accepted amount is owed until disbursement and excess is owed as free balance, so vault owes all of it
+        mint_vault_registry_data.vault_liabilities = mint_vault_registry_data
+            .vault_liabilities
             .checked_add(vault_balance_increased)
-            .unwrap();
-        state::pack_to_loan_info_header(
-            loan_info_header_data,
-            &mut loan_info_data_byte_array
-                [state::LOAN_INFO_HEADER_START_INDEX..state::LOAN_INFO_HEADER_END_INDEX],
-        )
-        .unwrap();
+            .ok_or(Funds4GoodError::AmountOverflow)?;
+        MintVaultRegistryAccount::pack(
+            mint_vault_registry_data,
+            &mut mint_vault_registry_account.data.borrow_mut(),
+        )?;
+
+        Funds4GoodEvent::Lent {
+            loan_info_pubkey: *loan_info_storage_account.key,
+            lender_pubkey: *lender_main_account.key,
+            lender_id: lender_id_input,
+            lent_amount: accepted_lending_amount,
+            excess_amount: excess_lending_amount,
+            total_amount_lended: loan_info_header_data.total_amount_lended,
+        }
+        .emit();
+
+        loan_info.set_header(loan_info_header_data)?;
 
         Ok(())
     }
 
+    // emi can be paid by borrower or by anyone on behalf of borrower (family, NGO, guarantor),
+    // either way payment is recorded against borrower's loan and credit score
     fn process_pay_emi(
         accounts: &[AccountInfo],
         emi_amount_to_pay_input: u64,
+        emi_payer: EmiPayer,
 
         program_id: &Pubkey,
     ) -> ProgramResult {
         let account_info_iter = &mut accounts.iter();
-        let borrower_main_account = next_account_info(account_info_iter)?;
+        let payer_main_account = next_account_info(account_info_iter)?;
 
-        if !borrower_main_account.is_signer {
+        if !payer_main_account.is_signer {
             return Err(ProgramError::MissingRequiredSignature);
         }
 
-        let borrower_Funds4Good_coin_account_to_debit = next_account_info(account_info_iter)?;
+        let payer_Funds4Good_coin_account_to_debit = next_account_info(account_info_iter)?;
 
         // Funds4Good_coin_vault_account is program controlled vault account and can be only controlled by our deployed program for debit funds or any kind of operation
         let Funds4Good_coin_vault_account = next_account_info(account_info_iter)?;
-        
 
+        let mint_vault_registry_account = next_account_info(account_info_iter)?;
+        let mut mint_vault_registry_data =
+            Self::verify_mint_vault_registry_account(mint_vault_registry_account, program_id)?;
+        Self::verify_vault_account(Funds4Good_coin_vault_account, &mint_vault_registry_data)?;
+        Self::verify_user_token_account(
+            payer_Funds4Good_coin_account_to_debit,
+            payer_main_account.key,
+            &mint_vault_registry_data.mint_pubkey,
+        )?;
+        let coin_unit = Self::coin_unit(mint_vault_registry_data.mint_decimals)?;
 
         let borrower_storage_account = next_account_info(account_info_iter)?;
         if borrower_storage_account.owner != program_id {
@@ -365,37 +592,259 @@ impl Processor {
             return Err(Funds4GoodError::InvalidTokenProgram.into());
         }
 
+        let reserve_pool_account = next_account_info(account_info_iter)?;
+        let reserve_vault_account = next_account_info(account_info_iter)?;
+
+        let loan_info_storage_account = next_account_info(account_info_iter)?;
+
+        if loan_info_storage_account.owner != program_id {
+            return Err(Funds4GoodError::WrongAccountPassed.into());
+        }
+
+        let mut loan_info_data_byte_array = loan_info_storage_account.try_borrow_mut_data()?;
+        let mut loan_info = LoanInfoAccount::new(&mut loan_info_data_byte_array)?;
+        let mut loan_info_header_data: LoanInfoAccDataHeader = loan_info.header()?;
+
+        if loan_info_header_data.acc_type != AccTypes::LoanInfoAcc as u8 {
+            return Err(Funds4GoodError::ExpectedAccountTypeMismatched.into());
+        }
+
+        if loan_info_header_data.loan_mint_pubkey != mint_vault_registry_data.mint_pubkey {
+            return Err(Funds4GoodError::LoanMintDoesNotMatched.into());
+        }
+
+        let reserve_pool_data = Self::verify_loan_reserve_pool_account(
+            reserve_pool_account,
+            reserve_vault_account,
+            &mint_vault_registry_data,
+            &loan_info_header_data,
+            program_id,
+        )?;
+
+        match emi_payer {
+            EmiPayer::Borrower => {
+                if loan_info_header_data.borrower_main_acc_pubkey != *payer_main_account.key {
+                    return Err(Funds4GoodError::BorrowerAccountMismatched.into());
+                }
+            }
+            EmiPayer::Guarantor => {
+                if loan_info_header_data.guarantor_main_acc_pubkey != *payer_main_account.key {
+                    return Err(Funds4GoodError::AccountMismatched.into());
+                }
+            }
+            EmiPayer::OnBehalf => {}
+        }
+
+        let mut borrower_data =
+            BorrowerAccount::unpack_versioned(&borrower_storage_account.data.borrow())?;
+        if borrower_data.acc_type != AccTypes::BorrowerAcc as u8 {
+            return Err(Funds4GoodError::ExpectedAccountTypeMismatched.into());
+        }
+        if borrower_data.borrower_main_acc_pubkey != loan_info_header_data.borrower_main_acc_pubkey {
+            return Err(Funds4GoodError::BorrowerAccountMismatched.into());
+        }
+
+        if loan_info_header_data.repaid_amount_by_borrower
+            >= loan_info_header_data.total_loan_amount
+        {
+            return Err(Funds4GoodError::LoanAlreadyPaid.into());
+        }
+
+        if loan_info_header_data.loan_status != LoanStatus::Active as u8 {
+            return Err(Funds4GoodError::LoanStatusDoesNotMatched.into());
+        }
+
+        let now = Clock::get()?.unix_timestamp as u64;
+
+        // late fee of the installment being paid and interest accrued until now are collected first,
+        // rest of the emi pays the installment
+        let late_fee_amount =
+            Self::calculate_late_fee_amount(&mint_vault_registry_data, &loan_info_header_data, now)?;
+        let interest_amount = Self::calculate_accrued_interest_amount(
+            &mint_vault_registry_data,
+            &loan_info,
+            &loan_info_header_data,
+            now,
+        )?;
+        let offered_installment_amount = emi_amount_to_pay_input
+            .checked_sub(late_fee_amount)
+            .and_then(|amount| amount.checked_sub(interest_amount))
+            .ok_or(Funds4GoodError::ExpectedAmountMismatch)?;
+
+        let outstanding_loan_amount = loan_info_header_data
+            .total_loan_amount
+            .checked_sub(loan_info_header_data.repaid_amount_by_borrower)
+            .ok_or(Funds4GoodError::AmountUnderflow)?;
+
+        // last installment can be smaller than the others when loan amount is not divisible by number of emis
+        let scheduled_installment_amount = Self::installment_amount(&loan_info_header_data)?;
+        let min_installment_amount = if scheduled_installment_amount > outstanding_loan_amount {
+            outstanding_loan_amount
+        } else {
+            scheduled_installment_amount
+        };
+
+        // installment paid within its grace period raises borrower credit score, a late one lowers it
+        let is_installment_on_time = scheduled_installment_amount == 0
+            || now
+                <= Self::installment_due_timestamp(
+                    &loan_info_header_data,
+                    Self::first_unpaid_installment_index(
+                        &loan_info_header_data,
+                        scheduled_installment_amount,
+                    ),
+                )?;
+
+        // guarantor can only step in for an installment borrower missed
+        if emi_payer == EmiPayer::Guarantor && is_installment_on_time {
+            return Err(Funds4GoodError::LoanInstallmentNotOverdue.into());
+        }
+
+        if min_installment_amount > offered_installment_amount {
+            return Err(Funds4GoodError::ExpectedAmountMismatch.into());
+        }
+
+        // only outstanding balance is accepted and transferred, borrower keeps anything above it
+        let installment_amount = if offered_installment_amount > outstanding_loan_amount {
+            outstanding_loan_amount
+        } else {
+            offered_installment_amount
+        };
+        let accepted_emi_amount = late_fee_amount
+            .checked_add(interest_amount)
+            .ok_or(Funds4GoodError::AmountOverflow)?
+            .checked_add(installment_amount)
+            .ok_or(Funds4GoodError::AmountOverflow)?;
+
+        let late_fee_amount_for_lenders = Self::calculate_fee_amount(
+            late_fee_amount,
+            mint_vault_registry_data.late_fee_lenders_share_bps,
+        )?;
+        let late_fee_amount_for_treasury = late_fee_amount
+            .checked_sub(late_fee_amount_for_lenders)
+            .ok_or(Funds4GoodError::AmountUnderflow)?;
+
+        loan_info_header_data.repaid_amount_by_borrower = loan_info_header_data
+            .repaid_amount_by_borrower
+            .checked_add(installment_amount)
+            .ok_or(Funds4GoodError::AmountOverflow)?;
+        loan_info_header_data.penalty_paid_amount = loan_info_header_data
+            .penalty_paid_amount
+            .checked_add(late_fee_amount)
+            .ok_or(Funds4GoodError::AmountOverflow)?;
+
+        if emi_payer == EmiPayer::Guarantor {
+            loan_info_header_data.guarantor_claim_amount = loan_info_header_data
+                .guarantor_claim_amount
+                .checked_add(accepted_emi_amount)
+                .ok_or(Funds4GoodError::AmountOverflow)?;
+            loan_info_header_data.guarantor_paid_amount = loan_info_header_data
+                .guarantor_paid_amount
+                .checked_add(accepted_emi_amount)
+                .ok_or(Funds4GoodError::AmountOverflow)?;
+        }
+
+        if is_installment_on_time {
+            borrower_data.credit_score = borrower_data
+                .credit_score
+                .saturating_add(ON_TIME_EMI_CREDIT_SCORE_REWARD);
+        } else {
+            borrower_data.credit_score = borrower_data
+                .credit_score
+                .saturating_sub(LATE_EMI_CREDIT_SCORE_PENALTY);
+        }
+        BorrowerAccount::pack_versioned(
+            borrower_data,
+            &mut borrower_storage_account.data.borrow_mut(),
+        )?;
+
+        if loan_info_header_data.repaid_amount_by_borrower
+            >= loan_info_header_data.total_loan_amount
+        {
+            loan_info_header_data.loan_status = LoanStatus::Repaid as u8;
+        }
+
+        let emi_paid_event = Funds4GoodEvent::EmiPaid {
+            loan_info_pubkey: *loan_info_storage_account.key,
+            payer_pubkey: *payer_main_account.key,
+            installment_amount,
+            late_fee_amount,
+            repaid_amount_by_borrower: loan_info_header_data.repaid_amount_by_borrower,
+            is_on_time: is_installment_on_time,
+            loan_status: loan_info_header_data.loan_status,
+        };
+
+        loan_info.push_repayment(
+            &mut loan_info_header_data,
+            LoanInfoAccRepaymentData {
+                emi_repayment_timestamp: now,
+                emi_repayment_amount: accepted_emi_amount,
+            },
+        )?;
+
         let Funds4Good_coin_vault_account_data_before =
             TokenAccount::unpack(&Funds4Good_coin_vault_account.data.borrow())?;
-            let (pda_Funds4Good_vault, _bump_seed) =
+        let (pda_Funds4Good_vault, _bump_seed) =
             Pubkey::find_program_address(&[b"Funds4GoodFinance"], program_id);
 
         if Funds4Good_coin_vault_account_data_before.owner != pda_Funds4Good_vault {
             return Err(Funds4GoodError::Funds4GoodVaultAccountDoesNotMatched.into());
         }
         let Funds4Good_coin_vault_balance_before = Funds4Good_coin_vault_account_data_before.amount;
+
+        // servicing fee goes directly from borrower to treasury, rest of the installment goes to vault for lenders
+        let servicing_fee_amount = Self::calculate_fee_amount(
+            installment_amount,
+            mint_vault_registry_data.servicing_fee_bps,
+        )?;
+        // reserve slice goes directly from borrower to reserve vault
+        let reserve_contribution_amount = match &reserve_pool_data {
+            Some(reserve_pool_data) => {
+                Self::calculate_fee_amount(installment_amount, reserve_pool_data.reserve_fee_bps)?
+            }
+            None => 0,
+        };
+        let emi_amount_for_lenders = installment_amount
+            .checked_sub(servicing_fee_amount)
+            .ok_or(Funds4GoodError::AmountUnderflow)?
+            .checked_sub(reserve_contribution_amount)
+            .ok_or(Funds4GoodError::AmountUnderflow)?
+            .checked_add(late_fee_amount_for_lenders)
+            .ok_or(Funds4GoodError::AmountOverflow)?
+            .checked_add(interest_amount)
+            .ok_or(Funds4GoodError::AmountOverflow)?;
+        let treasury_amount = servicing_fee_amount
+            .checked_add(late_fee_amount_for_treasury)
+            .ok_or(Funds4GoodError::AmountOverflow)?;
+
         let transfer_emi_amount_to_vault_ix = spl_token::instruction::transfer(
             token_program.key,
-            borrower_Funds4Good_coin_account_to_debit.key,
+            payer_Funds4Good_coin_account_to_debit.key,
             Funds4Good_coin_vault_account.key,
-            borrower_main_account.key,
+            payer_main_account.key,
             &[],
-            emi_amount_to_pay_input,
+            emi_amount_for_lenders,
         )?;
 
-        msg!("Calling the token program to transfer emi amount to vault...");
         msg!(
-            "amount of Funds4Good coin tokens to transfer {}, borrower debit key {}",
-            (emi_amount_to_pay_input as f64 / Funds4Good_COIN_DECIMALS as f64),
-            borrower_Funds4Good_coin_account_to_debit.key.to_string()
+            "Calling the token program to transfer emi amount to vault, payer {}, borrower {}...",
+            payer_main_account.key,
+            loan_info_header_data.borrower_main_acc_pubkey
+        );
+        msg!(
+            "amount of tokens to transfer {}, late fee {}, interest {}, borrower debit key {}",
+            (accepted_emi_amount as f64 / coin_unit as f64),
+            (late_fee_amount as f64 / coin_unit as f64),
+            (interest_amount as f64 / coin_unit as f64),
+            payer_Funds4Good_coin_account_to_debit.key.to_string()
         );
 
         invoke(
             &transfer_emi_amount_to_vault_ix,
             &[
-                borrower_Funds4Good_coin_account_to_debit.clone(),
+                payer_Funds4Good_coin_account_to_debit.clone(),
                 Funds4Good_coin_vault_account.clone(),
-                borrower_main_account.clone(),
+                payer_main_account.clone(),
                 token_program.clone(),
             ],
         )?;
@@ -409,134 +858,114 @@ impl Processor {
         );
         let vault_balance_increased = Funds4Good_coin_vault_balance_after
             .checked_sub(Funds4Good_coin_vault_balance_before)
-            .unwrap();
+            .ok_or(Funds4GoodError::AmountUnderflow)?;
 
-        if vault_balance_increased != emi_amount_to_pay_input {
+        if vault_balance_increased != emi_amount_for_lenders {
             return Err(Funds4GoodError::ExpectedAmountMismatch.into());
         }
 
-        let loan_info_storage_account = next_account_info(account_info_iter)?;
+        let lenders_data_storage_account = next_account_info(account_info_iter)?;
 
-        if loan_info_storage_account.owner != program_id {
+        if lenders_data_storage_account.owner != program_id {
             return Err(Funds4GoodError::WrongAccountPassed.into());
         }
 
-        let mut loan_info_data_byte_array = loan_info_storage_account.try_borrow_mut_data()?;
-        let mut loan_info_header_data: LoanInfoAccDataHeader = state::unpack_to_loan_info_header(
-            &loan_info_data_byte_array
-                [state::LOAN_INFO_HEADER_START_INDEX..state::LOAN_INFO_HEADER_END_INDEX],
-        )
-        .unwrap();
+        if mint_vault_registry_data.lenders_storage_acc_pubkey != *lenders_data_storage_account.key {
+            return Err(Funds4GoodError::LendersStorageAccountDoesNotMatched.into());
+        }
 
-        if loan_info_header_data.acc_type != AccTypes::LoanInfoAcc as u8 {
-            return Err(Funds4GoodError::ExpectedAccountTypeMismatched.into());
+        let mut lenders_storage_data_byte_array =
+            lenders_data_storage_account.try_borrow_mut_data()?;
+        let mut lenders_storage = LendersStorageAccount::new(&mut lenders_storage_data_byte_array)?;
+
+        // emi is split in proportion to the amount each lender lent to this loan
+        Self::credit_lenders_pro_rata(
+            &loan_info,
+            &mut lenders_storage,
+            &loan_info_header_data,
+            &mut mint_vault_registry_data,
+            vault_balance_increased,
+        )?;
+
+        loan_info.set_header(loan_info_header_data)?;
+
+        if reserve_contribution_amount > 0 {
+            let transfer_reserve_contribution_ix = spl_token::instruction::transfer(
+                token_program.key,
+                payer_Funds4Good_coin_account_to_debit.key,
+                reserve_vault_account.key,
+                payer_main_account.key,
+                &[],
+                reserve_contribution_amount,
+            )?;
+
+            msg!(
+                "Calling the token program to transfer reserve contribution {} to reserve vault...",
+                (reserve_contribution_amount as f64 / coin_unit as f64)
+            );
+            invoke(
+                &transfer_reserve_contribution_ix,
+                &[
+                    payer_Funds4Good_coin_account_to_debit.clone(),
+                    reserve_vault_account.clone(),
+                    payer_main_account.clone(),
+                    token_program.clone(),
+                ],
+            )?;
         }
 
-        if loan_info_header_data
-            .total_loan_amount
-            .checked_div(loan_info_header_data.number_of_emis_needed_to_repay_the_loan as u64)
-            .unwrap()
-            > emi_amount_to_pay_input
-        {
-            return Err(Funds4GoodError::ExpectedAmountMismatch.into());
+        if let Some(mut reserve_pool_data) = reserve_pool_data {
+            reserve_pool_data.pool_balance = reserve_pool_data
+                .pool_balance
+                .checked_add(reserve_contribution_amount)
+                .ok_or(Funds4GoodError::AmountOverflow)?;
+            reserve_pool_data.total_contributed_amount = reserve_pool_data
+                .total_contributed_amount
+                .checked_add(reserve_contribution_amount)
+                .ok_or(Funds4GoodError::AmountOverflow)?;
+            reserve_pool_data.total_outstanding_principal = reserve_pool_data
+                .total_outstanding_principal
+                .checked_sub(installment_amount)
+                .ok_or(Funds4GoodError::AmountUnderflow)?;
+            reserve_pool_data.update_coverage_ratio();
+            ReservePoolAccount::pack(reserve_pool_data, &mut reserve_pool_account.data.borrow_mut())?;
         }
 
-        if loan_info_header_data.repaid_amount_by_borrower
-            >= loan_info_header_data.total_loan_amount
-        {
-            return Err(Funds4GoodError::LoanAlreadyPaid.into());
+        // treasury account is only needed when servicing fee or treasury share of late fee is due
+        if treasury_amount > 0 {
+            let treasury_account = next_account_info(account_info_iter)?;
+            Self::verify_treasury_account(treasury_account, &mint_vault_registry_data)?;
+
+            let transfer_servicing_fee_to_treasury_ix = spl_token::instruction::transfer(
+                token_program.key,
+                payer_Funds4Good_coin_account_to_debit.key,
+                treasury_account.key,
+                payer_main_account.key,
+                &[],
+                treasury_amount,
+            )?;
+
+            msg!(
+                "Calling the token program to transfer servicing and late fee {} to treasury...",
+                (treasury_amount as f64 / coin_unit as f64)
+            );
+            invoke(
+                &transfer_servicing_fee_to_treasury_ix,
+                &[
+                    payer_Funds4Good_coin_account_to_debit.clone(),
+                    treasury_account.clone(),
+                    payer_main_account.clone(),
+                    token_program.clone(),
+                ],
+            )?;
         }
-        loan_info_header_data.repaid_amount_by_borrower = loan_info_header_data
-            .repaid_amount_by_borrower
-            .checked_add(vault_balance_increased)
-            .unwrap();
 
-        let loan_info_repayment_timestamp_si = 9116usize
-            + (loan_info_header_data.next_index_to_store_repayment_info as usize) * (16usize);
-        let loan_info_repayment_timestamp_ei = loan_info_repayment_timestamp_si + 8usize;
-        let loan_info_repayment_amount_ei = loan_info_repayment_timestamp_ei + 8usize;
-        let now = Clock::get()?.unix_timestamp as u64;
-        loan_info_data_byte_array
-            [loan_info_repayment_timestamp_si..loan_info_repayment_timestamp_ei]
-            .copy_from_slice(&now.to_le_bytes());
-        loan_info_data_byte_array[loan_info_repayment_timestamp_ei..loan_info_repayment_amount_ei]
-            .copy_from_slice(&vault_balance_increased.to_le_bytes());
-        loan_info_header_data.next_index_to_store_repayment_info = loan_info_header_data
-            .next_index_to_store_repayment_info
-            .checked_add(1u8)
-            .unwrap();
-
-            let lenders_data_storage_account = next_account_info(account_info_iter)?;
-
-            if lenders_data_storage_account.owner != program_id {
-                return Err(Funds4GoodError::WrongAccountPassed.into());
-            }
-    
-            let mut lenders_storage_data_byte_array =
-                lenders_data_storage_account.try_borrow_mut_data()?;
-    
-            if lenders_storage_data_byte_array[0] != AccTypes::LendersAcc as u8 {
-                return Err(Funds4GoodError::ExpectedAccountTypeMismatched.into());
-            }
-    
-            if lenders_storage_data_byte_array[1] != 1u8 {
-                return Err(Funds4GoodError::ExpectedLendersAccNumNotMatched.into());
-            }
-    
-        let emi_amount_distributed_per_lender: u64 = vault_balance_increased.checked_div(loan_info_header_data.next_index_to_store_lender_data as u64).unwrap();
-
-        for i in 0..loan_info_header_data.next_index_to_store_lender_data {
-            let loan_info_lender_data_si: usize = state::LOAN_INFO_HEADER_DATA_BYTES
-            + (i as usize)
-                * state::LOAN_INFO_ACC_LENDER_DATA_BYTES;
-        let loan_info_lender_data_ei: usize =
-            loan_info_lender_data_si + state::LOAN_INFO_ACC_LENDER_DATA_BYTES;
-        let loan_info_lender_data: LoanInfoAccLendersData =
-            state::unpack_to_loan_info_acc_lender_data(
-                &loan_info_data_byte_array[loan_info_lender_data_si..loan_info_lender_data_ei],
-            )
-            .unwrap();
-        
-
-        // lender_id_input can vary from 0 to 49_999 included
-        let lender_si_in_lenders_data_byte_array: usize = 2usize
-            + (loan_info_lender_data.lender_id as usize)
-                .checked_mul(state::LENDER_ACC_DATA_SIZE)
-                .unwrap();
-        let lender_ei_in_lenders_data_byte_array: usize =
-            lender_si_in_lenders_data_byte_array + state::LENDER_ACC_DATA_SIZE;
-        let mut lender_acc_data: LenderAccountData = state::unpack_to_lender_account_data(
-            &lenders_storage_data_byte_array
-                [lender_si_in_lenders_data_byte_array..lender_ei_in_lenders_data_byte_array],
-        )
-        .unwrap();
+        MintVaultRegistryAccount::pack(
+            mint_vault_registry_data,
+            &mut mint_vault_registry_account.data.borrow_mut(),
+        )?;
 
-        // update lender data in LendersAccountDataArray
-        lender_acc_data.total_lending_amount = lender_acc_data
-            .total_lending_amount
-            .checked_add(vault_balance_increased as u128)
-            .unwrap();
-        lender_acc_data.total_unique_lending_amount = lender_acc_data
-            .total_unique_lending_amount
-            .checked_add(vault_balance_increased)
-            .unwrap();
-
-        lender_acc_data.amount_to_withdraw_or_lend = lender_acc_data.amount_to_withdraw_or_lend.checked_add(emi_amount_distributed_per_lender).unwrap();
-
-        state::pack_to_lender_account_data(
-            lender_acc_data,
-            &mut lenders_storage_data_byte_array
-                [lender_si_in_lenders_data_byte_array..lender_ei_in_lenders_data_byte_array],
-        )
-        .unwrap();
-        }
-
-        state::pack_to_loan_info_header(
-            loan_info_header_data,
-            &mut loan_info_data_byte_array
-                [state::LOAN_INFO_HEADER_START_INDEX..state::LOAN_INFO_HEADER_END_INDEX],
-        )
-        .unwrap();
+        emi_paid_event.emit();
 
         Ok(())
     }
@@ -547,6 +976,7 @@ impl Processor {
         num_emis_needed_to_repay_the_loan_input: u16,
         num_days_for_fundraising_input: u16,
         total_loan_amount_input: u64,
+        min_funding_amount_input: u64,
         program_id: &Pubkey,
     ) -> ProgramResult {
         // also update borrower storage account
@@ -556,7 +986,35 @@ impl Processor {
         if !guarantor_main_account.is_signer {
             return Err(ProgramError::MissingRequiredSignature);
         }
-       let borrower_main_account = next_account_info(account_info_iter)?;
+        let borrower_main_account = next_account_info(account_info_iter)?;
+
+        if num_emis_needed_to_repay_the_loan_input == 0
+            || num_emis_needed_to_repay_the_loan_input as usize > state::LOAN_INFO_MAX_REPAYMENTS
+        {
+            return Err(Funds4GoodError::InvalidNumberOfEmis.into());
+        }
+
+        // loan is denominated in the mint of passed registry account
+        let mint_vault_registry_account = next_account_info(account_info_iter)?;
+        let mint_vault_registry_data =
+            Self::verify_mint_vault_registry_account(mint_vault_registry_account, program_id)?;
+        let coin_unit = Self::coin_unit(mint_vault_registry_data.mint_decimals)?;
+
+        if total_loan_amount_input < MIN_LOAN_COINS * coin_unit
+            || total_loan_amount_input > MAX_LOAN_COINS * coin_unit
+            || !total_loan_amount_input.is_multiple_of(LOAN_COINS_MULTIPLE * coin_unit)
+        {
+            return Err(Funds4GoodError::InvalidLoanAmount.into());
+        }
+
+        // partial funding is optional, when set it can't be below minimum loan amount
+        if min_funding_amount_input != 0
+            && (min_funding_amount_input < MIN_LOAN_COINS * coin_unit
+                || min_funding_amount_input > total_loan_amount_input
+                || !min_funding_amount_input.is_multiple_of(LENDING_COINS_MULTIPLE * coin_unit))
+        {
+            return Err(Funds4GoodError::InvalidMinFundingAmount.into());
+        }
 
         let loan_info_storage_account = next_account_info(account_info_iter)?;
 
@@ -579,8 +1037,10 @@ impl Processor {
         }
 
         let mut loan_info_data_byte_array = loan_info_storage_account.data.borrow_mut();
+        let mut loan_info = LoanInfoAccount::new(&mut loan_info_data_byte_array)?;
+        let mut loan_info_header_data: LoanInfoAccDataHeader = loan_info.header_unchecked()?;
 
-        if loan_info_data_byte_array[0] != 0 {
+        if loan_info_header_data.acc_type != 0 {
             return Err(Funds4GoodError::LoanInfoDataAlreadyInitialized.into());
         }
 
@@ -602,7 +1062,7 @@ impl Processor {
         */
 
         let mut borrower_data =
-            BorrowerAccount::unpack(&borrower_storage_account.data.try_borrow().unwrap())?;
+            BorrowerAccount::unpack_versioned(&borrower_storage_account.try_borrow_data()?)?;
         if borrower_data.is_active_loan != 0 {
             return Err(Funds4GoodError::BorrowerAlreadyHaveActiveLoan.into());
         }
@@ -610,56 +1070,70 @@ impl Processor {
             return Err(Funds4GoodError::ExpectedAccountTypeMismatched.into());
         }
         borrower_data.active_loan_address = *loan_info_storage_account.key;
-        BorrowerAccount::pack(
+        BorrowerAccount::pack_versioned(
             borrower_data,
-            &mut borrower_storage_account.data.try_borrow_mut().unwrap(),
+            &mut borrower_storage_account.try_borrow_mut_data()?,
         )?;
 
-        let num_seconds_in_one_day: u64 = 86400u64;
+        let num_seconds_in_one_day: u64 = NUM_SECONDS_IN_ONE_DAY;
         let now = Clock::get()?.unix_timestamp as u64;
-        let mut loan_info_header_data: LoanInfoAccDataHeader = state::unpack_unchecked_to_loan_info_header(
-            &loan_info_data_byte_array
-                [state::LOAN_INFO_HEADER_START_INDEX..state::LOAN_INFO_HEADER_END_INDEX],
-        )
-        .unwrap();
         loan_info_header_data.acc_type = AccTypes::LoanInfoAcc as u8;
         loan_info_header_data.borrower_main_acc_pubkey = *borrower_main_account.key;
         loan_info_header_data.guarantor_main_acc_pubkey = *guarantor_main_account.key;
-        loan_info_header_data.loan_approval_timestamp = now.clone();
+        loan_info_header_data.loan_approval_timestamp = now;
         let calculate_fundraising_period_ending_timestamp: u64 = now
             .checked_add(
                 (num_days_for_fundraising_input as u64)
                     .checked_mul(num_seconds_in_one_day)
-                    .unwrap(),
+                    .ok_or(Funds4GoodError::AmountOverflow)?,
             )
-            .unwrap();
+            .ok_or(Funds4GoodError::AmountOverflow)?;
         loan_info_header_data.fundraising_period_ending_timestamp =
             calculate_fundraising_period_ending_timestamp;
         // a user can pay upto 5 days late, after that his credit score will decrease
-        loan_info_header_data.first_repayment_last_date_timestamp =
-            (num_days_left_for_first_repayment_input
-                .checked_add(5u16)
-                .unwrap() as u64)
-                .checked_mul(num_seconds_in_one_day)
-                .unwrap();
+        loan_info_header_data.first_repayment_last_date_timestamp = now
+            .checked_add(
+                (num_days_left_for_first_repayment_input as u64)
+                    .checked_add(5u64)
+                    .ok_or(Funds4GoodError::AmountOverflow)?
+                    .checked_mul(num_seconds_in_one_day)
+                    .ok_or(Funds4GoodError::AmountOverflow)?,
+            )
+            .ok_or(Funds4GoodError::AmountOverflow)?;
         loan_info_header_data.total_loan_amount = total_loan_amount_input;
         loan_info_header_data.number_of_emis_needed_to_repay_the_loan =
             num_emis_needed_to_repay_the_loan_input as u8;
-
-        state::pack_to_loan_info_header(
-            loan_info_header_data,
-            &mut loan_info_data_byte_array
-                [state::LOAN_INFO_HEADER_START_INDEX..state::LOAN_INFO_HEADER_END_INDEX],
-        )
-        .unwrap();
+        loan_info_header_data.loan_mint_pubkey = mint_vault_registry_data.mint_pubkey;
+        loan_info_header_data.loan_status = LoanStatus::Fundraising as u8;
+        loan_info_header_data.min_funding_amount = min_funding_amount_input;
+
+        let loan_created_event = Funds4GoodEvent::LoanCreated {
+            loan_info_pubkey: *loan_info_storage_account.key,
+            borrower_pubkey: *borrower_main_account.key,
+            guarantor_pubkey: *guarantor_main_account.key,
+            mint_pubkey: mint_vault_registry_data.mint_pubkey,
+            total_loan_amount: total_loan_amount_input,
+            min_funding_amount: min_funding_amount_input,
+            number_of_emis: loan_info_header_data.number_of_emis_needed_to_repay_the_loan,
+            fundraising_period_ending_timestamp: loan_info_header_data
+                .fundraising_period_ending_timestamp,
+            first_repayment_last_date_timestamp: loan_info_header_data
+                .first_repayment_last_date_timestamp,
+        };
+
+        loan_info.set_header(loan_info_header_data)?;
+        loan_info.set_layout_version(state::LOAN_INFO_LAYOUT_VERSION);
+
+        loan_created_event.emit();
 
         Ok(())
     }
 
-    // This will credit all free funds to lender wallet
+    // This will credit amount_to_withdraw_input from lender free funds to lender wallet
     fn process_withdraw_lender_free_wallet_funds(
         accounts: &[AccountInfo],
         lender_id_input: u32,
+        amount_to_withdraw_input: u64,
         program_id: &Pubkey,
     ) -> ProgramResult {
         let account_info_iter = &mut accounts.iter();
@@ -674,9 +1148,16 @@ impl Processor {
         // Funds4Good_coin_vault_account is program controlled vault account and can be only controlled by our deployed program for debit funds or any kind of operation
         let Funds4Good_coin_vault_account = next_account_info(account_info_iter)?;
 
-        /*
-        check if right vault is passed 
-        */
+        let mint_vault_registry_account = next_account_info(account_info_iter)?;
+        let mut mint_vault_registry_data =
+            Self::verify_mint_vault_registry_account(mint_vault_registry_account, program_id)?;
+        Self::verify_vault_account(Funds4Good_coin_vault_account, &mint_vault_registry_data)?;
+        Self::verify_user_token_account(
+            lender_Funds4Good_coin_account_to_credit,
+            lender_main_account.key,
+            &mint_vault_registry_data.mint_pubkey,
+        )?;
+        let coin_unit = Self::coin_unit(mint_vault_registry_data.mint_decimals)?;
 
         let lenders_data_storage_account = next_account_info(account_info_iter)?;
 
@@ -684,53 +1165,42 @@ impl Processor {
             return Err(Funds4GoodError::WrongAccountPassed.into());
         }
 
+        if mint_vault_registry_data.lenders_storage_acc_pubkey != *lenders_data_storage_account.key {
+            return Err(Funds4GoodError::LendersStorageAccountDoesNotMatched.into());
+        }
+
         let mut lenders_storage_data_byte_array =
             lenders_data_storage_account.try_borrow_mut_data()?;
+        let mut lenders_storage = LendersStorageAccount::new(&mut lenders_storage_data_byte_array)?;
 
-        if lenders_storage_data_byte_array[0] != AccTypes::LendersAcc as u8 {
-            return Err(Funds4GoodError::ExpectedAccountTypeMismatched.into());
-        }
-
-        if lenders_storage_data_byte_array[1] != 1u8 {
-            return Err(Funds4GoodError::ExpectedLendersAccNumNotMatched.into());
-        }
+        let mut lender_acc_data: LenderAccountData = lenders_storage.lender(lender_id_input)?;
 
-        if lender_id_input > 49_999u32 {
+        if lender_acc_data.is_account_active != 1u8
+            || lender_acc_data.lender_main_acc_pubkey != *lender_main_account.key
+        {
             return Err(Funds4GoodError::InvalidLenderIdInput.into());
         }
 
-        // lender_id_input can vary from 0 to 49_999 included
-        let lender_si_in_lenders_data_byte_array: usize = 2usize
-            + (lender_id_input as usize)
-                .checked_mul(state::LENDER_ACC_DATA_SIZE)
-                .unwrap();
-        let lender_ei_in_lenders_data_byte_array: usize =
-            lender_si_in_lenders_data_byte_array + state::LENDER_ACC_DATA_SIZE;
-        let mut lender_acc_data: LenderAccountData = state::unpack_to_lender_account_data(
-            &lenders_storage_data_byte_array
-                [lender_si_in_lenders_data_byte_array..lender_ei_in_lenders_data_byte_array],
-        )
-        .unwrap();
+        if amount_to_withdraw_input == 0 {
+            return Err(Funds4GoodError::InvalidWithdrawAmount.into());
+        }
 
-        if lender_acc_data.is_account_active != 1u8
-            && lender_acc_data.lender_main_acc_pubkey != *lender_main_account.key
-        {
-            return Err(Funds4GoodError::InvalidLenderIdInput.into());
+        if amount_to_withdraw_input > lender_acc_data.amount_to_withdraw_or_lend {
+            return Err(Funds4GoodError::InsufficientFreeBalance.into());
         }
 
-        let withdraw_amount: u64 = lender_acc_data.amount_to_withdraw_or_lend;
+        let withdraw_amount: u64 = amount_to_withdraw_input;
         lender_acc_data.total_unique_lending_amount = lender_acc_data
             .total_unique_lending_amount
-            .checked_sub(withdraw_amount.clone())
-            .unwrap();
-        lender_acc_data.amount_to_withdraw_or_lend = 0u64;
-
-        state::pack_to_lender_account_data(
-            lender_acc_data,
-            &mut lenders_storage_data_byte_array
-                [lender_si_in_lenders_data_byte_array..lender_ei_in_lenders_data_byte_array],
-        )
-        .unwrap();
+            .checked_sub(withdraw_amount)
+            .ok_or(Funds4GoodError::AmountUnderflow)?;
+        lender_acc_data.amount_to_withdraw_or_lend = lender_acc_data
+            .amount_to_withdraw_or_lend
+            .checked_sub(withdraw_amount)
+            .ok_or(Funds4GoodError::AmountUnderflow)?;
+        let free_balance_after_withdrawal = lender_acc_data.amount_to_withdraw_or_lend;
+
+        lenders_storage.set_lender(lender_id_input, lender_acc_data)?;
 
         let token_program = next_account_info(account_info_iter)?;
 
@@ -760,8 +1230,8 @@ impl Processor {
         )?;
         msg!("Calling the token program to transfer withdraw amount to lender...");
         msg!(
-            "amount of Funds4Good coin tokens to transfer {}, lender credit key {}",
-            (withdraw_amount as f64 / Funds4Good_COIN_DECIMALS as f64),
+            "amount of tokens to transfer {}, lender credit key {}",
+            (withdraw_amount as f64 / coin_unit as f64),
             lender_Funds4Good_coin_account_to_credit.key.to_string()
         );
         invoke_signed(
@@ -775,196 +1245,2341 @@ impl Processor {
             &[&[&b"Funds4GoodFinance"[..], &[bump_seed]]],
         )?;
 
-        let Funds4Good_coin_vault_account_data_after =
-            TokenAccount::unpack(&Funds4Good_coin_vault_account.data.borrow())?;
-        let Funds4Good_coin_vault_balance_after = Funds4Good_coin_vault_account_data_after.amount;
-        msg!(
-            "Funds4GoodCoin vault balance after: {}",
-            Funds4Good_coin_vault_balance_after
-        );
+        let Funds4Good_coin_vault_account_data_after =
+            TokenAccount::unpack(&Funds4Good_coin_vault_account.data.borrow())?;
+        let Funds4Good_coin_vault_balance_after = Funds4Good_coin_vault_account_data_after.amount;
+        msg!(
+            "Funds4GoodCoin vault balance after: {}",
+            Funds4Good_coin_vault_balance_after
+        );
+
+        let vault_balance_decreased = Funds4Good_coin_vault_balance_before
+            .checked_sub(Funds4Good_coin_vault_balance_after)
+            .ok_or(Funds4GoodError::AmountUnderflow)?;
+
+        if vault_balance_decreased != withdraw_amount {
+            return Err(Funds4GoodError::ExpectedAmountMismatch.into());
+        }
+
+        mint_vault_registry_data.vault_liabilities = mint_vault_registry_data
+            .vault_liabilities
+            .checked_sub(withdraw_amount)
+            .ok_or(Funds4GoodError::AmountUnderflow)?;
+        MintVaultRegistryAccount::pack(
+            mint_vault_registry_data,
+            &mut mint_vault_registry_account.data.borrow_mut(),
+        )?;
+
+        // history is only recorded once lender has created the account
+        let lender_withdrawal_history_account = next_account_info(account_info_iter)?;
+        Self::record_lender_withdrawal(
+            lender_withdrawal_history_account,
+            lender_main_account.key,
+            withdraw_amount,
+            free_balance_after_withdrawal,
+            program_id,
+        )?;
+
+        Funds4GoodEvent::Withdrawn {
+            lender_pubkey: *lender_main_account.key,
+            lender_id: lender_id_input,
+            amount: withdraw_amount,
+            free_balance_after_withdrawal,
+        }
+        .emit();
+
+        Ok(())
+    }
+
+    // lender withdrawal history account is initialized on first withdrawal after lender creates it, after that oldest record
+    // gets overwritten once all slots are used. Nothing is recorded while the account at the seed address is not created
+    fn record_lender_withdrawal(
+        lender_withdrawal_history_account: &AccountInfo,
+        lender_main_acc_pubkey: &Pubkey,
+        withdraw_amount: u64,
+        free_balance_after_withdrawal: u64,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let expected_lender_withdrawal_history_pubkey = Pubkey::create_with_seed(
+            lender_main_acc_pubkey,
+            "Funds4GoodFinanceWithdrawals",
+            program_id,
+        )?;
+
+        if expected_lender_withdrawal_history_pubkey != *lender_withdrawal_history_account.key {
+            return Err(Funds4GoodError::AccountMismatched.into());
+        }
+
+        if lender_withdrawal_history_account.data_is_empty() {
+            return Ok(());
+        }
+
+        if lender_withdrawal_history_account.owner != program_id {
+            return Err(Funds4GoodError::WrongAccountPassed.into());
+        }
+
+        if lender_withdrawal_history_account.data_len()
+            != state::LENDER_WITHDRAWAL_HISTORY_ACC_DATA_SIZE
+        {
+            return Err(Funds4GoodError::DataSizeNotMatched.into());
+        }
+
+        let mut withdrawal_history_byte_array =
+            lender_withdrawal_history_account.try_borrow_mut_data()?;
+
+        let mut withdrawal_history_header = state::unpack_to_lender_withdrawal_history_header(
+            &withdrawal_history_byte_array[0..state::LENDER_WITHDRAWAL_HISTORY_HEADER_DATA_BYTES],
+        )?;
+
+        if withdrawal_history_header.acc_type == 0 {
+            let rent = Rent::get()?;
+
+            // data is already borrowed mutably here, its size was checked above
+            if !rent.is_exempt(
+                lender_withdrawal_history_account.lamports(),
+                state::LENDER_WITHDRAWAL_HISTORY_ACC_DATA_SIZE,
+            ) {
+                return Err(Funds4GoodError::NotRentExempt.into());
+            }
+
+            withdrawal_history_header.acc_type = AccTypes::LenderWithdrawalHistoryAcc as u8;
+            withdrawal_history_header.lender_main_acc_pubkey = *lender_main_acc_pubkey;
+        } else if withdrawal_history_header.acc_type
+            != AccTypes::LenderWithdrawalHistoryAcc as u8
+        {
+            return Err(Funds4GoodError::ExpectedAccountTypeMismatched.into());
+        }
+
+        if withdrawal_history_header.next_index_to_store_withdrawal as usize
+            >= state::LENDER_WITHDRAWAL_HISTORY_CAPACITY
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let withdrawal_record_si: usize = state::LENDER_WITHDRAWAL_HISTORY_HEADER_DATA_BYTES
+            + (withdrawal_history_header.next_index_to_store_withdrawal as usize)
+                * state::LENDER_WITHDRAWAL_RECORD_DATA_BYTES;
+        let withdrawal_record_ei: usize =
+            withdrawal_record_si + state::LENDER_WITHDRAWAL_RECORD_DATA_BYTES;
+
+        let now = Clock::get()?.unix_timestamp as u64;
+        state::pack_to_lender_withdrawal_record(
+            state::LenderWithdrawalRecord {
+                withdrawal_timestamp: now,
+                withdrawn_amount: withdraw_amount,
+                free_balance_after_withdrawal,
+            },
+            &mut withdrawal_history_byte_array[withdrawal_record_si..withdrawal_record_ei],
+        )?;
+
+        withdrawal_history_header.next_index_to_store_withdrawal =
+            ((withdrawal_history_header.next_index_to_store_withdrawal as usize + 1)
+                % state::LENDER_WITHDRAWAL_HISTORY_CAPACITY) as u8;
+        withdrawal_history_header.total_withdrawals_count = withdrawal_history_header
+            .total_withdrawals_count
+            .checked_add(1u32)
+            .ok_or(Funds4GoodError::AmountOverflow)?;
+
+        state::pack_to_lender_withdrawal_history_header(
+            withdrawal_history_header,
+            &mut withdrawal_history_byte_array[0..state::LENDER_WITHDRAWAL_HISTORY_HEADER_DATA_BYTES],
+        )?;
+
+        Ok(())
+    }
+
+    fn process_withdraw_collected_loan_funds(
+        accounts: &[AccountInfo],
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let borrower_main_account = next_account_info(account_info_iter)?;
+
+        if !borrower_main_account.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let borrower_Funds4Good_ata_to_credit = next_account_info(account_info_iter)?;
+
+        let Funds4Good_coin_vault_account = next_account_info(account_info_iter)?;
+
+        let mint_vault_registry_account = next_account_info(account_info_iter)?;
+        let mut mint_vault_registry_data =
+            Self::verify_mint_vault_registry_account(mint_vault_registry_account, program_id)?;
+        Self::verify_vault_account(Funds4Good_coin_vault_account, &mint_vault_registry_data)?;
+        Self::verify_user_token_account(
+            borrower_Funds4Good_ata_to_credit,
+            borrower_main_account.key,
+            &mint_vault_registry_data.mint_pubkey,
+        )?;
+        let coin_unit = Self::coin_unit(mint_vault_registry_data.mint_decimals)?;
+
+        let token_program = next_account_info(account_info_iter)?;
+
+        if token_program.key != &spl_token::id() {
+            return Err(Funds4GoodError::InvalidTokenProgram.into());
+        }
+
+        let loan_info_storage_account = next_account_info(account_info_iter)?;
+
+        if loan_info_storage_account.owner != program_id {
+            return Err(Funds4GoodError::WrongAccountPassed.into());
+        }
+
+        // update lender payment in LoanInfoAccData
+        let mut loan_info_data_byte_array = loan_info_storage_account.try_borrow_mut_data()?;
+        let mut loan_info = LoanInfoAccount::new(&mut loan_info_data_byte_array)?;
+        let mut loan_info_header_data: LoanInfoAccDataHeader = loan_info.header()?;
+
+        if loan_info_header_data.acc_type != AccTypes::LoanInfoAcc as u8 {
+            return Err(Funds4GoodError::ExpectedAccountTypeMismatched.into());
+        }
+
+        if loan_info_header_data.loan_mint_pubkey != mint_vault_registry_data.mint_pubkey {
+            return Err(Funds4GoodError::LoanMintDoesNotMatched.into());
+        }
+
+        if loan_info_header_data.loan_status == LoanStatus::Cancelled as u8 {
+            return Err(Funds4GoodError::LoanCancelled.into());
+        }
+        if loan_info_header_data.loan_status != LoanStatus::Fundraising as u8 {
+            return Err(Funds4GoodError::CollectedLoanFundsAlreadyWithdrawn.into());
+        }
+
+        // borrower can accept a partially funded loan once fundraising is over and minimum funding is reached,
+        // emi schedule then follows the raised amount
+        if loan_info_header_data.total_amount_lended < loan_info_header_data.total_loan_amount {
+            let now = Clock::get()?.unix_timestamp as u64;
+            if !Self::is_partial_funding_acceptable(&loan_info_header_data, now) {
+                return Err(Funds4GoodError::CollectedLoanFundsAlreadyWithdrawn.into());
+            }
+            msg!(
+                "accepting partial funding of {} out of {}",
+                loan_info_header_data.total_amount_lended,
+                loan_info_header_data.total_loan_amount
+            );
+            loan_info_header_data.total_loan_amount = loan_info_header_data.total_amount_lended;
+        }
+
+        if loan_info_header_data.borrower_main_acc_pubkey != *borrower_main_account.key {
+            return Err(Funds4GoodError::BorrowerAccountMismatched.into());
+        }
+
+        // borrower can withdraw total_amount_lended, after withdrawing we will set it to 0, so that he can't withdraw second time. We can also use a extra variable to store if funds
+        // withdrawn or not
+
+        let Funds4Good_coin_vault_account_data_before =
+            TokenAccount::unpack(&Funds4Good_coin_vault_account.data.borrow())?;
+        let Funds4Good_coin_vault_balance_before = Funds4Good_coin_vault_account_data_before.amount;
+
+        let pda_account = next_account_info(account_info_iter)?;
+
+        // we can also store bump_seed to save computations
+        let (pda, bump_seed) = Pubkey::find_program_address(&[b"Funds4GoodFinance"], program_id);
+        if pda != *pda_account.key {
+            return Err(Funds4GoodError::PdaAccountDoesNotMatched.into());
+        }
+
+        // loan is covered by reserve pool only when it is configured at disbursement
+        let reserve_pool_account = next_account_info(account_info_iter)?;
+        let reserve_pool_data = Self::verify_optional_reserve_pool_account(
+            reserve_pool_account,
+            &mint_vault_registry_data,
+            program_id,
+        )?;
+
+        // origination fee is kept from collected loan amount and sent to treasury, borrower gets the rest
+        let origination_fee_amount = Self::calculate_fee_amount(
+            loan_info_header_data.total_amount_lended,
+            mint_vault_registry_data.origination_fee_bps,
+        )?;
+        let amount_to_disburse = loan_info_header_data
+            .total_amount_lended
+            .checked_sub(origination_fee_amount)
+            .ok_or(Funds4GoodError::AmountUnderflow)?;
+
+        let transfer_collected_loan_funds_to_borrower_ix = spl_token::instruction::transfer(
+            token_program.key,
+            Funds4Good_coin_vault_account.key,
+            borrower_Funds4Good_ata_to_credit.key,
+            &pda,
+            &[&pda],
+            amount_to_disburse,
+        )?;
+        msg!("Calling the token program to transfer collected loan amount to borrower...");
+        msg!(
+            "amount of tokens to transfer {}, lender credit key {}",
+            (amount_to_disburse as f64 / coin_unit as f64),
+            borrower_Funds4Good_ata_to_credit.key.to_string()
+        );
+        invoke_signed(
+            &transfer_collected_loan_funds_to_borrower_ix,
+            &[
+                Funds4Good_coin_vault_account.clone(),
+                borrower_Funds4Good_ata_to_credit.clone(),
+                pda_account.clone(),
+                token_program.clone(),
+            ],
+            &[&[&b"Funds4GoodFinance"[..], &[bump_seed]]],
+        )?;
+
+        // treasury account is only needed when origination fee is set for the mint
+        if origination_fee_amount > 0 {
+            let treasury_account = next_account_info(account_info_iter)?;
+            Self::verify_treasury_account(treasury_account, &mint_vault_registry_data)?;
+
+            let transfer_origination_fee_to_treasury_ix = spl_token::instruction::transfer(
+                token_program.key,
+                Funds4Good_coin_vault_account.key,
+                treasury_account.key,
+                &pda,
+                &[&pda],
+                origination_fee_amount,
+            )?;
+            msg!(
+                "Calling the token program to transfer origination fee {} to treasury...",
+                (origination_fee_amount as f64 / coin_unit as f64)
+            );
+            invoke_signed(
+                &transfer_origination_fee_to_treasury_ix,
+                &[
+                    Funds4Good_coin_vault_account.clone(),
+                    treasury_account.clone(),
+                    pda_account.clone(),
+                    token_program.clone(),
+                ],
+                &[&[&b"Funds4GoodFinance"[..], &[bump_seed]]],
+            )?;
+        }
+
+        let Funds4Good_coin_vault_account_data_after =
+            TokenAccount::unpack(&Funds4Good_coin_vault_account.data.borrow())?;
+        let Funds4Good_coin_vault_balance_after = Funds4Good_coin_vault_account_data_after.amount;
+        msg!(
+            "Funds4GoodCoin vault balance after: {}",
+            Funds4Good_coin_vault_balance_after
+        );
+
+        let vault_balance_decreased = Funds4Good_coin_vault_balance_before
+            .checked_sub(Funds4Good_coin_vault_balance_after)
+            .ok_or(Funds4GoodError::AmountUnderflow)?;
+
+        if vault_balance_decreased != loan_info_header_data.total_amount_lended {
+            return Err(Funds4GoodError::ExpectedAmountMismatch.into());
+        }
+
+        // borrower owes the full loan amount even though origination fee was kept from disbursement
+        if let Some(mut reserve_pool_data) = reserve_pool_data {
+            reserve_pool_data.total_outstanding_principal = reserve_pool_data
+                .total_outstanding_principal
+                .checked_add(loan_info_header_data.total_loan_amount)
+                .ok_or(Funds4GoodError::AmountOverflow)?;
+            reserve_pool_data.update_coverage_ratio();
+            ReservePoolAccount::pack(reserve_pool_data, &mut reserve_pool_account.data.borrow_mut())?;
+            loan_info_header_data.is_reserve_covered = 1;
+        }
+
+        // whole lent amount left the vault, disbursed to borrower and origination fee to treasury
+        mint_vault_registry_data.vault_liabilities = mint_vault_registry_data
+            .vault_liabilities
+            .checked_sub(loan_info_header_data.total_amount_lended)
+            .ok_or(Funds4GoodError::AmountUnderflow)?;
+        MintVaultRegistryAccount::pack(
+            mint_vault_registry_data,
+            &mut mint_vault_registry_account.data.borrow_mut(),
+        )?;
+
+        loan_info_header_data.total_amount_lended = 0u64;
+        loan_info_header_data.loan_status = LoanStatus::Active as u8;
+        loan_info_header_data.disbursement_timestamp = Clock::get()?.unix_timestamp as u64;
+
+        loan_info.set_header(loan_info_header_data)?;
+
+        Funds4GoodEvent::Disbursed {
+            loan_info_pubkey: *loan_info_storage_account.key,
+            borrower_pubkey: *borrower_main_account.key,
+            disbursed_amount: amount_to_disburse,
+            origination_fee_amount,
+        }
+        .emit();
+
+        Ok(())
+    }
+
+    fn process_transfer_Funds4Good_vault_account_ownership(
+        accounts: &[AccountInfo],
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let initializer_account = next_account_info(account_info_iter)?;
+
+        if !initializer_account.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let Funds4Good_vault_account = next_account_info(account_info_iter)?;
+
+        let (pda, _nonce) = Pubkey::find_program_address(&[b"Funds4GoodFinance"], program_id);
+
+        let rent = Rent::get()?;
+
+        if !rent.is_exempt(
+            Funds4Good_vault_account.lamports(),
+            Funds4Good_vault_account.data_len(),
+        ) {
+            return Err(Funds4GoodError::NotRentExempt.into());
+        }
+        let token_program = next_account_info(account_info_iter)?;
+
+        if token_program.key != &spl_token::id() {
+            return Err(Funds4GoodError::InvalidTokenProgram.into());
+        }
+
+        let Funds4Good_vault_owner_change_ix = spl_token::instruction::set_authority(
+            token_program.key,
+            Funds4Good_vault_account.key,
+            Some(&pda),
+            spl_token::instruction::AuthorityType::AccountOwner,
+            initializer_account.key,
+            &[initializer_account.key],
+        )?;
+
+        msg!("Calling the token program to transfer Funds4Good vault account ownership to program...");
+        invoke(
+            &Funds4Good_vault_owner_change_ix,
+            &[
+                Funds4Good_vault_account.clone(),
+                initializer_account.clone(),
+                token_program.clone(),
+            ],
+        )?;
+
+        let Funds4Good_config_account = next_account_info(account_info_iter)?;
+        let system_program_account = next_account_info(account_info_iter)?;
+
+        if system_program_account.key != &system_program::id() {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        // program data account is always passed, it is only read when config is created
+        let program_data_account = next_account_info(account_info_iter)?;
+
+        let (pda_config, config_bump_seed) =
+            Pubkey::find_program_address(&[b"Funds4GoodFinanceConfig"], program_id);
+        if pda_config != *Funds4Good_config_account.key {
+            return Err(Funds4GoodError::ConfigAccountDoesNotMatched.into());
+        }
+
+        Self::create_pda_account_if_empty(
+            initializer_account,
+            Funds4Good_config_account,
+            system_program_account,
+            &[&b"Funds4GoodFinanceConfig"[..], &[config_bump_seed]],
+            Funds4GoodConfigAccount::LEN,
+            &rent,
+            program_id,
+        )?;
+
+        let mut Funds4Good_config_data =
+            Funds4GoodConfigAccount::unpack_unchecked(&Funds4Good_config_account.data.borrow())?;
+
+        let Funds4Good_vault_account_data =
+            TokenAccount::unpack(&Funds4Good_vault_account.data.borrow())?;
+
+        // first vault transferred to program is the Funds4Good coin vault and its initializer becomes admin,
+        // after that only admin can register vaults of other mints.
+        // Upgrade authority is checked here, so nobody else can become admin by calling this right after deployment
+        if !Funds4Good_config_data.is_initialized() {
+            Self::verify_upgrade_authority(
+                program_data_account,
+                initializer_account.key,
+                program_id,
+            )?;
+
+            Funds4Good_config_data.is_initialized = true;
+            Funds4Good_config_data.acc_type = AccTypes::ConfigAcc as u8;
+            Funds4Good_config_data.admin_pubkey = *initializer_account.key;
+            Funds4Good_config_data.coin_mint_pubkey = Funds4Good_vault_account_data.mint;
+            Funds4Good_config_data.vault_pubkey = *Funds4Good_vault_account.key;
+
+            Funds4GoodConfigAccount::pack(
+                Funds4Good_config_data,
+                &mut Funds4Good_config_account.data.borrow_mut(),
+            )?;
+        } else if Funds4Good_config_data.admin_pubkey != *initializer_account.key {
+            return Err(Funds4GoodError::AdminDoesNotMatched.into());
+        }
+
+        let mint_account = next_account_info(account_info_iter)?;
+
+        if *mint_account.key != Funds4Good_vault_account_data.mint {
+            return Err(Funds4GoodError::TokenAccountMintDoesNotMatched.into());
+        }
+
+        if mint_account.owner != &spl_token::id() {
+            return Err(Funds4GoodError::InvalidTokenAccount.into());
+        }
+
+        let mint_data = Mint::unpack(&mint_account.data.borrow())?;
+
+        // every coin amount must be representable for this mint
+        Self::coin_unit(mint_data.decimals)?
+            .checked_mul(MAX_LOAN_COINS)
+            .ok_or(Funds4GoodError::UnsupportedMintDecimals)?;
+
+        let mint_vault_registry_account = next_account_info(account_info_iter)?;
+
+        let (pda_mint_vault_registry, mint_vault_registry_bump_seed) = Pubkey::find_program_address(
+            &[b"Funds4GoodFinanceVault", mint_account.key.as_ref()],
+            program_id,
+        );
+        if pda_mint_vault_registry != *mint_vault_registry_account.key {
+            return Err(Funds4GoodError::MintVaultRegistryAccountDoesNotMatched.into());
+        }
+
+        Self::create_pda_account_if_empty(
+            initializer_account,
+            mint_vault_registry_account,
+            system_program_account,
+            &[
+                &b"Funds4GoodFinanceVault"[..],
+                mint_account.key.as_ref(),
+                &[mint_vault_registry_bump_seed],
+            ],
+            MintVaultRegistryAccount::LEN,
+            &rent,
+            program_id,
+        )?;
+
+        let mut mint_vault_registry_data =
+            MintVaultRegistryAccount::unpack_unchecked(&mint_vault_registry_account.data.borrow())?;
+
+        if mint_vault_registry_data.is_initialized() {
+            return Err(Funds4GoodError::VaultAlreadyRecorded.into());
+        }
+
+        // lenders balances are tracked per mint, so every mint gets its own lenders storage account
+        let lenders_storage_account = next_account_info(account_info_iter)?;
+
+        if lenders_storage_account.owner != program_id {
+            return Err(Funds4GoodError::WrongAccountPassed.into());
+        }
+
+        if lenders_storage_account.data.borrow().first() != Some(&(AccTypes::LendersAcc as u8)) {
+            return Err(Funds4GoodError::ExpectedAccountTypeMismatched.into());
+        }
+
+        mint_vault_registry_data.is_initialized = true;
+        mint_vault_registry_data.acc_type = AccTypes::MintVaultRegistryAcc as u8;
+        mint_vault_registry_data.mint_pubkey = *mint_account.key;
+        mint_vault_registry_data.vault_pubkey = *Funds4Good_vault_account.key;
+        mint_vault_registry_data.lenders_storage_acc_pubkey = *lenders_storage_account.key;
+        mint_vault_registry_data.mint_decimals = mint_data.decimals;
+
+        MintVaultRegistryAccount::pack(
+            mint_vault_registry_data,
+            &mut mint_vault_registry_account.data.borrow_mut(),
+        )?;
+
+        Ok(())
+    }
+
+    fn create_pda_account_if_empty<'a>(
+        payer_account: &AccountInfo<'a>,
+        pda_account: &AccountInfo<'a>,
+        system_program_account: &AccountInfo<'a>,
+        pda_signer_seeds: &[&[u8]],
+        data_len: usize,
+        rent: &Rent,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        if pda_account.data_len() == 0 {
+            let create_pda_account_ix = system_instruction::create_account(
+                payer_account.key,
+                pda_account.key,
+                rent.minimum_balance(data_len),
+                data_len as u64,
+                program_id,
+            );
+            msg!("Calling the system program to create program account {}...", pda_account.key);
+            invoke_signed(
+                &create_pda_account_ix,
+                &[
+                    payer_account.clone(),
+                    pda_account.clone(),
+                    system_program_account.clone(),
+                ],
+                &[pda_signer_seeds],
+            )?;
+        }
+
+        if pda_account.owner != program_id {
+            return Err(Funds4GoodError::WrongAccountPassed.into());
+        }
+
+        Ok(())
+    }
+
+    // number of base units in one whole coin of a mint
+    fn coin_unit(mint_decimals: u8) -> Result<u64, ProgramError> {
+        10u64
+            .checked_pow(mint_decimals as u32)
+            .ok_or_else(|| Funds4GoodError::UnsupportedMintDecimals.into())
+    }
+
+    fn calculate_fee_amount(amount: u64, fee_bps: u16) -> Result<u64, ProgramError> {
+        let fee_amount = (amount as u128)
+            .checked_mul(fee_bps as u128)
+            .ok_or(Funds4GoodError::AmountOverflow)?
+            / BPS_DENOMINATOR as u128;
+        Ok(fee_amount as u64)
+    }
+
+    // installments are due every EMI_INTERVAL_DAYS starting from first_repayment_last_date_timestamp,
+    // the installment being paid is the first one not yet covered by repaid_amount_by_borrower
+    fn installment_due_timestamp(
+        loan_info_header_data: &LoanInfoAccDataHeader,
+        installment_index: u64,
+    ) -> Result<u64, ProgramError> {
+        installment_index
+            .checked_mul(EMI_INTERVAL_DAYS * NUM_SECONDS_IN_ONE_DAY)
+            .and_then(|offset| {
+                loan_info_header_data
+                    .first_repayment_last_date_timestamp
+                    .checked_add(offset)
+            })
+            .ok_or_else(|| Funds4GoodError::AmountOverflow.into())
+    }
+
+    // current schedule spreads what was outstanding when it started over its number of emis,
+    // schedule_repaid_amount_base is 0 until the loan is restructured
+    fn installment_amount(loan_info_header_data: &LoanInfoAccDataHeader) -> Result<u64, ProgramError> {
+        loan_info_header_data
+            .total_loan_amount
+            .saturating_sub(loan_info_header_data.schedule_repaid_amount_base)
+            .checked_div(loan_info_header_data.number_of_emis_needed_to_repay_the_loan as u64)
+            .ok_or_else(|| Funds4GoodError::InvalidNumberOfEmis.into())
+    }
+
+    // index of the first installment of current schedule not yet covered by repayments
+    fn first_unpaid_installment_index(
+        loan_info_header_data: &LoanInfoAccDataHeader,
+        installment_amount: u64,
+    ) -> u64 {
+        loan_info_header_data
+            .repaid_amount_by_borrower
+            .saturating_sub(loan_info_header_data.schedule_repaid_amount_base)
+            / installment_amount
+    }
+
+    // late fee of the installment being paid
+    fn calculate_late_fee_amount(
+        mint_vault_registry_data: &MintVaultRegistryAccount,
+        loan_info_header_data: &LoanInfoAccDataHeader,
+        now: u64,
+    ) -> Result<u64, ProgramError> {
+        let installment_amount = Self::installment_amount(loan_info_header_data)?;
+        if installment_amount == 0 {
+            return Ok(0);
+        }
+
+        Self::calculate_installment_late_fee_amount(
+            mint_vault_registry_data,
+            loan_info_header_data,
+            Self::first_unpaid_installment_index(loan_info_header_data, installment_amount),
+            now,
+        )
+    }
+
+    // late fee of an overdue installment is flat fee plus daily bps of the installment for every started day it is late,
+    // it never exceeds the installment amount itself
+    fn calculate_installment_late_fee_amount(
+        mint_vault_registry_data: &MintVaultRegistryAccount,
+        loan_info_header_data: &LoanInfoAccDataHeader,
+        installment_index: u64,
+        now: u64,
+    ) -> Result<u64, ProgramError> {
+        let installment_amount = Self::installment_amount(loan_info_header_data)?;
+        let due_timestamp = Self::installment_due_timestamp(loan_info_header_data, installment_index)?;
+
+        if now <= due_timestamp {
+            return Ok(0);
+        }
+
+        let num_days_late = (now - due_timestamp).div_ceil(NUM_SECONDS_IN_ONE_DAY);
+        let daily_late_fee_amount =
+            Self::calculate_fee_amount(installment_amount, mint_vault_registry_data.late_fee_daily_bps)?;
+        let late_fee_amount = daily_late_fee_amount
+            .checked_mul(num_days_late)
+            .and_then(|amount| amount.checked_add(mint_vault_registry_data.late_fee_flat_amount))
+            .unwrap_or(u64::MAX);
+
+        Ok(if late_fee_amount > installment_amount {
+            installment_amount
+        } else {
+            late_fee_amount
+        })
+    }
+
+    // simple interest on outstanding principal for every full day since last repayment, or since disbursement
+    // when nothing is repaid yet
+    fn calculate_accrued_interest_amount(
+        mint_vault_registry_data: &MintVaultRegistryAccount,
+        loan_info: &LoanInfoAccount,
+        loan_info_header_data: &LoanInfoAccDataHeader,
+        now: u64,
+    ) -> Result<u64, ProgramError> {
+        if mint_vault_registry_data.interest_rate_bps == 0 {
+            return Ok(0);
+        }
+
+        let outstanding_loan_amount = loan_info_header_data
+            .total_loan_amount
+            .checked_sub(loan_info_header_data.repaid_amount_by_borrower)
+            .ok_or(Funds4GoodError::AmountUnderflow)?;
+        let accrued_from_timestamp = match loan_info_header_data.next_index_to_store_repayment_info
+        {
+            0 => loan_info_header_data.disbursement_timestamp,
+            next_index => {
+                loan_info
+                    .repayment(next_index as usize - 1)?
+                    .emi_repayment_timestamp
+            }
+        };
+        let num_days_accrued = now.saturating_sub(accrued_from_timestamp) / NUM_SECONDS_IN_ONE_DAY;
+
+        let interest_amount = (outstanding_loan_amount as u128)
+            .checked_mul(mint_vault_registry_data.interest_rate_bps as u128)
+            .and_then(|amount| amount.checked_mul(num_days_accrued as u128))
+            .ok_or(Funds4GoodError::AmountOverflow)?
+            / (BPS_DENOMINATOR as u128 * NUM_DAYS_IN_ONE_YEAR as u128);
+
+        interest_amount
+            .try_into()
+            .map_err(|_| Funds4GoodError::AmountOverflow.into())
+    }
+
+    // amount needed to clear the loan now, remaining principal less prepayment discount plus interest accrued until now
+    // and late fees of every overdue installment, prepayment discount is only given when no installment is overdue
+    fn calculate_payoff_amounts(
+        mint_vault_registry_data: &MintVaultRegistryAccount,
+        loan_info: &LoanInfoAccount,
+        loan_info_header_data: &LoanInfoAccDataHeader,
+        now: u64,
+    ) -> Result<(u64, u64, u64, u64), ProgramError> {
+        let outstanding_loan_amount = loan_info_header_data
+            .total_loan_amount
+            .checked_sub(loan_info_header_data.repaid_amount_by_borrower)
+            .ok_or(Funds4GoodError::AmountUnderflow)?;
+        let installment_amount = Self::installment_amount(loan_info_header_data)?;
+
+        let mut late_fee_amount: u64 = 0;
+        if installment_amount > 0 {
+            let first_unpaid_installment_index =
+                Self::first_unpaid_installment_index(loan_info_header_data, installment_amount);
+            for installment_index in first_unpaid_installment_index
+                ..loan_info_header_data.number_of_emis_needed_to_repay_the_loan as u64
+            {
+                late_fee_amount = late_fee_amount
+                    .checked_add(Self::calculate_installment_late_fee_amount(
+                        mint_vault_registry_data,
+                        loan_info_header_data,
+                        installment_index,
+                        now,
+                    )?)
+                    .ok_or(Funds4GoodError::AmountOverflow)?;
+            }
+        }
+
+        let prepayment_discount_amount = if late_fee_amount == 0 {
+            Self::calculate_fee_amount(
+                outstanding_loan_amount,
+                mint_vault_registry_data.prepayment_discount_bps,
+            )?
+        } else {
+            0
+        };
+
+        let interest_amount = Self::calculate_accrued_interest_amount(
+            mint_vault_registry_data,
+            loan_info,
+            loan_info_header_data,
+            now,
+        )?;
+
+        Ok((
+            outstanding_loan_amount,
+            prepayment_discount_amount,
+            late_fee_amount,
+            interest_amount,
+        ))
+    }
+
+    fn process_pay_off_loan(
+        accounts: &[AccountInfo],
+        max_amount_to_pay_input: u64,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let borrower_main_account = next_account_info(account_info_iter)?;
+
+        if !borrower_main_account.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let borrower_Funds4Good_coin_account_to_debit = next_account_info(account_info_iter)?;
+        let Funds4Good_coin_vault_account = next_account_info(account_info_iter)?;
+
+        let mint_vault_registry_account = next_account_info(account_info_iter)?;
+        let mut mint_vault_registry_data =
+            Self::verify_mint_vault_registry_account(mint_vault_registry_account, program_id)?;
+        Self::verify_vault_account(Funds4Good_coin_vault_account, &mint_vault_registry_data)?;
+        Self::verify_user_token_account(
+            borrower_Funds4Good_coin_account_to_debit,
+            borrower_main_account.key,
+            &mint_vault_registry_data.mint_pubkey,
+        )?;
+        let coin_unit = Self::coin_unit(mint_vault_registry_data.mint_decimals)?;
+
+        let borrower_storage_account = next_account_info(account_info_iter)?;
+        if borrower_storage_account.owner != program_id {
+            return Err(Funds4GoodError::WrongAccountPassed.into());
+        }
+
+        let token_program = next_account_info(account_info_iter)?;
+
+        if token_program.key != &spl_token::id() {
+            return Err(Funds4GoodError::InvalidTokenProgram.into());
+        }
+
+        let reserve_pool_account = next_account_info(account_info_iter)?;
+        let reserve_vault_account = next_account_info(account_info_iter)?;
+
+        let loan_info_storage_account = next_account_info(account_info_iter)?;
+
+        if loan_info_storage_account.owner != program_id {
+            return Err(Funds4GoodError::WrongAccountPassed.into());
+        }
+
+        let mut loan_info_data_byte_array = loan_info_storage_account.try_borrow_mut_data()?;
+        let mut loan_info = LoanInfoAccount::new(&mut loan_info_data_byte_array)?;
+        let mut loan_info_header_data: LoanInfoAccDataHeader = loan_info.header()?;
+
+        if loan_info_header_data.loan_mint_pubkey != mint_vault_registry_data.mint_pubkey {
+            return Err(Funds4GoodError::LoanMintDoesNotMatched.into());
+        }
+
+        let reserve_pool_data = Self::verify_loan_reserve_pool_account(
+            reserve_pool_account,
+            reserve_vault_account,
+            &mint_vault_registry_data,
+            &loan_info_header_data,
+            program_id,
+        )?;
+
+        if loan_info_header_data.borrower_main_acc_pubkey != *borrower_main_account.key {
+            return Err(Funds4GoodError::BorrowerAccountMismatched.into());
+        }
+
+        if loan_info_header_data.loan_status != LoanStatus::Active as u8 {
+            return Err(Funds4GoodError::LoanStatusDoesNotMatched.into());
+        }
+
+        let mut borrower_data =
+            BorrowerAccount::unpack_versioned(&borrower_storage_account.data.borrow())?;
+        if borrower_data.acc_type != AccTypes::BorrowerAcc as u8 {
+            return Err(Funds4GoodError::ExpectedAccountTypeMismatched.into());
+        }
+        if borrower_data.borrower_main_acc_pubkey != loan_info_header_data.borrower_main_acc_pubkey {
+            return Err(Funds4GoodError::BorrowerAccountMismatched.into());
+        }
+
+        let now = Clock::get()?.unix_timestamp as u64;
+        let (outstanding_loan_amount, prepayment_discount_amount, late_fee_amount, interest_amount) =
+            Self::calculate_payoff_amounts(
+                &mint_vault_registry_data,
+                &loan_info,
+                &loan_info_header_data,
+                now,
+            )?;
+        let principal_amount_to_pay = outstanding_loan_amount
+            .checked_sub(prepayment_discount_amount)
+            .ok_or(Funds4GoodError::AmountUnderflow)?;
+        let payoff_amount = principal_amount_to_pay
+            .checked_add(late_fee_amount)
+            .ok_or(Funds4GoodError::AmountOverflow)?
+            .checked_add(interest_amount)
+            .ok_or(Funds4GoodError::AmountOverflow)?;
+
+        // payoff amount depends on clock, borrower signs the most he is willing to pay
+        if payoff_amount > max_amount_to_pay_input {
+            return Err(Funds4GoodError::PayoffAmountExceedsMaximum.into());
+        }
+
+        if loan_info_header_data.next_index_to_store_repayment_info as usize
+            >= state::LOAN_INFO_MAX_REPAYMENTS
+        {
+            return Err(Funds4GoodError::LoanRepaymentsCapacityExceeded.into());
+        }
+
+        let late_fee_amount_for_lenders = Self::calculate_fee_amount(
+            late_fee_amount,
+            mint_vault_registry_data.late_fee_lenders_share_bps,
+        )?;
+        let servicing_fee_amount = Self::calculate_fee_amount(
+            principal_amount_to_pay,
+            mint_vault_registry_data.servicing_fee_bps,
+        )?;
+        let reserve_contribution_amount = match &reserve_pool_data {
+            Some(reserve_pool_data) => Self::calculate_fee_amount(
+                principal_amount_to_pay,
+                reserve_pool_data.reserve_fee_bps,
+            )?,
+            None => 0,
+        };
+        let amount_for_lenders = principal_amount_to_pay
+            .checked_sub(servicing_fee_amount)
+            .ok_or(Funds4GoodError::AmountUnderflow)?
+            .checked_sub(reserve_contribution_amount)
+            .ok_or(Funds4GoodError::AmountUnderflow)?
+            .checked_add(late_fee_amount_for_lenders)
+            .ok_or(Funds4GoodError::AmountOverflow)?
+            .checked_add(interest_amount)
+            .ok_or(Funds4GoodError::AmountOverflow)?;
+        let treasury_amount = servicing_fee_amount
+            .checked_add(
+                late_fee_amount
+                    .checked_sub(late_fee_amount_for_lenders)
+                    .ok_or(Funds4GoodError::AmountUnderflow)?,
+            )
+            .ok_or(Funds4GoodError::AmountOverflow)?;
+
+        let lenders_data_storage_account = next_account_info(account_info_iter)?;
+
+        if lenders_data_storage_account.owner != program_id {
+            return Err(Funds4GoodError::WrongAccountPassed.into());
+        }
+
+        if mint_vault_registry_data.lenders_storage_acc_pubkey != *lenders_data_storage_account.key {
+            return Err(Funds4GoodError::LendersStorageAccountDoesNotMatched.into());
+        }
+
+        let mut lenders_storage_data_byte_array =
+            lenders_data_storage_account.try_borrow_mut_data()?;
+        let mut lenders_storage = LendersStorageAccount::new(&mut lenders_storage_data_byte_array)?;
+
+        Self::credit_lenders_pro_rata(
+            &loan_info,
+            &mut lenders_storage,
+            &loan_info_header_data,
+            &mut mint_vault_registry_data,
+            amount_for_lenders,
+        )?;
+
+        msg!(
+            "paying off loan with {}, prepayment discount {}, late fee {}, interest {}",
+            (payoff_amount as f64 / coin_unit as f64),
+            (prepayment_discount_amount as f64 / coin_unit as f64),
+            (late_fee_amount as f64 / coin_unit as f64),
+            (interest_amount as f64 / coin_unit as f64)
+        );
+
+        let Funds4Good_coin_vault_balance_before =
+            TokenAccount::unpack(&Funds4Good_coin_vault_account.data.borrow())?.amount;
+
+        let transfer_payoff_amount_to_vault_ix = spl_token::instruction::transfer(
+            token_program.key,
+            borrower_Funds4Good_coin_account_to_debit.key,
+            Funds4Good_coin_vault_account.key,
+            borrower_main_account.key,
+            &[],
+            amount_for_lenders,
+        )?;
+        invoke(
+            &transfer_payoff_amount_to_vault_ix,
+            &[
+                borrower_Funds4Good_coin_account_to_debit.clone(),
+                Funds4Good_coin_vault_account.clone(),
+                borrower_main_account.clone(),
+                token_program.clone(),
+            ],
+        )?;
+
+        let Funds4Good_coin_vault_balance_after =
+            TokenAccount::unpack(&Funds4Good_coin_vault_account.data.borrow())?.amount;
+        if Funds4Good_coin_vault_balance_after
+            .checked_sub(Funds4Good_coin_vault_balance_before)
+            .ok_or(Funds4GoodError::ExpectedAmountMismatch)?
+            != amount_for_lenders
+        {
+            return Err(Funds4GoodError::ExpectedAmountMismatch.into());
+        }
+
+        if reserve_contribution_amount > 0 {
+            let transfer_reserve_contribution_ix = spl_token::instruction::transfer(
+                token_program.key,
+                borrower_Funds4Good_coin_account_to_debit.key,
+                reserve_vault_account.key,
+                borrower_main_account.key,
+                &[],
+                reserve_contribution_amount,
+            )?;
+            invoke(
+                &transfer_reserve_contribution_ix,
+                &[
+                    borrower_Funds4Good_coin_account_to_debit.clone(),
+                    reserve_vault_account.clone(),
+                    borrower_main_account.clone(),
+                    token_program.clone(),
+                ],
+            )?;
+        }
+
+        // treasury account is only needed when servicing fee or treasury share of late fee is due
+        if treasury_amount > 0 {
+            let treasury_account = next_account_info(account_info_iter)?;
+            Self::verify_treasury_account(treasury_account, &mint_vault_registry_data)?;
+
+            let transfer_fees_to_treasury_ix = spl_token::instruction::transfer(
+                token_program.key,
+                borrower_Funds4Good_coin_account_to_debit.key,
+                treasury_account.key,
+                borrower_main_account.key,
+                &[],
+                treasury_amount,
+            )?;
+            invoke(
+                &transfer_fees_to_treasury_ix,
+                &[
+                    borrower_Funds4Good_coin_account_to_debit.clone(),
+                    treasury_account.clone(),
+                    borrower_main_account.clone(),
+                    token_program.clone(),
+                ],
+            )?;
+        }
+
+        loan_info.push_repayment(
+            &mut loan_info_header_data,
+            LoanInfoAccRepaymentData {
+                emi_repayment_timestamp: now,
+                emi_repayment_amount: payoff_amount,
+            },
+        )?;
+
+        // paying off counts as an installment for credit score, it is on time when no installment is overdue
+        let scheduled_installment_amount = Self::installment_amount(&loan_info_header_data)?;
+        let is_payoff_on_time = scheduled_installment_amount == 0
+            || now
+                <= Self::installment_due_timestamp(
+                    &loan_info_header_data,
+                    Self::first_unpaid_installment_index(
+                        &loan_info_header_data,
+                        scheduled_installment_amount,
+                    ),
+                )?;
+        if is_payoff_on_time {
+            borrower_data.credit_score = borrower_data
+                .credit_score
+                .saturating_add(ON_TIME_EMI_CREDIT_SCORE_REWARD);
+        } else {
+            borrower_data.credit_score = borrower_data
+                .credit_score
+                .saturating_sub(LATE_EMI_CREDIT_SCORE_PENALTY);
+        }
+        BorrowerAccount::pack_versioned(
+            borrower_data,
+            &mut borrower_storage_account.data.borrow_mut(),
+        )?;
+
+        // discounted part of principal is forgiven, so loan counts as fully repaid
+        loan_info_header_data.repaid_amount_by_borrower = loan_info_header_data.total_loan_amount;
+        loan_info_header_data.penalty_paid_amount = loan_info_header_data
+            .penalty_paid_amount
+            .checked_add(late_fee_amount)
+            .ok_or(Funds4GoodError::AmountOverflow)?;
+        loan_info_header_data.loan_status = LoanStatus::Repaid as u8;
+
+        // payoff is logged as an emi payment of the whole payoff amount
+        let paid_off_event = Funds4GoodEvent::EmiPaid {
+            loan_info_pubkey: *loan_info_storage_account.key,
+            payer_pubkey: *borrower_main_account.key,
+            installment_amount: payoff_amount,
+            late_fee_amount,
+            repaid_amount_by_borrower: loan_info_header_data.repaid_amount_by_borrower,
+            is_on_time: is_payoff_on_time,
+            loan_status: loan_info_header_data.loan_status,
+        };
+
+        loan_info.set_header(loan_info_header_data)?;
+
+        if let Some(mut reserve_pool_data) = reserve_pool_data {
+            reserve_pool_data.pool_balance = reserve_pool_data
+                .pool_balance
+                .checked_add(reserve_contribution_amount)
+                .ok_or(Funds4GoodError::AmountOverflow)?;
+            reserve_pool_data.total_contributed_amount = reserve_pool_data
+                .total_contributed_amount
+                .checked_add(reserve_contribution_amount)
+                .ok_or(Funds4GoodError::AmountOverflow)?;
+            reserve_pool_data.total_outstanding_principal = reserve_pool_data
+                .total_outstanding_principal
+                .checked_sub(outstanding_loan_amount)
+                .ok_or(Funds4GoodError::AmountUnderflow)?;
+            reserve_pool_data.update_coverage_ratio();
+            ReservePoolAccount::pack(reserve_pool_data, &mut reserve_pool_account.data.borrow_mut())?;
+        }
+
+        MintVaultRegistryAccount::pack(
+            mint_vault_registry_data,
+            &mut mint_vault_registry_account.data.borrow_mut(),
+        )?;
+
+        paid_off_event.emit();
+
+        Ok(())
+    }
+
+    // guarantor proposes a new schedule for the remaining balance of an active loan and borrower agrees by co-signing,
+    // old schedule is kept as an amendment record in loan info account
+    fn process_restructure_loan(
+        accounts: &[AccountInfo],
+        new_number_of_emis_input: u16,
+        num_days_left_for_next_repayment_input: u16,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let guarantor_main_account = next_account_info(account_info_iter)?;
+
+        if !guarantor_main_account.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let borrower_main_account = next_account_info(account_info_iter)?;
+
+        if !borrower_main_account.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let loan_info_storage_account = next_account_info(account_info_iter)?;
+
+        if loan_info_storage_account.owner != program_id {
+            return Err(Funds4GoodError::WrongAccountPassed.into());
+        }
+
+        let mut loan_info_data_byte_array = loan_info_storage_account.try_borrow_mut_data()?;
+        let mut loan_info = LoanInfoAccount::new(&mut loan_info_data_byte_array)?;
+        let mut loan_info_header_data: LoanInfoAccDataHeader = loan_info.header()?;
+
+        if loan_info_header_data.guarantor_main_acc_pubkey != *guarantor_main_account.key {
+            return Err(Funds4GoodError::AccountMismatched.into());
+        }
+
+        if loan_info_header_data.borrower_main_acc_pubkey != *borrower_main_account.key {
+            return Err(Funds4GoodError::BorrowerAccountMismatched.into());
+        }
+
+        if loan_info_header_data.loan_status != LoanStatus::Active as u8 {
+            return Err(Funds4GoodError::LoanStatusDoesNotMatched.into());
+        }
+
+        // every emi of new schedule still needs a repayment slot
+        if new_number_of_emis_input == 0
+            || loan_info_header_data.next_index_to_store_repayment_info as usize
+                + new_number_of_emis_input as usize
+                > state::LOAN_INFO_MAX_REPAYMENTS
+        {
+            return Err(Funds4GoodError::InvalidNumberOfEmis.into());
+        }
+
+        if loan_info_header_data.schedule_version as usize >= state::LOAN_INFO_MAX_AMENDMENTS {
+            return Err(Funds4GoodError::LoanAmendmentsCapacityExceeded.into());
+        }
+
+        let borrower_storage_account = next_account_info(account_info_iter)?;
+        if borrower_storage_account.owner != program_id {
+            return Err(Funds4GoodError::WrongAccountPassed.into());
+        }
+
+        let mut borrower_data =
+            BorrowerAccount::unpack_versioned(&borrower_storage_account.data.borrow())?;
+        if borrower_data.acc_type != AccTypes::BorrowerAcc as u8 {
+            return Err(Funds4GoodError::ExpectedAccountTypeMismatched.into());
+        }
+        if borrower_data.borrower_main_acc_pubkey != *borrower_main_account.key {
+            return Err(Funds4GoodError::BorrowerAccountMismatched.into());
+        }
+
+        let now = Clock::get()?.unix_timestamp as u64;
+        // same 5 days of grace as the original schedule
+        let new_first_repayment_last_date_timestamp = now
+            .checked_add(
+                (num_days_left_for_next_repayment_input as u64)
+                    .checked_add(5u64)
+                    .ok_or(Funds4GoodError::AmountOverflow)?
+                    .checked_mul(NUM_SECONDS_IN_ONE_DAY)
+                    .ok_or(Funds4GoodError::AmountOverflow)?,
+            )
+            .ok_or(Funds4GoodError::AmountOverflow)?;
+
+        let amendment_data = state::LoanInfoAccAmendmentData {
+            schedule_version: loan_info_header_data.schedule_version + 1,
+            amendment_timestamp: now,
+            previous_number_of_emis: loan_info_header_data.number_of_emis_needed_to_repay_the_loan,
+            new_number_of_emis: new_number_of_emis_input as u8,
+            previous_first_repayment_last_date_timestamp: loan_info_header_data
+                .first_repayment_last_date_timestamp,
+            new_first_repayment_last_date_timestamp,
+            repaid_amount_at_amendment: loan_info_header_data.repaid_amount_by_borrower,
+        };
+
+        loan_info.set_amendment(loan_info_header_data.schedule_version as usize, amendment_data)?;
+
+        loan_info_header_data.schedule_version += 1;
+        loan_info_header_data.number_of_emis_needed_to_repay_the_loan = new_number_of_emis_input as u8;
+        loan_info_header_data.first_repayment_last_date_timestamp =
+            new_first_repayment_last_date_timestamp;
+        loan_info_header_data.schedule_repaid_amount_base =
+            loan_info_header_data.repaid_amount_by_borrower;
+
+        loan_info.set_header(loan_info_header_data)?;
+
+        borrower_data.credit_score = borrower_data
+            .credit_score
+            .saturating_sub(RESTRUCTURE_CREDIT_SCORE_PENALTY);
+        BorrowerAccount::pack_versioned(
+            borrower_data,
+            &mut borrower_storage_account.data.borrow_mut(),
+        )?;
+
+        Ok(())
+    }
+
+    fn is_partial_funding_acceptable(loan_info_header_data: &LoanInfoAccDataHeader, now: u64) -> bool {
+        loan_info_header_data.min_funding_amount > 0
+            && loan_info_header_data.fundraising_period_ending_timestamp < now
+            && loan_info_header_data.total_amount_lended >= loan_info_header_data.min_funding_amount
+    }
+
+    // guarantor or borrower can withdraw a loan listing while it is still fundraising, anyone can cancel it
+    // once fundraising period is over without enough funding,
+    // every lender gets the amount lent to this loan back in free balance and can withdraw it from vault
+    fn process_cancel_loan(accounts: &[AccountInfo], program_id: &Pubkey) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let signer_account = next_account_info(account_info_iter)?;
+
+        if !signer_account.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let loan_info_storage_account = next_account_info(account_info_iter)?;
+
+        if loan_info_storage_account.owner != program_id {
+            return Err(Funds4GoodError::WrongAccountPassed.into());
+        }
+
+        let mint_vault_registry_account = next_account_info(account_info_iter)?;
+        let mint_vault_registry_data =
+            Self::verify_mint_vault_registry_account(mint_vault_registry_account, program_id)?;
+
+        let lenders_data_storage_account = next_account_info(account_info_iter)?;
+
+        if lenders_data_storage_account.owner != program_id {
+            return Err(Funds4GoodError::WrongAccountPassed.into());
+        }
+
+        if mint_vault_registry_data.lenders_storage_acc_pubkey != *lenders_data_storage_account.key {
+            return Err(Funds4GoodError::LendersStorageAccountDoesNotMatched.into());
+        }
+
+        let mut loan_info_data_byte_array = loan_info_storage_account.try_borrow_mut_data()?;
+        let mut loan_info = LoanInfoAccount::new(&mut loan_info_data_byte_array)?;
+        let mut loan_info_header_data: LoanInfoAccDataHeader = loan_info.header()?;
+
+        if loan_info_header_data.loan_mint_pubkey != mint_vault_registry_data.mint_pubkey {
+            return Err(Funds4GoodError::LoanMintDoesNotMatched.into());
+        }
+
+        if loan_info_header_data.loan_status != LoanStatus::Fundraising as u8 {
+            return Err(Funds4GoodError::LoanStatusDoesNotMatched.into());
+        }
+
+        let now = Clock::get()?.unix_timestamp as u64;
+        let is_fundraising_failed = loan_info_header_data.fundraising_period_ending_timestamp < now
+            && loan_info_header_data.total_amount_lended < loan_info_header_data.total_loan_amount
+            && !Self::is_partial_funding_acceptable(&loan_info_header_data, now);
+
+        if !is_fundraising_failed
+            && loan_info_header_data.guarantor_main_acc_pubkey != *signer_account.key
+            && loan_info_header_data.borrower_main_acc_pubkey != *signer_account.key
+        {
+            return Err(Funds4GoodError::AccountMismatched.into());
+        }
+
+        let mut lenders_storage_data_byte_array =
+            lenders_data_storage_account.try_borrow_mut_data()?;
+        let mut lenders_storage = LendersStorageAccount::new(&mut lenders_storage_data_byte_array)?;
+
+        // refunds move lent funds back to free balances, vault_liabilities of registry stays the same
+        for i in 0..loan_info_header_data.next_index_to_store_lender_data as usize {
+            let loan_info_lender_data = loan_info.lender(i)?;
+
+            let mut lender_acc_data: LenderAccountData =
+                lenders_storage.lender(loan_info_lender_data.lender_id)?;
+
+            lender_acc_data.amount_to_withdraw_or_lend = lender_acc_data
+                .amount_to_withdraw_or_lend
+                .checked_add(loan_info_lender_data.lent_amount)
+                .ok_or(Funds4GoodError::AmountOverflow)?;
+
+            lenders_storage.set_lender(loan_info_lender_data.lender_id, lender_acc_data)?;
+
+            Funds4GoodEvent::Refunded {
+                loan_info_pubkey: *loan_info_storage_account.key,
+                lender_id: loan_info_lender_data.lender_id,
+                amount: loan_info_lender_data.lent_amount,
+            }
+            .emit();
+        }
+
+        msg!(
+            "loan cancelled, {} lenders refunded",
+            loan_info_header_data.next_index_to_store_lender_data
+        );
+
+        loan_info_header_data.total_amount_lended = 0;
+        loan_info_header_data.loan_status = LoanStatus::Cancelled as u8;
+
+        loan_info.set_header(loan_info_header_data)?;
+
+        Ok(())
+    }
+
+    // borrower pays back guarantor what guarantor covered for missed emis, directly from borrower to guarantor token account
+    fn process_repay_guarantor(
+        accounts: &[AccountInfo],
+        amount_to_repay_input: u64,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let borrower_main_account = next_account_info(account_info_iter)?;
+
+        if !borrower_main_account.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let borrower_Funds4Good_coin_account_to_debit = next_account_info(account_info_iter)?;
+        let guarantor_Funds4Good_coin_account_to_credit = next_account_info(account_info_iter)?;
+
+        let mint_vault_registry_account = next_account_info(account_info_iter)?;
+        let mint_vault_registry_data =
+            Self::verify_mint_vault_registry_account(mint_vault_registry_account, program_id)?;
+        let coin_unit = Self::coin_unit(mint_vault_registry_data.mint_decimals)?;
+
+        let token_program = next_account_info(account_info_iter)?;
+
+        if token_program.key != &spl_token::id() {
+            return Err(Funds4GoodError::InvalidTokenProgram.into());
+        }
+
+        let loan_info_storage_account = next_account_info(account_info_iter)?;
+
+        if loan_info_storage_account.owner != program_id {
+            return Err(Funds4GoodError::WrongAccountPassed.into());
+        }
+
+        let mut loan_info_data_byte_array = loan_info_storage_account.try_borrow_mut_data()?;
+        let mut loan_info = LoanInfoAccount::new(&mut loan_info_data_byte_array)?;
+        let mut loan_info_header_data: LoanInfoAccDataHeader = loan_info.header()?;
+
+        if loan_info_header_data.loan_mint_pubkey != mint_vault_registry_data.mint_pubkey {
+            return Err(Funds4GoodError::LoanMintDoesNotMatched.into());
+        }
+
+        if loan_info_header_data.borrower_main_acc_pubkey != *borrower_main_account.key {
+            return Err(Funds4GoodError::BorrowerAccountMismatched.into());
+        }
+
+        Self::verify_user_token_account(
+            borrower_Funds4Good_coin_account_to_debit,
+            borrower_main_account.key,
+            &mint_vault_registry_data.mint_pubkey,
+        )?;
+        Self::verify_user_token_account(
+            guarantor_Funds4Good_coin_account_to_credit,
+            &loan_info_header_data.guarantor_main_acc_pubkey,
+            &mint_vault_registry_data.mint_pubkey,
+        )?;
+
+        // borrower can't repay more than guarantor has paid on their behalf
+        if amount_to_repay_input == 0
+            || amount_to_repay_input > loan_info_header_data.guarantor_claim_amount
+        {
+            return Err(Funds4GoodError::InvalidGuarantorClaimAmount.into());
+        }
+        let remaining_guarantor_claim_amount = loan_info_header_data
+            .guarantor_claim_amount
+            .checked_sub(amount_to_repay_input)
+            .ok_or(Funds4GoodError::AmountUnderflow)?;
+
+        let transfer_amount_to_guarantor_ix = spl_token::instruction::transfer(
+            token_program.key,
+            borrower_Funds4Good_coin_account_to_debit.key,
+            guarantor_Funds4Good_coin_account_to_credit.key,
+            borrower_main_account.key,
+            &[],
+            amount_to_repay_input,
+        )?;
+        msg!(
+            "Calling the token program to repay {} to guarantor...",
+            (amount_to_repay_input as f64 / coin_unit as f64)
+        );
+        invoke(
+            &transfer_amount_to_guarantor_ix,
+            &[
+                borrower_Funds4Good_coin_account_to_debit.clone(),
+                guarantor_Funds4Good_coin_account_to_credit.clone(),
+                borrower_main_account.clone(),
+                token_program.clone(),
+            ],
+        )?;
+
+        loan_info_header_data.guarantor_claim_amount = remaining_guarantor_claim_amount;
+
+        loan_info.set_header(loan_info_header_data)?;
+
+        Ok(())
+    }
+
+    fn process_set_prepayment_discount(
+        accounts: &[AccountInfo],
+        prepayment_discount_bps_input: u16,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let admin_account = next_account_info(account_info_iter)?;
+
+        if !admin_account.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let Funds4Good_config_account = next_account_info(account_info_iter)?;
+        let Funds4Good_config_data =
+            Self::verify_Funds4Good_config_account(Funds4Good_config_account, program_id)?;
+
+        if Funds4Good_config_data.admin_pubkey != *admin_account.key {
+            return Err(Funds4GoodError::AdminDoesNotMatched.into());
+        }
+
+        if prepayment_discount_bps_input > MAX_PREPAYMENT_DISCOUNT_BPS {
+            return Err(Funds4GoodError::InvalidFeeBps.into());
+        }
+
+        let mint_vault_registry_account = next_account_info(account_info_iter)?;
+        let mut mint_vault_registry_data =
+            Self::verify_mint_vault_registry_account(mint_vault_registry_account, program_id)?;
+
+        mint_vault_registry_data.prepayment_discount_bps = prepayment_discount_bps_input;
+
+        MintVaultRegistryAccount::pack(
+            mint_vault_registry_data,
+            &mut mint_vault_registry_account.data.borrow_mut(),
+        )?;
+
+        Ok(())
+    }
+
+    fn process_set_interest_rate(
+        accounts: &[AccountInfo],
+        interest_rate_bps_input: u16,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let admin_account = next_account_info(account_info_iter)?;
+
+        if !admin_account.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let Funds4Good_config_account = next_account_info(account_info_iter)?;
+        let Funds4Good_config_data =
+            Self::verify_Funds4Good_config_account(Funds4Good_config_account, program_id)?;
+
+        if Funds4Good_config_data.admin_pubkey != *admin_account.key {
+            return Err(Funds4GoodError::AdminDoesNotMatched.into());
+        }
+
+        if interest_rate_bps_input > MAX_INTEREST_RATE_BPS {
+            return Err(Funds4GoodError::InvalidFeeBps.into());
+        }
+
+        let mint_vault_registry_account = next_account_info(account_info_iter)?;
+        let mut mint_vault_registry_data =
+            Self::verify_mint_vault_registry_account(mint_vault_registry_account, program_id)?;
+
+        mint_vault_registry_data.interest_rate_bps = interest_rate_bps_input;
+
+        MintVaultRegistryAccount::pack(
+            mint_vault_registry_data,
+            &mut mint_vault_registry_account.data.borrow_mut(),
+        )?;
+
+        Ok(())
+    }
+
+    fn process_set_late_fees(
+        accounts: &[AccountInfo],
+        late_fee_flat_amount_input: u64,
+        late_fee_daily_bps_input: u16,
+        late_fee_lenders_share_bps_input: u16,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let admin_account = next_account_info(account_info_iter)?;
+
+        if !admin_account.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let Funds4Good_config_account = next_account_info(account_info_iter)?;
+        let Funds4Good_config_data =
+            Self::verify_Funds4Good_config_account(Funds4Good_config_account, program_id)?;
+
+        if Funds4Good_config_data.admin_pubkey != *admin_account.key {
+            return Err(Funds4GoodError::AdminDoesNotMatched.into());
+        }
+
+        if late_fee_daily_bps_input > MAX_LATE_FEE_DAILY_BPS
+            || late_fee_lenders_share_bps_input as u64 > BPS_DENOMINATOR
+        {
+            return Err(Funds4GoodError::InvalidFeeBps.into());
+        }
+
+        let mint_vault_registry_account = next_account_info(account_info_iter)?;
+        let mut mint_vault_registry_data =
+            Self::verify_mint_vault_registry_account(mint_vault_registry_account, program_id)?;
+
+        if (late_fee_flat_amount_input > 0 || late_fee_daily_bps_input > 0)
+            && (late_fee_lenders_share_bps_input as u64) < BPS_DENOMINATOR
+            && mint_vault_registry_data.treasury_pubkey == Pubkey::default()
+        {
+            return Err(Funds4GoodError::TreasuryAccountNotRegistered.into());
+        }
+
+        mint_vault_registry_data.late_fee_flat_amount = late_fee_flat_amount_input;
+        mint_vault_registry_data.late_fee_daily_bps = late_fee_daily_bps_input;
+        mint_vault_registry_data.late_fee_lenders_share_bps = late_fee_lenders_share_bps_input;
+
+        MintVaultRegistryAccount::pack(
+            mint_vault_registry_data,
+            &mut mint_vault_registry_account.data.borrow_mut(),
+        )?;
+
+        Ok(())
+    }
+
+    fn verify_treasury_account(
+        treasury_account: &AccountInfo,
+        mint_vault_registry_data: &MintVaultRegistryAccount,
+    ) -> ProgramResult {
+        if mint_vault_registry_data.treasury_pubkey == Pubkey::default() {
+            return Err(Funds4GoodError::TreasuryAccountNotRegistered.into());
+        }
+
+        if mint_vault_registry_data.treasury_pubkey != *treasury_account.key {
+            return Err(Funds4GoodError::TreasuryAccountDoesNotMatched.into());
+        }
+
+        Ok(())
+    }
+
+    fn process_set_platform_fees(
+        accounts: &[AccountInfo],
+        origination_fee_bps_input: u16,
+        servicing_fee_bps_input: u16,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let admin_account = next_account_info(account_info_iter)?;
+
+        if !admin_account.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let Funds4Good_config_account = next_account_info(account_info_iter)?;
+        let Funds4Good_config_data =
+            Self::verify_Funds4Good_config_account(Funds4Good_config_account, program_id)?;
+
+        if Funds4Good_config_data.admin_pubkey != *admin_account.key {
+            return Err(Funds4GoodError::AdminDoesNotMatched.into());
+        }
+
+        if origination_fee_bps_input > MAX_PLATFORM_FEE_BPS
+            || servicing_fee_bps_input > MAX_PLATFORM_FEE_BPS
+        {
+            return Err(Funds4GoodError::InvalidFeeBps.into());
+        }
+
+        let mint_vault_registry_account = next_account_info(account_info_iter)?;
+        let mut mint_vault_registry_data =
+            Self::verify_mint_vault_registry_account(mint_vault_registry_account, program_id)?;
+
+        if (origination_fee_bps_input > 0 || servicing_fee_bps_input > 0)
+            && mint_vault_registry_data.treasury_pubkey == Pubkey::default()
+        {
+            return Err(Funds4GoodError::TreasuryAccountNotRegistered.into());
+        }
+
+        mint_vault_registry_data.origination_fee_bps = origination_fee_bps_input;
+        mint_vault_registry_data.servicing_fee_bps = servicing_fee_bps_input;
+
+        MintVaultRegistryAccount::pack(
+            mint_vault_registry_data,
+            &mut mint_vault_registry_account.data.borrow_mut(),
+        )?;
+
+        Ok(())
+    }
+
+    fn process_transfer_treasury_account_ownership(
+        accounts: &[AccountInfo],
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let admin_account = next_account_info(account_info_iter)?;
+
+        if !admin_account.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let treasury_account = next_account_info(account_info_iter)?;
+
+        let rent = Rent::get()?;
+
+        if !rent.is_exempt(treasury_account.lamports(), treasury_account.data_len()) {
+            return Err(Funds4GoodError::NotRentExempt.into());
+        }
+
+        let token_program = next_account_info(account_info_iter)?;
+
+        if token_program.key != &spl_token::id() {
+            return Err(Funds4GoodError::InvalidTokenProgram.into());
+        }
+
+        let Funds4Good_config_account = next_account_info(account_info_iter)?;
+        let Funds4Good_config_data =
+            Self::verify_Funds4Good_config_account(Funds4Good_config_account, program_id)?;
+
+        if Funds4Good_config_data.admin_pubkey != *admin_account.key {
+            return Err(Funds4GoodError::AdminDoesNotMatched.into());
+        }
+
+        let mint_vault_registry_account = next_account_info(account_info_iter)?;
+        let mut mint_vault_registry_data =
+            Self::verify_mint_vault_registry_account(mint_vault_registry_account, program_id)?;
+
+        if mint_vault_registry_data.treasury_pubkey != Pubkey::default() {
+            return Err(Funds4GoodError::VaultAlreadyRecorded.into());
+        }
+
+        Self::verify_token_account_mint(treasury_account, &mint_vault_registry_data.mint_pubkey)?;
+
+        let (pda_treasury, _nonce) =
+            Pubkey::find_program_address(&[b"Funds4GoodFinanceTreasury"], program_id);
+
+        let treasury_owner_change_ix = spl_token::instruction::set_authority(
+            token_program.key,
+            treasury_account.key,
+            Some(&pda_treasury),
+            spl_token::instruction::AuthorityType::AccountOwner,
+            admin_account.key,
+            &[admin_account.key],
+        )?;
+
+        msg!("Calling the token program to transfer treasury account ownership to program...");
+        invoke(
+            &treasury_owner_change_ix,
+            &[
+                treasury_account.clone(),
+                admin_account.clone(),
+                token_program.clone(),
+            ],
+        )?;
+
+        mint_vault_registry_data.treasury_pubkey = *treasury_account.key;
+
+        MintVaultRegistryAccount::pack(
+            mint_vault_registry_data,
+            &mut mint_vault_registry_account.data.borrow_mut(),
+        )?;
+
+        Ok(())
+    }
+
+    fn process_withdraw_treasury(
+        accounts: &[AccountInfo],
+        amount_to_withdraw_input: u64,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let admin_account = next_account_info(account_info_iter)?;
+
+        if !admin_account.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let treasury_account = next_account_info(account_info_iter)?;
+        let admin_token_account_to_credit = next_account_info(account_info_iter)?;
+
+        let token_program = next_account_info(account_info_iter)?;
+
+        if token_program.key != &spl_token::id() {
+            return Err(Funds4GoodError::InvalidTokenProgram.into());
+        }
+
+        let Funds4Good_config_account = next_account_info(account_info_iter)?;
+        let Funds4Good_config_data =
+            Self::verify_Funds4Good_config_account(Funds4Good_config_account, program_id)?;
+
+        if Funds4Good_config_data.admin_pubkey != *admin_account.key {
+            return Err(Funds4GoodError::AdminDoesNotMatched.into());
+        }
+
+        let mint_vault_registry_account = next_account_info(account_info_iter)?;
+        let mint_vault_registry_data =
+            Self::verify_mint_vault_registry_account(mint_vault_registry_account, program_id)?;
+        Self::verify_treasury_account(treasury_account, &mint_vault_registry_data)?;
+        Self::verify_token_account_mint(
+            admin_token_account_to_credit,
+            &mint_vault_registry_data.mint_pubkey,
+        )?;
+
+        if amount_to_withdraw_input == 0 {
+            return Err(Funds4GoodError::InvalidWithdrawAmount.into());
+        }
+
+        let pda_account = next_account_info(account_info_iter)?;
+
+        let (pda, bump_seed) =
+            Pubkey::find_program_address(&[b"Funds4GoodFinanceTreasury"], program_id);
+        if pda != *pda_account.key {
+            return Err(Funds4GoodError::PdaAccountDoesNotMatched.into());
+        }
+
+        let transfer_treasury_amount_to_admin_ix = spl_token::instruction::transfer(
+            token_program.key,
+            treasury_account.key,
+            admin_token_account_to_credit.key,
+            &pda,
+            &[&pda],
+            amount_to_withdraw_input,
+        )?;
+        msg!("Calling the token program to transfer treasury amount to admin...");
+        invoke_signed(
+            &transfer_treasury_amount_to_admin_ix,
+            &[
+                treasury_account.clone(),
+                admin_token_account_to_credit.clone(),
+                pda_account.clone(),
+                token_program.clone(),
+            ],
+            &[&[&b"Funds4GoodFinanceTreasury"[..], &[bump_seed]]],
+        )?;
+
+        Ok(())
+    }
+
+    fn process_configure_reserve_pool(
+        accounts: &[AccountInfo],
+        reserve_fee_bps_input: u16,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let admin_account = next_account_info(account_info_iter)?;
+
+        if !admin_account.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let Funds4Good_config_account = next_account_info(account_info_iter)?;
+        let Funds4Good_config_data =
+            Self::verify_Funds4Good_config_account(Funds4Good_config_account, program_id)?;
+
+        if Funds4Good_config_data.admin_pubkey != *admin_account.key {
+            return Err(Funds4GoodError::AdminDoesNotMatched.into());
+        }
+
+        if reserve_fee_bps_input > MAX_RESERVE_FEE_BPS {
+            return Err(Funds4GoodError::InvalidFeeBps.into());
+        }
+
+        let mint_vault_registry_account = next_account_info(account_info_iter)?;
+        let mint_vault_registry_data =
+            Self::verify_mint_vault_registry_account(mint_vault_registry_account, program_id)?;
+
+        let reserve_pool_account = next_account_info(account_info_iter)?;
+        let reserve_vault_account = next_account_info(account_info_iter)?;
+        let token_program = next_account_info(account_info_iter)?;
+
+        if token_program.key != &spl_token::id() {
+            return Err(Funds4GoodError::InvalidTokenProgram.into());
+        }
+
+        let system_program_account = next_account_info(account_info_iter)?;
+
+        if system_program_account.key != &system_program::id() {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        let (pda_reserve_pool, reserve_pool_bump_seed) = Pubkey::find_program_address(
+            &[
+                b"Funds4GoodFinanceReserve",
+                mint_vault_registry_data.mint_pubkey.as_ref(),
+            ],
+            program_id,
+        );
+        if pda_reserve_pool != *reserve_pool_account.key {
+            return Err(Funds4GoodError::ReservePoolAccountDoesNotMatched.into());
+        }
+
+        let rent = Rent::get()?;
+
+        Self::create_pda_account_if_empty(
+            admin_account,
+            reserve_pool_account,
+            system_program_account,
+            &[
+                &b"Funds4GoodFinanceReserve"[..],
+                mint_vault_registry_data.mint_pubkey.as_ref(),
+                &[reserve_pool_bump_seed],
+            ],
+            ReservePoolAccount::LEN,
+            &rent,
+            program_id,
+        )?;
+
+        let mut reserve_pool_data =
+            ReservePoolAccount::unpack_unchecked(&reserve_pool_account.data.borrow())?;
+
+        // first call records reserve vault and hands its ownership to reserve pool PDA, later calls only update the fee
+        if !reserve_pool_data.is_initialized() {
+            if !rent.is_exempt(reserve_vault_account.lamports(), reserve_vault_account.data_len()) {
+                return Err(Funds4GoodError::NotRentExempt.into());
+            }
+
+            Self::verify_token_account_mint(
+                reserve_vault_account,
+                &mint_vault_registry_data.mint_pubkey,
+            )?;
+
+            let reserve_vault_owner_change_ix = spl_token::instruction::set_authority(
+                token_program.key,
+                reserve_vault_account.key,
+                Some(&pda_reserve_pool),
+                spl_token::instruction::AuthorityType::AccountOwner,
+                admin_account.key,
+                &[admin_account.key],
+            )?;
+
+            msg!("Calling the token program to transfer reserve vault account ownership to program...");
+            invoke(
+                &reserve_vault_owner_change_ix,
+                &[
+                    reserve_vault_account.clone(),
+                    admin_account.clone(),
+                    token_program.clone(),
+                ],
+            )?;
+
+            reserve_pool_data.is_initialized = true;
+            reserve_pool_data.acc_type = AccTypes::ReservePoolAcc as u8;
+            reserve_pool_data.mint_pubkey = mint_vault_registry_data.mint_pubkey;
+            reserve_pool_data.reserve_vault_pubkey = *reserve_vault_account.key;
+        } else {
+            Self::verify_reserve_vault_account(reserve_vault_account, &reserve_pool_data)?;
+        }
+
+        reserve_pool_data.reserve_fee_bps = reserve_fee_bps_input;
+
+        ReservePoolAccount::pack(reserve_pool_data, &mut reserve_pool_account.data.borrow_mut())?;
+
+        Ok(())
+    }
+
+    // anyone can mark a loan defaulted once grace period after its last emi date is over,
+    // shortfall of lenders is then covered from reserve pool as far as pool balance allows
+    fn process_mark_loan_defaulted(accounts: &[AccountInfo], program_id: &Pubkey) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let caller_account = next_account_info(account_info_iter)?;
+
+        if !caller_account.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let loan_info_storage_account = next_account_info(account_info_iter)?;
+
+        if loan_info_storage_account.owner != program_id {
+            return Err(Funds4GoodError::WrongAccountPassed.into());
+        }
+
+        let Funds4Good_coin_vault_account = next_account_info(account_info_iter)?;
+
+        let mint_vault_registry_account = next_account_info(account_info_iter)?;
+        let mut mint_vault_registry_data =
+            Self::verify_mint_vault_registry_account(mint_vault_registry_account, program_id)?;
+        Self::verify_vault_account(Funds4Good_coin_vault_account, &mint_vault_registry_data)?;
+        let coin_unit = Self::coin_unit(mint_vault_registry_data.mint_decimals)?;
+
+        let reserve_pool_account = next_account_info(account_info_iter)?;
+        let reserve_vault_account = next_account_info(account_info_iter)?;
+
+        let token_program = next_account_info(account_info_iter)?;
+
+        if token_program.key != &spl_token::id() {
+            return Err(Funds4GoodError::InvalidTokenProgram.into());
+        }
+
+        let lenders_data_storage_account = next_account_info(account_info_iter)?;
+
+        if lenders_data_storage_account.owner != program_id {
+            return Err(Funds4GoodError::WrongAccountPassed.into());
+        }
+
+        if mint_vault_registry_data.lenders_storage_acc_pubkey != *lenders_data_storage_account.key {
+            return Err(Funds4GoodError::LendersStorageAccountDoesNotMatched.into());
+        }
+
+        let mut loan_info_data_byte_array = loan_info_storage_account.try_borrow_mut_data()?;
+        let mut loan_info = LoanInfoAccount::new(&mut loan_info_data_byte_array)?;
+        let mut loan_info_header_data: LoanInfoAccDataHeader = loan_info.header()?;
+
+        if loan_info_header_data.acc_type != AccTypes::LoanInfoAcc as u8 {
+            return Err(Funds4GoodError::ExpectedAccountTypeMismatched.into());
+        }
+
+        if loan_info_header_data.loan_mint_pubkey != mint_vault_registry_data.mint_pubkey {
+            return Err(Funds4GoodError::LoanMintDoesNotMatched.into());
+        }
+
+        if loan_info_header_data.loan_status != LoanStatus::Active as u8 {
+            return Err(Funds4GoodError::LoanStatusDoesNotMatched.into());
+        }
+
+        let reserve_pool_data = Self::verify_loan_reserve_pool_account(
+            reserve_pool_account,
+            reserve_vault_account,
+            &mint_vault_registry_data,
+            &loan_info_header_data,
+            program_id,
+        )?;
+
+        let last_emi_date_timestamp = loan_info_header_data
+            .first_repayment_last_date_timestamp
+            .checked_add(
+                (loan_info_header_data.number_of_emis_needed_to_repay_the_loan as u64)
+                    .saturating_sub(1)
+                    .checked_mul(EMI_INTERVAL_DAYS * NUM_SECONDS_IN_ONE_DAY)
+                    .ok_or(Funds4GoodError::AmountOverflow)?,
+            )
+            .ok_or(Funds4GoodError::AmountOverflow)?;
+        let now = Clock::get()?.unix_timestamp as u64;
+
+        if now
+            <= last_emi_date_timestamp
+                .checked_add(LOAN_DEFAULT_GRACE_PERIOD_DAYS * NUM_SECONDS_IN_ONE_DAY)
+                .ok_or(Funds4GoodError::AmountOverflow)?
+        {
+            return Err(Funds4GoodError::LoanNotYetDefaulted.into());
+        }
+
+        let shortfall_amount = loan_info_header_data
+            .total_loan_amount
+            .checked_sub(loan_info_header_data.repaid_amount_by_borrower)
+            .ok_or(Funds4GoodError::AmountUnderflow)?;
+        // coverage is limited to contributions recorded in pool, not to whatever reserve vault holds
+        let coverable_amount = match &reserve_pool_data {
+            Some(reserve_pool_data) if shortfall_amount > reserve_pool_data.pool_balance => {
+                reserve_pool_data.pool_balance
+            }
+            Some(_) => shortfall_amount,
+            None => 0,
+        };
+
+        let mut lenders_storage_data_byte_array =
+            lenders_data_storage_account.try_borrow_mut_data()?;
+        let mut lenders_storage = LendersStorageAccount::new(&mut lenders_storage_data_byte_array)?;
+
+        // coverage is split in proportion to the amount each lender lent to this loan
+        let covered_amount = coverable_amount;
+        Self::credit_lenders_pro_rata(
+            &loan_info,
+            &mut lenders_storage,
+            &loan_info_header_data,
+            &mut mint_vault_registry_data,
+            covered_amount,
+        )?;
+
+        if covered_amount > 0 {
+            let (pda_reserve_pool, reserve_pool_bump_seed) = Pubkey::find_program_address(
+                &[
+                    b"Funds4GoodFinanceReserve",
+                    mint_vault_registry_data.mint_pubkey.as_ref(),
+                ],
+                program_id,
+            );
+
+            let transfer_coverage_to_vault_ix = spl_token::instruction::transfer(
+                token_program.key,
+                reserve_vault_account.key,
+                Funds4Good_coin_vault_account.key,
+                &pda_reserve_pool,
+                &[&pda_reserve_pool],
+                covered_amount,
+            )?;
+            msg!(
+                "Calling the token program to transfer coverage {} from reserve vault to vault...",
+                (covered_amount as f64 / coin_unit as f64)
+            );
+            invoke_signed(
+                &transfer_coverage_to_vault_ix,
+                &[
+                    reserve_vault_account.clone(),
+                    Funds4Good_coin_vault_account.clone(),
+                    reserve_pool_account.clone(),
+                    token_program.clone(),
+                ],
+                &[&[
+                    &b"Funds4GoodFinanceReserve"[..],
+                    mint_vault_registry_data.mint_pubkey.as_ref(),
+                    &[reserve_pool_bump_seed],
+                ]],
+            )?;
+
+            MintVaultRegistryAccount::pack(
+                mint_vault_registry_data,
+                &mut mint_vault_registry_account.data.borrow_mut(),
+            )?;
+        }
 
-        let vault_balance_decreased = Funds4Good_coin_vault_balance_before
-            .checked_sub(Funds4Good_coin_vault_balance_after)
-            .unwrap();
+        loan_info_header_data.loan_status = LoanStatus::Defaulted as u8;
+        loan_info.set_header(loan_info_header_data)?;
 
-        if vault_balance_decreased != withdraw_amount {
-            return Err(Funds4GoodError::ExpectedAmountMismatch.into());
+        Funds4GoodEvent::Defaulted {
+            loan_info_pubkey: *loan_info_storage_account.key,
+            shortfall_amount,
+            covered_amount,
+        }
+        .emit();
+
+        if let Some(mut reserve_pool_data) = reserve_pool_data {
+            reserve_pool_data.pool_balance = reserve_pool_data
+                .pool_balance
+                .checked_sub(covered_amount)
+                .ok_or(Funds4GoodError::AmountUnderflow)?;
+            reserve_pool_data.total_covered_amount = reserve_pool_data
+                .total_covered_amount
+                .checked_add(covered_amount)
+                .ok_or(Funds4GoodError::AmountOverflow)?;
+            reserve_pool_data.total_outstanding_principal = reserve_pool_data
+                .total_outstanding_principal
+                .checked_sub(shortfall_amount)
+                .ok_or(Funds4GoodError::AmountUnderflow)?;
+            reserve_pool_data.update_coverage_ratio();
+            ReservePoolAccount::pack(reserve_pool_data, &mut reserve_pool_account.data.borrow_mut())?;
         }
 
         Ok(())
     }
 
-    fn process_withdraw_collected_loan_funds(
-        accounts: &[AccountInfo],
-        program_id: &Pubkey,
+    // credits amount to free balance of lenders of a loan in proportion to their lent amount,
+    // rounding remainder goes to the last lender so that exactly amount is credited.
+    // lending totals of a lender grow by the share credited to that lender only
+    fn credit_lenders_pro_rata(
+        loan_info: &LoanInfoAccount,
+        lenders_storage: &mut LendersStorageAccount,
+        loan_info_header_data: &LoanInfoAccDataHeader,
+        mint_vault_registry_data: &mut MintVaultRegistryAccount,
+        amount: u64,
     ) -> ProgramResult {
-        let account_info_iter = &mut accounts.iter();
-        let borrower_main_account = next_account_info(account_info_iter)?;
-
-        if !borrower_main_account.is_signer {
-            return Err(ProgramError::MissingRequiredSignature);
+        let num_lenders = loan_info_header_data.next_index_to_store_lender_data as usize;
+        if amount == 0 {
+            return Ok(());
+        }
+        if num_lenders == 0 {
+            return Err(Funds4GoodError::LoanHasNoLenders.into());
         }
 
-        let borrower_Funds4Good_ata_to_credit = next_account_info(account_info_iter)?;
-
-        let Funds4Good_coin_vault_account = next_account_info(account_info_iter)?;
+        let mut loan_info_lenders_data: Vec<LoanInfoAccLendersData> = Vec::with_capacity(num_lenders);
+        let mut total_lent_amount: u128 = 0;
+        for i in 0..num_lenders {
+            let loan_info_lender_data = loan_info.lender(i)?;
+            total_lent_amount += loan_info_lender_data.lent_amount as u128;
+            loan_info_lenders_data.push(loan_info_lender_data);
+        }
 
-        /*
-        let Funds4Good_vault_pubkey = utils::get_Funds4Good_vault_pubkey();
+        if total_lent_amount == 0 {
+            return Err(Funds4GoodError::ExpectedAmountMismatch.into());
+        }
 
-        if Funds4Good_vault_pubkey != *Funds4Good_coin_vault_account.key {
-            return Err(Funds4GoodError::Funds4GoodVaultAccountDoesNotMatched.into());
+        let mut credited_amount: u64 = 0;
+        for (i, loan_info_lender_data) in loan_info_lenders_data.iter().enumerate() {
+            let lender_amount = if i + 1 == num_lenders {
+                amount - credited_amount
+            } else {
+                ((amount as u128) * (loan_info_lender_data.lent_amount as u128) / total_lent_amount)
+                    as u64
+            };
+
+            let mut lender_acc_data: LenderAccountData =
+                lenders_storage.lender(loan_info_lender_data.lender_id)?;
+
+            lender_acc_data.total_lending_amount = lender_acc_data
+                .total_lending_amount
+                .checked_add(lender_amount as u128)
+                .ok_or(Funds4GoodError::AmountOverflow)?;
+            lender_acc_data.total_unique_lending_amount = lender_acc_data
+                .total_unique_lending_amount
+                .checked_add(lender_amount)
+                .ok_or(Funds4GoodError::AmountOverflow)?;
+            lender_acc_data.amount_to_withdraw_or_lend = lender_acc_data
+                .amount_to_withdraw_or_lend
+                .checked_add(lender_amount)
+                .ok_or(Funds4GoodError::AmountOverflow)?;
+
+            lenders_storage.set_lender(loan_info_lender_data.lender_id, lender_acc_data)?;
+
+            credited_amount += lender_amount;
         }
 
-        */
+        // credited amount is owed from vault as lender free balances, caller packs registry
+        mint_vault_registry_data.vault_liabilities = mint_vault_registry_data
+            .vault_liabilities
+            .checked_add(amount)
+            .ok_or(Funds4GoodError::AmountOverflow)?;
 
-        let token_program = next_account_info(account_info_iter)?;
+        Ok(())
+    }
 
-        if token_program.key != &spl_token::id() {
-            return Err(Funds4GoodError::InvalidTokenProgram.into());
+    fn verify_reserve_pool_account(
+        reserve_pool_account: &AccountInfo,
+        mint_vault_registry_data: &MintVaultRegistryAccount,
+        program_id: &Pubkey,
+    ) -> Result<ReservePoolAccount, ProgramError> {
+        let (pda_reserve_pool, _bump_seed) = Pubkey::find_program_address(
+            &[
+                b"Funds4GoodFinanceReserve",
+                mint_vault_registry_data.mint_pubkey.as_ref(),
+            ],
+            program_id,
+        );
+        if pda_reserve_pool != *reserve_pool_account.key {
+            return Err(Funds4GoodError::ReservePoolAccountDoesNotMatched.into());
         }
 
-        let loan_info_storage_account = next_account_info(account_info_iter)?;
-
-        if loan_info_storage_account.owner != program_id {
+        if reserve_pool_account.owner != program_id {
             return Err(Funds4GoodError::WrongAccountPassed.into());
         }
 
-        // update lender payment in LoanInfoAccData
-        let mut loan_info_data_byte_array = loan_info_storage_account.try_borrow_mut_data()?;
-        let mut loan_info_header_data: LoanInfoAccDataHeader = state::unpack_to_loan_info_header(
-            &loan_info_data_byte_array
-                [state::LOAN_INFO_HEADER_START_INDEX..state::LOAN_INFO_HEADER_END_INDEX],
-        )
-        .unwrap();
+        let reserve_pool_data = ReservePoolAccount::unpack(&reserve_pool_account.data.borrow())?;
 
-        if loan_info_header_data.acc_type != AccTypes::LoanInfoAcc as u8 {
+        if reserve_pool_data.acc_type != AccTypes::ReservePoolAcc as u8 {
             return Err(Funds4GoodError::ExpectedAccountTypeMismatched.into());
         }
 
-        if loan_info_header_data.total_amount_lended < loan_info_header_data.total_loan_amount {
-            return Err(Funds4GoodError::CollectedLoanFundsAlreadyWithdrawn.into());
+        Ok(reserve_pool_data)
+    }
+
+    // reserve pool of a mint is optional, nothing exists at its address until admin configures it
+    fn verify_optional_reserve_pool_account(
+        reserve_pool_account: &AccountInfo,
+        mint_vault_registry_data: &MintVaultRegistryAccount,
+        program_id: &Pubkey,
+    ) -> Result<Option<ReservePoolAccount>, ProgramError> {
+        if !reserve_pool_account.data_is_empty() {
+            return Self::verify_reserve_pool_account(
+                reserve_pool_account,
+                mint_vault_registry_data,
+                program_id,
+            )
+            .map(Some);
         }
 
-        if loan_info_header_data.borrower_main_acc_pubkey != *borrower_main_account.key {
-            return Err(Funds4GoodError::BorrowerAccountMismatched.into());
+        let (pda_reserve_pool, _bump_seed) = Pubkey::find_program_address(
+            &[
+                b"Funds4GoodFinanceReserve",
+                mint_vault_registry_data.mint_pubkey.as_ref(),
+            ],
+            program_id,
+        );
+        if pda_reserve_pool != *reserve_pool_account.key {
+            return Err(Funds4GoodError::ReservePoolAccountDoesNotMatched.into());
         }
 
-        // borrower can withdraw total_amount_lended, after withdrawing we will set it to 0, so that he can't withdraw second time. We can also use a extra variable to store if funds
-        // withdrawn or not
+        Ok(None)
+    }
 
-        let Funds4Good_coin_vault_account_data_before =
-            TokenAccount::unpack(&Funds4Good_coin_vault_account.data.borrow())?;
-        let Funds4Good_coin_vault_balance_before = Funds4Good_coin_vault_account_data_before.amount;
+    // loans disbursed while reserve pool of their mint wasn't configured are not covered by it,
+    // for the others reserve pool and reserve vault are verified
+    fn verify_loan_reserve_pool_account(
+        reserve_pool_account: &AccountInfo,
+        reserve_vault_account: &AccountInfo,
+        mint_vault_registry_data: &MintVaultRegistryAccount,
+        loan_info_header_data: &LoanInfoAccDataHeader,
+        program_id: &Pubkey,
+    ) -> Result<Option<ReservePoolAccount>, ProgramError> {
+        if loan_info_header_data.is_reserve_covered == 0 {
+            return Ok(None);
+        }
 
-        let pda_account = next_account_info(account_info_iter)?;
+        let reserve_pool_data = Self::verify_reserve_pool_account(
+            reserve_pool_account,
+            mint_vault_registry_data,
+            program_id,
+        )?;
+        Self::verify_reserve_vault_account(reserve_vault_account, &reserve_pool_data)?;
+
+        Ok(Some(reserve_pool_data))
+    }
+
+    fn verify_reserve_vault_account(
+        reserve_vault_account: &AccountInfo,
+        reserve_pool_data: &ReservePoolAccount,
+    ) -> ProgramResult {
+        if reserve_pool_data.reserve_vault_pubkey != *reserve_vault_account.key {
+            return Err(Funds4GoodError::ReserveVaultAccountDoesNotMatched.into());
+        }
+
+        Ok(())
+    }
 
+    fn verify_Funds4Good_config_account(
+        Funds4Good_config_account: &AccountInfo,
+        program_id: &Pubkey,
+    ) -> Result<Funds4GoodConfigAccount, ProgramError> {
         // we can also store bump_seed to save computations
-        let (pda, bump_seed) = Pubkey::find_program_address(&[b"Funds4GoodFinance"], program_id);
-        if pda != *pda_account.key {
-            return Err(Funds4GoodError::PdaAccountDoesNotMatched.into());
+        let (pda_config, _bump_seed) =
+            Pubkey::find_program_address(&[b"Funds4GoodFinanceConfig"], program_id);
+        if pda_config != *Funds4Good_config_account.key {
+            return Err(Funds4GoodError::ConfigAccountDoesNotMatched.into());
         }
 
-        let transfer_collected_loan_funds_to_borrower_ix = spl_token::instruction::transfer(
-            token_program.key,
-            Funds4Good_coin_vault_account.key,
-            borrower_Funds4Good_ata_to_credit.key,
-            &pda,
-            &[&pda],
-            loan_info_header_data.total_amount_lended,
-        )?;
-        msg!("Calling the token program to transfer collected loan amount to borrower...");
-        msg!(
-            "amount of Funds4Good coin tokens to transfer {}, lender credit key {}",
-            (loan_info_header_data.total_amount_lended as f64 / Funds4Good_COIN_DECIMALS as f64),
-            borrower_Funds4Good_ata_to_credit.key.to_string()
-        );
-        invoke_signed(
-            &transfer_collected_loan_funds_to_borrower_ix,
-            &[
-                Funds4Good_coin_vault_account.clone(),
-                borrower_Funds4Good_ata_to_credit.clone(),
-                pda_account.clone(),
-                token_program.clone(),
-            ],
-            &[&[&b"Funds4GoodFinance"[..], &[bump_seed]]],
-        )?;
+        if Funds4Good_config_account.owner != program_id {
+            return Err(Funds4GoodError::WrongAccountPassed.into());
+        }
 
-        let Funds4Good_coin_vault_account_data_after =
-            TokenAccount::unpack(&Funds4Good_coin_vault_account.data.borrow())?;
-        let Funds4Good_coin_vault_balance_after = Funds4Good_coin_vault_account_data_after.amount;
-        msg!(
-            "Funds4GoodCoin vault balance after: {}",
-            Funds4Good_coin_vault_balance_after
-        );
+        let Funds4Good_config_data =
+            Funds4GoodConfigAccount::unpack(&Funds4Good_config_account.data.borrow())?;
 
-        let vault_balance_decreased = Funds4Good_coin_vault_balance_before
-            .checked_sub(Funds4Good_coin_vault_balance_after)
-            .unwrap();
+        if Funds4Good_config_data.acc_type != AccTypes::ConfigAcc as u8 {
+            return Err(Funds4GoodError::ExpectedAccountTypeMismatched.into());
+        }
 
-        if vault_balance_decreased != loan_info_header_data.total_amount_lended {
-            return Err(Funds4GoodError::ExpectedAmountMismatch.into());
+        Ok(Funds4Good_config_data)
+    }
+
+    // program data account of an upgradeable program is a pda of bpf_loader_upgradeable derived from program id,
+    // its bincode encoded state starts with u32 variant index 3 for ProgramData, u64 slot and Option<Pubkey> upgrade authority
+    fn verify_upgrade_authority(
+        program_data_account: &AccountInfo,
+        authority_pubkey: &Pubkey,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let (program_data_pubkey, _bump_seed) =
+            Pubkey::find_program_address(&[program_id.as_ref()], &bpf_loader_upgradeable::id());
+        if program_data_pubkey != *program_data_account.key
+            || program_data_account.owner != &bpf_loader_upgradeable::id()
+        {
+            return Err(Funds4GoodError::ProgramDataAccountDoesNotMatched.into());
         }
 
-        loan_info_header_data.total_amount_lended = 0u64;
+        let program_data = program_data_account.data.borrow();
+        if program_data.len() < PROGRAM_DATA_METADATA_SIZE {
+            return Err(Funds4GoodError::ProgramDataAccountDoesNotMatched.into());
+        }
+        let program_data = array_ref![program_data, 0, PROGRAM_DATA_METADATA_SIZE];
+        let (state_src, _slot_src, upgrade_authority_option_src, upgrade_authority_src) =
+            array_refs![program_data, 4, 8, 1, 32];
 
-        state::pack_to_loan_info_header(
-            loan_info_header_data,
-            &mut loan_info_data_byte_array
-                [state::LOAN_INFO_HEADER_START_INDEX..state::LOAN_INFO_HEADER_END_INDEX],
-        )
-        .unwrap();
+        if u32::from_le_bytes(*state_src) != PROGRAM_DATA_STATE_INDEX {
+            return Err(Funds4GoodError::ProgramDataAccountDoesNotMatched.into());
+        }
+
+        // a program without upgrade authority can't be initialized by anyone
+        if upgrade_authority_option_src[0] != 1
+            || Pubkey::new_from_array(*upgrade_authority_src) != *authority_pubkey
+        {
+            return Err(Funds4GoodError::UpgradeAuthorityDoesNotMatched.into());
+        }
 
         Ok(())
     }
 
-    fn process_transfer_Funds4Good_vault_account_ownership(
-        accounts: &[AccountInfo],
+    fn verify_mint_vault_registry_account(
+        mint_vault_registry_account: &AccountInfo,
         program_id: &Pubkey,
-    ) -> ProgramResult {
-        let account_info_iter = &mut accounts.iter();
-        let initializer_account = next_account_info(account_info_iter)?;
+    ) -> Result<MintVaultRegistryAccount, ProgramError> {
+        if mint_vault_registry_account.owner != program_id {
+            return Err(Funds4GoodError::WrongAccountPassed.into());
+        }
 
-        if !initializer_account.is_signer {
-            return Err(ProgramError::MissingRequiredSignature);
+        let mint_vault_registry_data =
+            MintVaultRegistryAccount::unpack(&mint_vault_registry_account.data.borrow())?;
+
+        if mint_vault_registry_data.acc_type != AccTypes::MintVaultRegistryAcc as u8 {
+            return Err(Funds4GoodError::ExpectedAccountTypeMismatched.into());
         }
 
-        let Funds4Good_vault_account = next_account_info(account_info_iter)?;
+        // registry address is derived from the mint it stores, so a registry of one mint can't be passed for another
+        let (pda_mint_vault_registry, _bump_seed) = Pubkey::find_program_address(
+            &[b"Funds4GoodFinanceVault", mint_vault_registry_data.mint_pubkey.as_ref()],
+            program_id,
+        );
+        if pda_mint_vault_registry != *mint_vault_registry_account.key {
+            return Err(Funds4GoodError::MintVaultRegistryAccountDoesNotMatched.into());
+        }
 
-        let (pda, _nonce) = Pubkey::find_program_address(&[b"Funds4GoodFinance"], program_id);
+        Ok(mint_vault_registry_data)
+    }
 
-        let rent = Rent::get()?;
+    fn verify_vault_account(
+        Funds4Good_coin_vault_account: &AccountInfo,
+        mint_vault_registry_data: &MintVaultRegistryAccount,
+    ) -> ProgramResult {
+        if mint_vault_registry_data.vault_pubkey != *Funds4Good_coin_vault_account.key {
+            return Err(Funds4GoodError::Funds4GoodVaultAccountDoesNotMatched.into());
+        }
 
-        if !rent.is_exempt(
-            Funds4Good_vault_account.lamports(),
-            Funds4Good_vault_account.data_len(),
-        ) {
-            return Err(Funds4GoodError::NotRentExempt.into());
+        Ok(())
+    }
+
+    fn verify_token_account_mint(
+        token_account: &AccountInfo,
+        expected_mint_pubkey: &Pubkey,
+    ) -> Result<TokenAccount, ProgramError> {
+        if token_account.owner != &spl_token::id() {
+            return Err(Funds4GoodError::InvalidTokenAccount.into());
         }
-        let token_program = next_account_info(account_info_iter)?;
 
-        if token_program.key != &spl_token::id() {
-            return Err(Funds4GoodError::InvalidTokenProgram.into());
+        let token_account_data = TokenAccount::unpack(&token_account.data.borrow())?;
+
+        if token_account_data.mint != *expected_mint_pubkey {
+            return Err(Funds4GoodError::TokenAccountMintDoesNotMatched.into());
         }
 
-        let Funds4Good_vault_owner_change_ix = spl_token::instruction::set_authority(
-            token_program.key,
-            Funds4Good_vault_account.key,
-            Some(&pda),
-            spl_token::instruction::AuthorityType::AccountOwner,
-            initializer_account.key,
-            &[&initializer_account.key],
-        )?;
+        Ok(token_account_data)
+    }
 
-        msg!("Calling the token program to transfer Funds4Good vault account ownership to program...");
-        invoke(
-            &Funds4Good_vault_owner_change_ix,
-            &[
-                Funds4Good_vault_account.clone(),
-                initializer_account.clone(),
-                token_program.clone(),
-            ],
-        )?;
+    // user token accounts must hold tokens of expected mint and belong to the user main account signing the instruction
+    fn verify_user_token_account(
+        token_account: &AccountInfo,
+        user_main_acc_pubkey: &Pubkey,
+        expected_mint_pubkey: &Pubkey,
+    ) -> ProgramResult {
+        let token_account_data = Self::verify_token_account_mint(token_account, expected_mint_pubkey)?;
+
+        if token_account_data.owner != *user_main_acc_pubkey {
+            return Err(Funds4GoodError::TokenAccountOwnerDoesNotMatched.into());
+        }
 
         Ok(())
     }
@@ -992,8 +3607,7 @@ impl Processor {
             return Err(Funds4GoodError::DataSizeNotMatched.into());
         }
 
-        let mut lenders_storage_data_byte_array =
-            lenders_storage_account.data.try_borrow_mut().unwrap();
+        let mut lenders_storage_data_byte_array = lenders_storage_account.try_borrow_mut_data()?;
 
         if lenders_storage_data_byte_array[0] != 0 {
             return Err(Funds4GoodError::LendersStorageDataAlreadyInitialized.into());
@@ -1002,6 +3616,8 @@ impl Processor {
         lenders_storage_data_byte_array[0] = AccTypes::LendersAcc as u8;
         // currently for prototype I'm making every lenders_data_storage_acc_number to 1, but in future when we need more accounts, we have to increment it for every new account generation
         lenders_storage_data_byte_array[1] = 1u8;
+        lenders_storage_data_byte_array[state::LENDERS_STORAGE_LAYOUT_VERSION_INDEX] =
+            state::LENDERS_STORAGE_LAYOUT_VERSION;
 
         Ok(())
     }
@@ -1041,7 +3657,7 @@ impl Processor {
             return Err(Funds4GoodError::NotRentExempt.into());
         }
 
-        // put a condition if borrower_storage_account.data_len() != 75, then error
+        // new borrower storage accounts must have current layout size, unpack_unchecked fails otherwise
 
         let mut borrower_data =
             BorrowerAccount::unpack_unchecked(&borrower_storage_account.data.borrow())?;
@@ -1054,6 +3670,7 @@ impl Processor {
         borrower_data.acc_type = AccTypes::BorrowerAcc as u8;
         borrower_data.borrower_main_acc_pubkey = *borrower_main_account.key;
         borrower_data.credit_score = 500_000_000_000u64;
+        borrower_data.layout_version = state::BORROWER_ACCOUNT_LAYOUT_VERSION;
 
         BorrowerAccount::pack(
             borrower_data,
@@ -1097,7 +3714,7 @@ impl Processor {
             return Err(Funds4GoodError::NotRentExempt.into());
         }
 
-        // put a condition if guarantor_storage_account.data_len() != 75, then error
+        // new guarantor storage accounts must have current layout size, unpack_unchecked fails otherwise
 
         let mut guarantor_data =
             GuarantorAccount::unpack_unchecked(&guarantor_storage_account.data.borrow())?;
@@ -1110,6 +3727,7 @@ impl Processor {
         guarantor_data.acc_type = AccTypes::GuarantorAcc as u8;
         guarantor_data.guarantor_main_acc_pubkey = *guarantor_main_account.key;
         guarantor_data.approval_score = 500_000_000_000u64;
+        guarantor_data.layout_version = state::GUARANTOR_ACCOUNT_LAYOUT_VERSION;
 
         GuarantorAccount::pack(
             guarantor_data,
@@ -1156,19 +3774,25 @@ impl Processor {
             return Err(Funds4GoodError::AccountMismatched.into());
         }
 
+        // airdrop user storage only holds total airdropped amount as u64
+        if Funds4Good_coin_airdrop_user_storage_account.data_len() != 8 {
+            return Err(Funds4GoodError::DataSizeNotMatched.into());
+        }
+
         let mut Funds4Good_coin_airdrop_user_storage_byte_array =
             Funds4Good_coin_airdrop_user_storage_account.try_borrow_mut_data()?;
         let stored_amount = u64::from_le_bytes(
-            Funds4Good_coin_airdrop_user_storage_byte_array[0..8]
-                .try_into()
-                .unwrap(),
+            Funds4Good_coin_airdrop_user_storage_byte_array
+                .get(0..8)
+                .and_then(|stored_amount_bytes| stored_amount_bytes.try_into().ok())
+                .ok_or(Funds4GoodError::DataSizeNotMatched)?,
         );
         if stored_amount >= max_amount_to_airdrop {
             return Err(Funds4GoodError::UserAlreadyAirdroped.into());
         }
         let new_total_airdrop_amount_for_user: u64 =
-            stored_amount.checked_add(amount_to_airdrop).unwrap();
-        Funds4Good_coin_airdrop_user_storage_byte_array
+            stored_amount.checked_add(amount_to_airdrop).ok_or(Funds4GoodError::AmountOverflow)?;
+        Funds4Good_coin_airdrop_user_storage_byte_array[0..8]
             .copy_from_slice(&new_total_airdrop_amount_for_user.to_le_bytes());
 
         let user_Funds4Good_coin_associated_token_to_credit_account =
@@ -1176,6 +3800,18 @@ impl Processor {
 
         let airdrop_vault_Funds4Good_coin_account = next_account_info(account_info_iter)?;
 
+        let Funds4Good_config_account = next_account_info(account_info_iter)?;
+        let Funds4Good_config_data =
+            Self::verify_Funds4Good_config_account(Funds4Good_config_account, program_id)?;
+        if Funds4Good_config_data.airdrop_vault_pubkey != *airdrop_vault_Funds4Good_coin_account.key {
+            return Err(Funds4GoodError::AirdropVaultAccountDoesNotMatched.into());
+        }
+        Self::verify_user_token_account(
+            user_Funds4Good_coin_associated_token_to_credit_account,
+            airdrop_user_main_account.key,
+            &Funds4Good_config_data.coin_mint_pubkey,
+        )?;
+
         let token_program = next_account_info(account_info_iter)?;
 
         if token_program.key != &spl_token::id() {
@@ -1220,12 +3856,19 @@ impl Processor {
         let airdrop_vault_balance_after = airdrop_vault_account_data_after.amount;
         let vault_balance_decreased = airdrop_vault_balance_before
             .checked_sub(airdrop_vault_balance_after)
-            .unwrap();
+            .ok_or(Funds4GoodError::AmountUnderflow)?;
 
         if vault_balance_decreased != amount_to_airdrop {
             return Err(Funds4GoodError::ExpectedAmountMismatch.into());
         }
 
+        Funds4GoodEvent::Airdropped {
+            user_pubkey: *airdrop_user_main_account.key,
+            amount: amount_to_airdrop,
+            total_airdropped_amount: new_total_airdrop_amount_for_user,
+        }
+        .emit();
+
         Ok(())
     }
 
@@ -1264,7 +3907,7 @@ impl Processor {
             Some(&pda),
             spl_token::instruction::AuthorityType::AccountOwner,
             initializer_account.key,
-            &[&initializer_account.key],
+            &[initializer_account.key],
         )?;
 
         msg!("Calling the token program to transfer Funds4Good airdrop vault account ownership to program...");
@@ -1277,22 +3920,207 @@ impl Processor {
             ],
         )?;
 
+        // Funds4Good vault ownership must be transferred first, as that creates config account with coin mint
+        let Funds4Good_config_account = next_account_info(account_info_iter)?;
+        let mut Funds4Good_config_data =
+            Self::verify_Funds4Good_config_account(Funds4Good_config_account, program_id)?;
+
+        if Funds4Good_config_data.admin_pubkey != *initializer_account.key {
+            return Err(Funds4GoodError::AdminDoesNotMatched.into());
+        }
+
+        if Funds4Good_config_data.airdrop_vault_pubkey != Pubkey::default() {
+            return Err(Funds4GoodError::VaultAlreadyRecorded.into());
+        }
+
+        Self::verify_token_account_mint(
+            airdrop_vault_Funds4Good_coin_account,
+            &Funds4Good_config_data.coin_mint_pubkey,
+        )?;
+
+        Funds4Good_config_data.airdrop_vault_pubkey = *airdrop_vault_Funds4Good_coin_account.key;
+
+        Funds4GoodConfigAccount::pack(
+            Funds4Good_config_data,
+            &mut Funds4Good_config_account.data.borrow_mut(),
+        )?;
+
         Ok(())
     }
 
-    fn process_close_loan_info_account(accounts: &[AccountInfo]) -> ProgramResult {
+    // upgrades a borrower, guarantor, loan info or lenders storage account from legacy layout (version 0) to current layout
+    // in place by growing it with the layout version byte. Data itself is unchanged, so anyone can pay to migrate any account.
+    // Legacy loan info accounts also get their header extension filled, loan mint can't be read from the account
+    // so admin has to pass config and mint vault registry of the loan
+    fn process_migrate_account(accounts: &[AccountInfo], program_id: &Pubkey) -> ProgramResult {
         let account_info_iter = &mut accounts.iter();
+        let payer_account = next_account_info(account_info_iter)?;
+
+        if !payer_account.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let account_to_migrate = next_account_info(account_info_iter)?;
+
+        if account_to_migrate.owner != program_id {
+            return Err(Funds4GoodError::WrongAccountPassed.into());
+        }
+
+        let system_program_account = next_account_info(account_info_iter)?;
+
+        if system_program_account.key != &system_program::id() {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        let legacy_data_len = account_to_migrate.data_len();
+        // acc type is at byte 1 of Pack accounts (after is_initialized) and at byte 0 of loan info and lenders storage
+        let (acc_type_index, expected_acc_type, layout_version, new_data_len) = match legacy_data_len {
+            state::BORROWER_ACCOUNT_LEGACY_LEN => (
+                1usize,
+                AccTypes::BorrowerAcc,
+                state::BORROWER_ACCOUNT_LAYOUT_VERSION,
+                BorrowerAccount::LEN,
+            ),
+            state::GUARANTOR_ACCOUNT_LEGACY_LEN => (
+                1usize,
+                AccTypes::GuarantorAcc,
+                state::GUARANTOR_ACCOUNT_LAYOUT_VERSION,
+                GuarantorAccount::LEN,
+            ),
+            state::LOAN_INFO_ACC_LEGACY_DATA_SIZE => (
+                0usize,
+                AccTypes::LoanInfoAcc,
+                state::LOAN_INFO_LAYOUT_VERSION,
+                state::LOAN_INFO_ACC_DATA_SIZE,
+            ),
+            state::LENDERS_STORAGE_ACC_LEGACY_DATA_SIZE => (
+                0usize,
+                AccTypes::LendersAcc,
+                state::LENDERS_STORAGE_LAYOUT_VERSION,
+                state::LENDERS_STORAGE_ACC_DATA_SIZE,
+            ),
+            BorrowerAccount::LEN
+            | GuarantorAccount::LEN
+            | state::LOAN_INFO_ACC_DATA_SIZE
+            | state::LENDERS_STORAGE_ACC_DATA_SIZE => {
+                return Err(Funds4GoodError::AccountAlreadyMigrated.into());
+            }
+            _ => return Err(Funds4GoodError::DataSizeNotMatched.into()),
+        };
+
+        if account_to_migrate.data.borrow()[acc_type_index] != expected_acc_type as u8 {
+            return Err(Funds4GoodError::ExpectedAccountTypeMismatched.into());
+        }
+
+        let loan_mint_pubkey = if legacy_data_len == state::LOAN_INFO_ACC_LEGACY_DATA_SIZE {
+            let Funds4Good_config_account = next_account_info(account_info_iter)?;
+            let Funds4Good_config_data =
+                Self::verify_Funds4Good_config_account(Funds4Good_config_account, program_id)?;
+
+            if Funds4Good_config_data.admin_pubkey != *payer_account.key {
+                return Err(Funds4GoodError::AdminDoesNotMatched.into());
+            }
+
+            let mint_vault_registry_account = next_account_info(account_info_iter)?;
+            let mint_vault_registry_data =
+                Self::verify_mint_vault_registry_account(mint_vault_registry_account, program_id)?;
+            Some(mint_vault_registry_data.mint_pubkey)
+        } else {
+            None
+        };
+
+        let rent = Rent::get()?;
+        let rent_exempt_lamports = rent.minimum_balance(new_data_len);
+
+        if account_to_migrate.lamports() < rent_exempt_lamports {
+            let top_up_rent_ix = system_instruction::transfer(
+                payer_account.key,
+                account_to_migrate.key,
+                rent_exempt_lamports - account_to_migrate.lamports(),
+            );
+            msg!("Calling the system program to top up rent of migrated account...");
+            invoke(
+                &top_up_rent_ix,
+                &[
+                    payer_account.clone(),
+                    account_to_migrate.clone(),
+                    system_program_account.clone(),
+                ],
+            )?;
+        }
+
+        account_to_migrate.realloc(new_data_len, true)?;
+
+        if let Some(loan_mint_pubkey) = loan_mint_pubkey {
+            let mut loan_info_data_byte_array = account_to_migrate.try_borrow_mut_data()?;
+            let mut loan_info = LoanInfoAccount::new(&mut loan_info_data_byte_array)?;
+            loan_info.migrate_legacy_header(loan_mint_pubkey)?;
+        } else {
+            account_to_migrate.data.borrow_mut()[legacy_data_len] = layout_version;
+        }
+
+        msg!(
+            "account {} migrated to layout version {}",
+            account_to_migrate.key,
+            layout_version
+        );
 
         Ok(())
     }
 
-    fn process_return_funds_to_lenders(
-        accounts: &[AccountInfo],
-        num_accounts_input: u16,
-        program_id: &Pubkey,
-    ) -> ProgramResult {
+    // anyone can audit a vault, its balance is checked against vault_liabilities of registry
+    // instead of summing lenders storage and loan infos, which wouldn't fit in one instruction
+    fn process_audit_vault(accounts: &[AccountInfo], program_id: &Pubkey) -> ProgramResult {
         let account_info_iter = &mut accounts.iter();
+        let Funds4Good_coin_vault_account = next_account_info(account_info_iter)?;
 
-        Ok(())
+        let mint_vault_registry_account = next_account_info(account_info_iter)?;
+        let mint_vault_registry_data =
+            Self::verify_mint_vault_registry_account(mint_vault_registry_account, program_id)?;
+        Self::verify_vault_account(Funds4Good_coin_vault_account, &mint_vault_registry_data)?;
+        let vault_data = Self::verify_token_account_mint(
+            Funds4Good_coin_vault_account,
+            &mint_vault_registry_data.mint_pubkey,
+        )?;
+
+        // reserve vault is only read when reserve pool of the mint is configured
+        let reserve_pool_account = next_account_info(account_info_iter)?;
+        let reserve_pool_data = Self::verify_optional_reserve_pool_account(
+            reserve_pool_account,
+            &mint_vault_registry_data,
+            program_id,
+        )?;
+        let reserve_vault_balance = match &reserve_pool_data {
+            Some(reserve_pool_data) => {
+                let reserve_vault_account = next_account_info(account_info_iter)?;
+                Self::verify_reserve_vault_account(reserve_vault_account, reserve_pool_data)?;
+                let reserve_vault_data = Self::verify_token_account_mint(
+                    reserve_vault_account,
+                    &mint_vault_registry_data.mint_pubkey,
+                )?;
+                Some(reserve_vault_data.amount)
+            }
+            None => None,
+        };
+
+        let vault_audit_report = audit::audit_vault(
+            &mint_vault_registry_data,
+            vault_data.amount,
+            reserve_pool_data.as_ref(),
+            reserve_vault_balance,
+        )?;
+        vault_audit_report.log();
+        vault_audit_report.verify()
+    }
+
+    // closing loan info accounts is not supported yet, instruction fails instead of silently succeeding
+    fn process_close_loan_info_account() -> ProgramResult {
+        Err(Funds4GoodError::InvalidInstruction.into())
+    }
+
+    // lenders of a loan that is not disbursed get their funds back through CancelLoan,
+    // instruction fails instead of silently succeeding
+    fn process_return_funds_to_lenders() -> ProgramResult {
+        Err(Funds4GoodError::InvalidInstruction.into())
     }
 }
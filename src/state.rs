@@ -11,13 +11,65 @@ pub enum AccTypes {
     LendersAcc = 3,
     GuarantorAcc = 4,
     LoanInfoAcc = 5,
+    LenderWithdrawalHistoryAcc = 6,
+    ConfigAcc = 7,
+    MintVaultRegistryAcc = 8,
+    ReservePoolAcc = 9,
 }
 // AccTypes::LenderAcc as u8
 
-// total bytes needed to store LoanInfoAccData = 116 + 9000 + 800 = 9916
-pub const LOAN_INFO_ACC_DATA_SIZE: usize = 9916;
-// total bytes needed to store lender data = 65*50_000 + 2
-pub const LENDERS_STORAGE_ACC_DATA_SIZE: usize = 3_250_002;
+pub enum LoanStatus {
+    Fundraising = 0,
+    Active = 1,
+    Repaid = 2,
+    Defaulted = 3,
+    Cancelled = 4,
+}
+
+// Every account layout ends with a layout version byte. Accounts created before versioning don't have it,
+// they are recognised by their legacy size and read as layout version 0 until MigrateAccount grows them by the version byte.
+// Legacy loan info accounts also lack the header extension, amendments region and version byte, so they can only be
+// used after MigrateAccount has grown them to LOAN_INFO_ACC_DATA_SIZE
+pub const BORROWER_ACCOUNT_LAYOUT_VERSION: u8 = 1;
+pub const GUARANTOR_ACCOUNT_LAYOUT_VERSION: u8 = 1;
+pub const LOAN_INFO_LAYOUT_VERSION: u8 = 1;
+pub const LENDERS_STORAGE_LAYOUT_VERSION: u8 = 1;
+
+// total bytes needed to store LoanInfoAccData = 116 header + 9000 lenders + 800 repayments + 83 header extension
+// + 140 amendments + 1 layout version byte = 10140
+pub const LOAN_INFO_ACC_DATA_SIZE: usize = 10140;
+// legacy loan info account = 116 header + 9000 lenders + 800 repayments = 9916
+pub const LOAN_INFO_ACC_LEGACY_DATA_SIZE: usize = 9916;
+// total bytes needed to store lender data = 65*50_000 + 2 + 1 layout version byte
+pub const LENDERS_STORAGE_ACC_DATA_SIZE: usize = 3_250_003;
+pub const LENDERS_STORAGE_ACC_LEGACY_DATA_SIZE: usize = 3_250_002;
+pub const BORROWER_ACCOUNT_LEGACY_LEN: usize = 75;
+pub const GUARANTOR_ACCOUNT_LEGACY_LEN: usize = 42;
+
+// reads a Pack account which may still have its legacy size, missing version byte is read as 0
+fn unpack_with_legacy_len<T: Pack + IsInitialized>(
+    src: &[u8],
+    legacy_len: usize,
+) -> Result<T, ProgramError> {
+    if src.len() == legacy_len {
+        let mut current_src = vec![0u8; T::LEN];
+        current_src[..legacy_len].copy_from_slice(src);
+        return T::unpack(&current_src);
+    }
+    T::unpack(src)
+}
+
+// writes a Pack account which may still have its legacy size, version byte is left out for legacy accounts
+fn pack_with_legacy_len<T: Pack>(src: T, dst: &mut [u8], legacy_len: usize) -> Result<(), ProgramError> {
+    if dst.len() == legacy_len {
+        let mut current_dst = vec![0u8; T::LEN];
+        T::pack(src, &mut current_dst)?;
+        dst.copy_from_slice(&current_dst[..legacy_len]);
+        return Ok(());
+    }
+    T::pack(src, dst)
+}
+
 pub struct BorrowerAccount {
     pub is_initialized: bool,
     pub acc_type: u8,
@@ -25,6 +77,21 @@ pub struct BorrowerAccount {
     pub borrower_main_acc_pubkey: Pubkey,
     pub credit_score: u64,
     pub active_loan_address: Pubkey,
+    pub layout_version: u8,
+}
+
+impl BorrowerAccount {
+    pub fn unpack_versioned(src: &[u8]) -> Result<Self, ProgramError> {
+        let borrower_data: BorrowerAccount = unpack_with_legacy_len(src, BORROWER_ACCOUNT_LEGACY_LEN)?;
+        if borrower_data.layout_version > BORROWER_ACCOUNT_LAYOUT_VERSION {
+            return Err(Funds4GoodError::UnsupportedLayoutVersion.into());
+        }
+        Ok(borrower_data)
+    }
+
+    pub fn pack_versioned(src: Self, dst: &mut [u8]) -> Result<(), ProgramError> {
+        pack_with_legacy_len(src, dst, BORROWER_ACCOUNT_LEGACY_LEN)
+    }
 }
 
 impl Sealed for BorrowerAccount {}
@@ -36,7 +103,7 @@ impl IsInitialized for BorrowerAccount {
 }
 
 impl Pack for BorrowerAccount {
-    const LEN: usize = 75;
+    const LEN: usize = 76;
     fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
         let src = array_ref![src, 0, BorrowerAccount::LEN];
         let (
@@ -46,7 +113,8 @@ impl Pack for BorrowerAccount {
             borrower_main_acc_pubkey_src,
             credit_score_src,
             active_loan_address_src,
-        ) = array_refs![src, 1, 1, 1, 32, 8, 32];
+            layout_version_src,
+        ) = array_refs![src, 1, 1, 1, 32, 8, 32, 1];
 
         let is_initialized = match is_initialized_src {
             [0] => false,
@@ -61,6 +129,7 @@ impl Pack for BorrowerAccount {
             borrower_main_acc_pubkey: Pubkey::new_from_array(*borrower_main_acc_pubkey_src),
             credit_score: u64::from_le_bytes(*credit_score_src),
             active_loan_address: Pubkey::new_from_array(*active_loan_address_src),
+            layout_version: layout_version_src[0],
         })
     }
 
@@ -73,7 +142,8 @@ impl Pack for BorrowerAccount {
             borrower_main_acc_pubkey_dst,
             credit_score_dst,
             active_loan_address_dst,
-        ) = mut_array_refs![dst, 1, 1, 1, 32, 8, 32];
+            layout_version_dst,
+        ) = mut_array_refs![dst, 1, 1, 1, 32, 8, 32, 1];
         let BorrowerAccount {
             is_initialized,
             acc_type,
@@ -81,13 +151,15 @@ impl Pack for BorrowerAccount {
             borrower_main_acc_pubkey,
             credit_score,
             active_loan_address,
+            layout_version,
         } = self;
         is_initialized_dst[0] = *is_initialized as u8;
-        type_dst[0] = *acc_type as u8;
-        is_active_loan_dst[0] = *is_active_loan as u8;
+        type_dst[0] = *acc_type;
+        is_active_loan_dst[0] = *is_active_loan;
         borrower_main_acc_pubkey_dst.copy_from_slice(borrower_main_acc_pubkey.as_ref());
         *credit_score_dst = credit_score.to_le_bytes();
         active_loan_address_dst.copy_from_slice(active_loan_address.as_ref());
+        layout_version_dst[0] = *layout_version;
     }
 }
 
@@ -96,6 +168,22 @@ pub struct GuarantorAccount {
     pub acc_type: u8,
     pub guarantor_main_acc_pubkey: Pubkey,
     pub approval_score: u64,
+    pub layout_version: u8,
+}
+
+impl GuarantorAccount {
+    pub fn unpack_versioned(src: &[u8]) -> Result<Self, ProgramError> {
+        let guarantor_data: GuarantorAccount =
+            unpack_with_legacy_len(src, GUARANTOR_ACCOUNT_LEGACY_LEN)?;
+        if guarantor_data.layout_version > GUARANTOR_ACCOUNT_LAYOUT_VERSION {
+            return Err(Funds4GoodError::UnsupportedLayoutVersion.into());
+        }
+        Ok(guarantor_data)
+    }
+
+    pub fn pack_versioned(src: Self, dst: &mut [u8]) -> Result<(), ProgramError> {
+        pack_with_legacy_len(src, dst, GUARANTOR_ACCOUNT_LEGACY_LEN)
+    }
 }
 
 impl Sealed for GuarantorAccount {}
@@ -107,11 +195,16 @@ impl IsInitialized for GuarantorAccount {
 }
 
 impl Pack for GuarantorAccount {
-    const LEN: usize = 42;
+    const LEN: usize = 43;
     fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
         let src = array_ref![src, 0, GuarantorAccount::LEN];
-        let (is_initialized_src, type_src, guarantor_main_acc_pubkey_src, approval_score_src) =
-            array_refs![src, 1, 1, 32, 8];
+        let (
+            is_initialized_src,
+            type_src,
+            guarantor_main_acc_pubkey_src,
+            approval_score_src,
+            layout_version_src,
+        ) = array_refs![src, 1, 1, 32, 8, 1];
 
         let is_initialized = match is_initialized_src {
             [0] => false,
@@ -124,27 +217,364 @@ impl Pack for GuarantorAccount {
             acc_type: type_src[0],
             guarantor_main_acc_pubkey: Pubkey::new_from_array(*guarantor_main_acc_pubkey_src),
             approval_score: u64::from_le_bytes(*approval_score_src),
+            layout_version: layout_version_src[0],
         })
     }
 
     fn pack_into_slice(&self, dst: &mut [u8]) {
         let dst = array_mut_ref![dst, 0, GuarantorAccount::LEN];
-        let (is_initialized_dst, type_dst, guarantor_main_acc_pubkey_dst, approval_score_dst) =
-            mut_array_refs![dst, 1, 1, 32, 8];
+        let (
+            is_initialized_dst,
+            type_dst,
+            guarantor_main_acc_pubkey_dst,
+            approval_score_dst,
+            layout_version_dst,
+        ) = mut_array_refs![dst, 1, 1, 32, 8, 1];
         let GuarantorAccount {
             is_initialized,
             acc_type,
             guarantor_main_acc_pubkey,
             approval_score,
+            layout_version,
         } = self;
         is_initialized_dst[0] = *is_initialized as u8;
-        type_dst[0] = *acc_type as u8;
+        type_dst[0] = *acc_type;
         guarantor_main_acc_pubkey_dst.copy_from_slice(guarantor_main_acc_pubkey.as_ref());
         *approval_score_dst = approval_score.to_le_bytes();
+        layout_version_dst[0] = *layout_version;
+    }
+}
+
+// Funds4GoodConfigAccount is a PDA derived with seed "Funds4GoodFinanceConfig", it is created when first vault ownership is transferred to program
+// and stores admin, Funds4Good coin mint and vault, and airdrop vault. Vaults of every mint are recorded in MintVaultRegistryAccount
+pub struct Funds4GoodConfigAccount {
+    pub is_initialized: bool,
+    pub acc_type: u8,
+    pub admin_pubkey: Pubkey,
+    pub coin_mint_pubkey: Pubkey,
+    pub vault_pubkey: Pubkey,
+    pub airdrop_vault_pubkey: Pubkey,
+}
+
+impl Sealed for Funds4GoodConfigAccount {}
+
+impl IsInitialized for Funds4GoodConfigAccount {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Pack for Funds4GoodConfigAccount {
+    const LEN: usize = 130;
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, Funds4GoodConfigAccount::LEN];
+        let (
+            is_initialized_src,
+            type_src,
+            admin_pubkey_src,
+            coin_mint_pubkey_src,
+            vault_pubkey_src,
+            airdrop_vault_pubkey_src,
+        ) = array_refs![src, 1, 1, 32, 32, 32, 32];
+
+        let is_initialized = match is_initialized_src {
+            [0] => false,
+            [1] => true,
+            _ => return Err(ProgramError::InvalidAccountData),
+        };
+
+        Ok(Funds4GoodConfigAccount {
+            is_initialized,
+            acc_type: type_src[0],
+            admin_pubkey: Pubkey::new_from_array(*admin_pubkey_src),
+            coin_mint_pubkey: Pubkey::new_from_array(*coin_mint_pubkey_src),
+            vault_pubkey: Pubkey::new_from_array(*vault_pubkey_src),
+            airdrop_vault_pubkey: Pubkey::new_from_array(*airdrop_vault_pubkey_src),
+        })
+    }
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, Funds4GoodConfigAccount::LEN];
+        let (
+            is_initialized_dst,
+            type_dst,
+            admin_pubkey_dst,
+            coin_mint_pubkey_dst,
+            vault_pubkey_dst,
+            airdrop_vault_pubkey_dst,
+        ) = mut_array_refs![dst, 1, 1, 32, 32, 32, 32];
+        let Funds4GoodConfigAccount {
+            is_initialized,
+            acc_type,
+            admin_pubkey,
+            coin_mint_pubkey,
+            vault_pubkey,
+            airdrop_vault_pubkey,
+        } = self;
+        is_initialized_dst[0] = *is_initialized as u8;
+        type_dst[0] = *acc_type;
+        admin_pubkey_dst.copy_from_slice(admin_pubkey.as_ref());
+        coin_mint_pubkey_dst.copy_from_slice(coin_mint_pubkey.as_ref());
+        vault_pubkey_dst.copy_from_slice(vault_pubkey.as_ref());
+        airdrop_vault_pubkey_dst.copy_from_slice(airdrop_vault_pubkey.as_ref());
+    }
+}
+
+// MintVaultRegistryAccount is a PDA derived with seeds "Funds4GoodFinanceVault" and mint pubkey, one for every mint loans can be denominated in
+// It binds the vault token account and lenders storage account of that mint, so lender balances of different mints are never mixed
+// decimals are read from mint account at registration and used to scale coin amounts like minimum lending amount
+// platform fees are in basis points, origination fee is taken at disbursement and servicing fee on every emi, both go to treasury_pubkey
+// late fee of an overdue installment is late_fee_flat_amount plus late_fee_daily_bps of the installment for every day it is late,
+// late_fee_lenders_share_bps of it goes to lenders and rest goes to treasury
+// prepayment_discount_bps of remaining principal is waived when a loan is paid off early
+// interest_rate_bps is yearly simple interest on outstanding principal, it accrues daily from disbursement or last repayment and goes to lenders
+// vault_liabilities is what vault owes lenders, their free balances plus funds lent to loans still in fundraising,
+// every instruction moving either of them updates it so AuditVault can check vault without reading every lender and loan
+pub struct MintVaultRegistryAccount {
+    pub is_initialized: bool,
+    pub acc_type: u8,
+    pub mint_pubkey: Pubkey,
+    pub vault_pubkey: Pubkey,
+    pub lenders_storage_acc_pubkey: Pubkey,
+    pub mint_decimals: u8,
+    pub treasury_pubkey: Pubkey,
+    pub origination_fee_bps: u16,
+    pub servicing_fee_bps: u16,
+    pub late_fee_flat_amount: u64,
+    pub late_fee_daily_bps: u16,
+    pub late_fee_lenders_share_bps: u16,
+    pub prepayment_discount_bps: u16,
+    pub interest_rate_bps: u16,
+    pub vault_liabilities: u64,
+}
+
+impl Sealed for MintVaultRegistryAccount {}
+
+impl IsInitialized for MintVaultRegistryAccount {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Pack for MintVaultRegistryAccount {
+    const LEN: usize = 159;
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, MintVaultRegistryAccount::LEN];
+        let (
+            is_initialized_src,
+            type_src,
+            mint_pubkey_src,
+            vault_pubkey_src,
+            lenders_storage_acc_pubkey_src,
+            mint_decimals_src,
+            treasury_pubkey_src,
+            origination_fee_bps_src,
+            servicing_fee_bps_src,
+            late_fee_flat_amount_src,
+            late_fee_daily_bps_src,
+            late_fee_lenders_share_bps_src,
+            prepayment_discount_bps_src,
+            interest_rate_bps_src,
+            vault_liabilities_src,
+        ) = array_refs![src, 1, 1, 32, 32, 32, 1, 32, 2, 2, 8, 2, 2, 2, 2, 8];
+
+        let is_initialized = match is_initialized_src {
+            [0] => false,
+            [1] => true,
+            _ => return Err(ProgramError::InvalidAccountData),
+        };
+
+        Ok(MintVaultRegistryAccount {
+            is_initialized,
+            acc_type: type_src[0],
+            mint_pubkey: Pubkey::new_from_array(*mint_pubkey_src),
+            vault_pubkey: Pubkey::new_from_array(*vault_pubkey_src),
+            lenders_storage_acc_pubkey: Pubkey::new_from_array(*lenders_storage_acc_pubkey_src),
+            mint_decimals: mint_decimals_src[0],
+            treasury_pubkey: Pubkey::new_from_array(*treasury_pubkey_src),
+            origination_fee_bps: u16::from_le_bytes(*origination_fee_bps_src),
+            servicing_fee_bps: u16::from_le_bytes(*servicing_fee_bps_src),
+            late_fee_flat_amount: u64::from_le_bytes(*late_fee_flat_amount_src),
+            late_fee_daily_bps: u16::from_le_bytes(*late_fee_daily_bps_src),
+            late_fee_lenders_share_bps: u16::from_le_bytes(*late_fee_lenders_share_bps_src),
+            prepayment_discount_bps: u16::from_le_bytes(*prepayment_discount_bps_src),
+            interest_rate_bps: u16::from_le_bytes(*interest_rate_bps_src),
+            vault_liabilities: u64::from_le_bytes(*vault_liabilities_src),
+        })
+    }
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, MintVaultRegistryAccount::LEN];
+        let (
+            is_initialized_dst,
+            type_dst,
+            mint_pubkey_dst,
+            vault_pubkey_dst,
+            lenders_storage_acc_pubkey_dst,
+            mint_decimals_dst,
+            treasury_pubkey_dst,
+            origination_fee_bps_dst,
+            servicing_fee_bps_dst,
+            late_fee_flat_amount_dst,
+            late_fee_daily_bps_dst,
+            late_fee_lenders_share_bps_dst,
+            prepayment_discount_bps_dst,
+            interest_rate_bps_dst,
+            vault_liabilities_dst,
+        ) = mut_array_refs![dst, 1, 1, 32, 32, 32, 1, 32, 2, 2, 8, 2, 2, 2, 2, 8];
+        let MintVaultRegistryAccount {
+            is_initialized,
+            acc_type,
+            mint_pubkey,
+            vault_pubkey,
+            lenders_storage_acc_pubkey,
+            mint_decimals,
+            treasury_pubkey,
+            origination_fee_bps,
+            servicing_fee_bps,
+            late_fee_flat_amount,
+            late_fee_daily_bps,
+            late_fee_lenders_share_bps,
+            prepayment_discount_bps,
+            interest_rate_bps,
+            vault_liabilities,
+        } = self;
+        is_initialized_dst[0] = *is_initialized as u8;
+        type_dst[0] = *acc_type;
+        mint_pubkey_dst.copy_from_slice(mint_pubkey.as_ref());
+        vault_pubkey_dst.copy_from_slice(vault_pubkey.as_ref());
+        lenders_storage_acc_pubkey_dst.copy_from_slice(lenders_storage_acc_pubkey.as_ref());
+        mint_decimals_dst[0] = *mint_decimals;
+        treasury_pubkey_dst.copy_from_slice(treasury_pubkey.as_ref());
+        *origination_fee_bps_dst = origination_fee_bps.to_le_bytes();
+        *servicing_fee_bps_dst = servicing_fee_bps.to_le_bytes();
+        *late_fee_flat_amount_dst = late_fee_flat_amount.to_le_bytes();
+        *late_fee_daily_bps_dst = late_fee_daily_bps.to_le_bytes();
+        *late_fee_lenders_share_bps_dst = late_fee_lenders_share_bps.to_le_bytes();
+        *prepayment_discount_bps_dst = prepayment_discount_bps.to_le_bytes();
+        *interest_rate_bps_dst = interest_rate_bps.to_le_bytes();
+        *vault_liabilities_dst = vault_liabilities.to_le_bytes();
+    }
+}
+
+// ReservePoolAccount is a PDA derived with seeds "Funds4GoodFinanceReserve" and mint pubkey, it is also the owner of reserve vault token account
+// reserve vault receives reserve_fee_bps of every emi and covers lenders shortfall when a loan of this mint is defaulted
+// coverage_ratio_bps = pool_balance * 10_000 / total_outstanding_principal, it is 0 when nothing is outstanding
+pub struct ReservePoolAccount {
+    pub is_initialized: bool,
+    pub acc_type: u8,
+    pub mint_pubkey: Pubkey,
+    pub reserve_vault_pubkey: Pubkey,
+    pub reserve_fee_bps: u16,
+    pub pool_balance: u64,
+    pub total_outstanding_principal: u64,
+    pub total_contributed_amount: u64,
+    pub total_covered_amount: u64,
+    pub coverage_ratio_bps: u64,
+}
+
+impl Sealed for ReservePoolAccount {}
+
+impl IsInitialized for ReservePoolAccount {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Pack for ReservePoolAccount {
+    const LEN: usize = 108;
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, ReservePoolAccount::LEN];
+        let (
+            is_initialized_src,
+            type_src,
+            mint_pubkey_src,
+            reserve_vault_pubkey_src,
+            reserve_fee_bps_src,
+            pool_balance_src,
+            total_outstanding_principal_src,
+            total_contributed_amount_src,
+            total_covered_amount_src,
+            coverage_ratio_bps_src,
+        ) = array_refs![src, 1, 1, 32, 32, 2, 8, 8, 8, 8, 8];
+
+        let is_initialized = match is_initialized_src {
+            [0] => false,
+            [1] => true,
+            _ => return Err(ProgramError::InvalidAccountData),
+        };
+
+        Ok(ReservePoolAccount {
+            is_initialized,
+            acc_type: type_src[0],
+            mint_pubkey: Pubkey::new_from_array(*mint_pubkey_src),
+            reserve_vault_pubkey: Pubkey::new_from_array(*reserve_vault_pubkey_src),
+            reserve_fee_bps: u16::from_le_bytes(*reserve_fee_bps_src),
+            pool_balance: u64::from_le_bytes(*pool_balance_src),
+            total_outstanding_principal: u64::from_le_bytes(*total_outstanding_principal_src),
+            total_contributed_amount: u64::from_le_bytes(*total_contributed_amount_src),
+            total_covered_amount: u64::from_le_bytes(*total_covered_amount_src),
+            coverage_ratio_bps: u64::from_le_bytes(*coverage_ratio_bps_src),
+        })
+    }
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, ReservePoolAccount::LEN];
+        let (
+            is_initialized_dst,
+            type_dst,
+            mint_pubkey_dst,
+            reserve_vault_pubkey_dst,
+            reserve_fee_bps_dst,
+            pool_balance_dst,
+            total_outstanding_principal_dst,
+            total_contributed_amount_dst,
+            total_covered_amount_dst,
+            coverage_ratio_bps_dst,
+        ) = mut_array_refs![dst, 1, 1, 32, 32, 2, 8, 8, 8, 8, 8];
+        let ReservePoolAccount {
+            is_initialized,
+            acc_type,
+            mint_pubkey,
+            reserve_vault_pubkey,
+            reserve_fee_bps,
+            pool_balance,
+            total_outstanding_principal,
+            total_contributed_amount,
+            total_covered_amount,
+            coverage_ratio_bps,
+        } = self;
+        is_initialized_dst[0] = *is_initialized as u8;
+        type_dst[0] = *acc_type;
+        mint_pubkey_dst.copy_from_slice(mint_pubkey.as_ref());
+        reserve_vault_pubkey_dst.copy_from_slice(reserve_vault_pubkey.as_ref());
+        *reserve_fee_bps_dst = reserve_fee_bps.to_le_bytes();
+        *pool_balance_dst = pool_balance.to_le_bytes();
+        *total_outstanding_principal_dst = total_outstanding_principal.to_le_bytes();
+        *total_contributed_amount_dst = total_contributed_amount.to_le_bytes();
+        *total_covered_amount_dst = total_covered_amount.to_le_bytes();
+        *coverage_ratio_bps_dst = coverage_ratio_bps.to_le_bytes();
+    }
+}
+
+impl ReservePoolAccount {
+    pub fn update_coverage_ratio(&mut self) {
+        self.coverage_ratio_bps = if self.total_outstanding_principal == 0 {
+            0
+        } else {
+            ((self.pool_balance as u128) * 10_000u128 / (self.total_outstanding_principal as u128))
+                as u64
+        };
     }
 }
 
-// LoanInfoAccDataHeader has 116 bytes of data
+// LoanInfoAccDataHeader has 199 bytes of data, first 116 bytes are the original header up to number_of_emis_needed_to_repay_the_loan
+// and fields from loan_mint_pubkey onwards are its 83 byte extension. In loan info account the extension is stored after repayments
+// region, so adding header fields never moves lenders and repayments regions of existing accounts
+// guarantor_paid_amount is everything guarantor paid for overdue emis and guarantor_claim_amount is what borrower still owes guarantor
+// min_funding_amount is 0 when loan needs full funding, otherwise borrower can accept at least this much after fundraising ends
+// is_reserve_covered is 1 when reserve pool of loan mint was configured at disbursement, only such loans contribute to and are covered by it
+// disbursement_timestamp is 0 until borrower withdraws collected funds, interest accrues from it until first repayment
 pub struct LoanInfoAccDataHeader {
     pub acc_type: u8,
     pub borrower_main_acc_pubkey: Pubkey,
@@ -158,10 +588,20 @@ pub struct LoanInfoAccDataHeader {
     pub next_index_to_store_lender_data: u8,
     pub next_index_to_store_repayment_info: u8,
     pub number_of_emis_needed_to_repay_the_loan: u8,
+    pub loan_mint_pubkey: Pubkey,
+    pub loan_status: u8,
+    pub penalty_paid_amount: u64,
+    pub schedule_version: u8,
+    pub schedule_repaid_amount_base: u64,
+    pub guarantor_claim_amount: u64,
+    pub guarantor_paid_amount: u64,
+    pub min_funding_amount: u64,
+    pub is_reserve_covered: u8,
+    pub disbursement_timestamp: u64,
 }
 
 pub fn unpack_to_loan_info_header(input: &[u8]) -> Result<LoanInfoAccDataHeader, ProgramError> {
-    const LOAN_INFO_HEADER_DATA_BYTES: usize = 116;
+    const LOAN_INFO_HEADER_DATA_BYTES: usize = 199;
     if input.len() != LOAN_INFO_HEADER_DATA_BYTES {
         return Err(ProgramError::InvalidAccountData);
     }
@@ -179,7 +619,17 @@ pub fn unpack_to_loan_info_header(input: &[u8]) -> Result<LoanInfoAccDataHeader,
         next_index_to_store_lender_data_src,
         next_index_to_store_repayment_info_src,
         number_of_emis_needed_to_repay_the_loan_src,
-    ) = array_refs![input, 1, 32, 32, 8, 8, 8, 8, 8, 8, 1, 1, 1];
+        loan_mint_pubkey_src,
+        loan_status_src,
+        penalty_paid_amount_src,
+        schedule_version_src,
+        schedule_repaid_amount_base_src,
+        guarantor_claim_amount_src,
+        guarantor_paid_amount_src,
+        min_funding_amount_src,
+        is_reserve_covered_src,
+        disbursement_timestamp_src,
+    ) = array_refs![input, 1, 32, 32, 8, 8, 8, 8, 8, 8, 1, 1, 1, 32, 1, 8, 1, 8, 8, 8, 8, 1, 8];
 
     if acc_type_src[0] != AccTypes::LoanInfoAcc as u8 {
         return Err(Funds4GoodError::ExpectedAccountTypeMismatched.into());
@@ -202,12 +652,22 @@ pub fn unpack_to_loan_info_header(input: &[u8]) -> Result<LoanInfoAccDataHeader,
         next_index_to_store_lender_data: next_index_to_store_lender_data_src[0],
         next_index_to_store_repayment_info: next_index_to_store_repayment_info_src[0],
         number_of_emis_needed_to_repay_the_loan: number_of_emis_needed_to_repay_the_loan_src[0],
+        loan_mint_pubkey: Pubkey::new_from_array(*loan_mint_pubkey_src),
+        loan_status: loan_status_src[0],
+        penalty_paid_amount: u64::from_le_bytes(*penalty_paid_amount_src),
+        schedule_version: schedule_version_src[0],
+        schedule_repaid_amount_base: u64::from_le_bytes(*schedule_repaid_amount_base_src),
+        guarantor_claim_amount: u64::from_le_bytes(*guarantor_claim_amount_src),
+        guarantor_paid_amount: u64::from_le_bytes(*guarantor_paid_amount_src),
+        min_funding_amount: u64::from_le_bytes(*min_funding_amount_src),
+        is_reserve_covered: is_reserve_covered_src[0],
+        disbursement_timestamp: u64::from_le_bytes(*disbursement_timestamp_src),
     })
 }
 
      
 pub fn unpack_unchecked_to_loan_info_header(input: &[u8]) -> Result<LoanInfoAccDataHeader, ProgramError> {
-    const LOAN_INFO_HEADER_DATA_BYTES: usize = 116;
+    const LOAN_INFO_HEADER_DATA_BYTES: usize = 199;
     if input.len() != LOAN_INFO_HEADER_DATA_BYTES {
         return Err(ProgramError::InvalidAccountData);
     }
@@ -225,7 +685,17 @@ pub fn unpack_unchecked_to_loan_info_header(input: &[u8]) -> Result<LoanInfoAccD
         next_index_to_store_lender_data_src,
         next_index_to_store_repayment_info_src,
         number_of_emis_needed_to_repay_the_loan_src,
-    ) = array_refs![input, 1, 32, 32, 8, 8, 8, 8, 8, 8, 1, 1, 1];
+        loan_mint_pubkey_src,
+        loan_status_src,
+        penalty_paid_amount_src,
+        schedule_version_src,
+        schedule_repaid_amount_base_src,
+        guarantor_claim_amount_src,
+        guarantor_paid_amount_src,
+        min_funding_amount_src,
+        is_reserve_covered_src,
+        disbursement_timestamp_src,
+    ) = array_refs![input, 1, 32, 32, 8, 8, 8, 8, 8, 8, 1, 1, 1, 32, 1, 8, 1, 8, 8, 8, 8, 1, 8];
 /*
     if acc_type_src[0] != AccTypes::LoanInfoAcc as u8 {
         return Err(Funds4GoodError::ExpectedAccountTypeMismatched.into());
@@ -248,6 +718,16 @@ pub fn unpack_unchecked_to_loan_info_header(input: &[u8]) -> Result<LoanInfoAccD
         next_index_to_store_lender_data: next_index_to_store_lender_data_src[0],
         next_index_to_store_repayment_info: next_index_to_store_repayment_info_src[0],
         number_of_emis_needed_to_repay_the_loan: number_of_emis_needed_to_repay_the_loan_src[0],
+        loan_mint_pubkey: Pubkey::new_from_array(*loan_mint_pubkey_src),
+        loan_status: loan_status_src[0],
+        penalty_paid_amount: u64::from_le_bytes(*penalty_paid_amount_src),
+        schedule_version: schedule_version_src[0],
+        schedule_repaid_amount_base: u64::from_le_bytes(*schedule_repaid_amount_base_src),
+        guarantor_claim_amount: u64::from_le_bytes(*guarantor_claim_amount_src),
+        guarantor_paid_amount: u64::from_le_bytes(*guarantor_paid_amount_src),
+        min_funding_amount: u64::from_le_bytes(*min_funding_amount_src),
+        is_reserve_covered: is_reserve_covered_src[0],
+        disbursement_timestamp: u64::from_le_bytes(*disbursement_timestamp_src),
     })
 }
 
@@ -255,7 +735,7 @@ pub fn pack_to_loan_info_header(
     src: LoanInfoAccDataHeader,
     dst: &mut [u8],
 ) -> Result<(), ProgramError> {
-    const LOAN_INFO_HEADER_DATA_BYTES: usize = 116;
+    const LOAN_INFO_HEADER_DATA_BYTES: usize = 199;
     if dst.len() != LOAN_INFO_HEADER_DATA_BYTES {
         return Err(ProgramError::InvalidAccountData);
     }
@@ -274,7 +754,17 @@ pub fn pack_to_loan_info_header(
         next_index_to_store_lender_data_dst,
         next_index_to_store_repayment_info_dst,
         number_of_emis_needed_to_repay_the_loan_dst,
-    ) = mut_array_refs![dst, 1, 32, 32, 8, 8, 8, 8, 8, 8, 1, 1, 1];
+        loan_mint_pubkey_dst,
+        loan_status_dst,
+        penalty_paid_amount_dst,
+        schedule_version_dst,
+        schedule_repaid_amount_base_dst,
+        guarantor_claim_amount_dst,
+        guarantor_paid_amount_dst,
+        min_funding_amount_dst,
+        is_reserve_covered_dst,
+        disbursement_timestamp_dst,
+    ) = mut_array_refs![dst, 1, 32, 32, 8, 8, 8, 8, 8, 8, 1, 1, 1, 32, 1, 8, 1, 8, 8, 8, 8, 1, 8];
     let LoanInfoAccDataHeader {
         acc_type,
         borrower_main_acc_pubkey,
@@ -288,6 +778,16 @@ pub fn pack_to_loan_info_header(
         next_index_to_store_lender_data,
         next_index_to_store_repayment_info,
         number_of_emis_needed_to_repay_the_loan,
+        loan_mint_pubkey,
+        loan_status,
+        penalty_paid_amount,
+        schedule_version,
+        schedule_repaid_amount_base,
+        guarantor_claim_amount,
+        guarantor_paid_amount,
+        min_funding_amount,
+        is_reserve_covered,
+        disbursement_timestamp,
     } = src;
     acc_type_dst[0] = acc_type;
     borrower_main_acc_pubkey_dst.copy_from_slice(borrower_main_acc_pubkey.as_ref());
@@ -301,6 +801,101 @@ pub fn pack_to_loan_info_header(
     next_index_to_store_lender_data_dst[0] = next_index_to_store_lender_data;
     next_index_to_store_repayment_info_dst[0] = next_index_to_store_repayment_info;
     number_of_emis_needed_to_repay_the_loan_dst[0] = number_of_emis_needed_to_repay_the_loan;
+    loan_mint_pubkey_dst.copy_from_slice(loan_mint_pubkey.as_ref());
+    loan_status_dst[0] = loan_status;
+    *penalty_paid_amount_dst = penalty_paid_amount.to_le_bytes();
+    schedule_version_dst[0] = schedule_version;
+    *schedule_repaid_amount_base_dst = schedule_repaid_amount_base.to_le_bytes();
+    *guarantor_claim_amount_dst = guarantor_claim_amount.to_le_bytes();
+    *guarantor_paid_amount_dst = guarantor_paid_amount.to_le_bytes();
+    *min_funding_amount_dst = min_funding_amount.to_le_bytes();
+    is_reserve_covered_dst[0] = is_reserve_covered;
+    *disbursement_timestamp_dst = disbursement_timestamp.to_le_bytes();
+    Ok(())
+}
+
+// each LoanInfoAccAmendmentData takes 35 bytes, it keeps the schedule a loan had before restructuring
+// and the schedule it got, so that original schedule is never lost. schedule_version is 1 for the first amendment
+pub struct LoanInfoAccAmendmentData {
+    pub schedule_version: u8,
+    pub amendment_timestamp: u64,
+    pub previous_number_of_emis: u8,
+    pub new_number_of_emis: u8,
+    pub previous_first_repayment_last_date_timestamp: u64,
+    pub new_first_repayment_last_date_timestamp: u64,
+    pub repaid_amount_at_amendment: u64,
+}
+
+pub fn unpack_to_loan_info_acc_amendment_data(
+    input: &[u8],
+) -> Result<LoanInfoAccAmendmentData, ProgramError> {
+    const LOAN_INFO_ACC_AMENDMENT_DATA_BYTES: usize = 35;
+    if input.len() != LOAN_INFO_ACC_AMENDMENT_DATA_BYTES {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let input = array_ref![input, 0, LOAN_INFO_ACC_AMENDMENT_DATA_BYTES];
+    let (
+        schedule_version_src,
+        amendment_timestamp_src,
+        previous_number_of_emis_src,
+        new_number_of_emis_src,
+        previous_first_repayment_last_date_timestamp_src,
+        new_first_repayment_last_date_timestamp_src,
+        repaid_amount_at_amendment_src,
+    ) = array_refs![input, 1, 8, 1, 1, 8, 8, 8];
+
+    Ok(LoanInfoAccAmendmentData {
+        schedule_version: schedule_version_src[0],
+        amendment_timestamp: u64::from_le_bytes(*amendment_timestamp_src),
+        previous_number_of_emis: previous_number_of_emis_src[0],
+        new_number_of_emis: new_number_of_emis_src[0],
+        previous_first_repayment_last_date_timestamp: u64::from_le_bytes(
+            *previous_first_repayment_last_date_timestamp_src,
+        ),
+        new_first_repayment_last_date_timestamp: u64::from_le_bytes(
+            *new_first_repayment_last_date_timestamp_src,
+        ),
+        repaid_amount_at_amendment: u64::from_le_bytes(*repaid_amount_at_amendment_src),
+    })
+}
+
+pub fn pack_to_loan_info_acc_amendment_data(
+    src: LoanInfoAccAmendmentData,
+    dst: &mut [u8],
+) -> Result<(), ProgramError> {
+    const LOAN_INFO_ACC_AMENDMENT_DATA_BYTES: usize = 35;
+    if dst.len() != LOAN_INFO_ACC_AMENDMENT_DATA_BYTES {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let dst = array_mut_ref![dst, 0, LOAN_INFO_ACC_AMENDMENT_DATA_BYTES];
+    let (
+        schedule_version_dst,
+        amendment_timestamp_dst,
+        previous_number_of_emis_dst,
+        new_number_of_emis_dst,
+        previous_first_repayment_last_date_timestamp_dst,
+        new_first_repayment_last_date_timestamp_dst,
+        repaid_amount_at_amendment_dst,
+    ) = mut_array_refs![dst, 1, 8, 1, 1, 8, 8, 8];
+    let LoanInfoAccAmendmentData {
+        schedule_version,
+        amendment_timestamp,
+        previous_number_of_emis,
+        new_number_of_emis,
+        previous_first_repayment_last_date_timestamp,
+        new_first_repayment_last_date_timestamp,
+        repaid_amount_at_amendment,
+    } = src;
+    schedule_version_dst[0] = schedule_version;
+    *amendment_timestamp_dst = amendment_timestamp.to_le_bytes();
+    previous_number_of_emis_dst[0] = previous_number_of_emis;
+    new_number_of_emis_dst[0] = new_number_of_emis;
+    *previous_first_repayment_last_date_timestamp_dst =
+        previous_first_repayment_last_date_timestamp.to_le_bytes();
+    *new_first_repayment_last_date_timestamp_dst =
+        new_first_repayment_last_date_timestamp.to_le_bytes();
+    *repaid_amount_at_amendment_dst = repaid_amount_at_amendment.to_le_bytes();
     Ok(())
 }
 
@@ -376,6 +971,42 @@ pub struct LoanInfoAccRepaymentData {
     pub emi_repayment_amount: u64,
 }
 
+pub fn unpack_to_loan_info_acc_repayment_data(
+    input: &[u8],
+) -> Result<LoanInfoAccRepaymentData, ProgramError> {
+    const LOAN_INFO_ACC_REPAYMENT_DATA_BYTES: usize = 16;
+    if input.len() != LOAN_INFO_ACC_REPAYMENT_DATA_BYTES {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let input = array_ref![input, 0, LOAN_INFO_ACC_REPAYMENT_DATA_BYTES];
+    let (emi_repayment_timestamp_src, emi_repayment_amount_src) = array_refs![input, 8, 8];
+
+    Ok(LoanInfoAccRepaymentData {
+        emi_repayment_timestamp: u64::from_le_bytes(*emi_repayment_timestamp_src),
+        emi_repayment_amount: u64::from_le_bytes(*emi_repayment_amount_src),
+    })
+}
+
+pub fn pack_to_loan_info_acc_repayment_data(
+    src: LoanInfoAccRepaymentData,
+    dst: &mut [u8],
+) -> Result<(), ProgramError> {
+    const LOAN_INFO_ACC_REPAYMENT_DATA_BYTES: usize = 16;
+    if dst.len() != LOAN_INFO_ACC_REPAYMENT_DATA_BYTES {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let dst = array_mut_ref![dst, 0, LOAN_INFO_ACC_REPAYMENT_DATA_BYTES];
+    let (emi_repayment_timestamp_dst, emi_repayment_amount_dst) = mut_array_refs![dst, 8, 8];
+    let LoanInfoAccRepaymentData {
+        emi_repayment_timestamp,
+        emi_repayment_amount,
+    } = src;
+    *emi_repayment_timestamp_dst = emi_repayment_timestamp.to_le_bytes();
+    *emi_repayment_amount_dst = emi_repayment_amount.to_le_bytes();
+    Ok(())
+}
+
 /*
 
 pub struct LoanInfoAccData {
@@ -387,17 +1018,23 @@ pub struct LoanInfoAccData {
 
 */
 
-pub const LOAN_INFO_HEADER_DATA_BYTES: usize = 116;
+pub const LOAN_INFO_HEADER_DATA_BYTES: usize = 199;
+pub const LOAN_INFO_HEADER_BASE_DATA_BYTES: usize = 116;
+pub const LOAN_INFO_HEADER_EXTENSION_DATA_BYTES: usize =
+    LOAN_INFO_HEADER_DATA_BYTES - LOAN_INFO_HEADER_BASE_DATA_BYTES;
 pub const LOAN_INFO_ACC_LENDER_DATA_BYTES: usize = 45;
 pub const LOAN_INFO_LENDERS_DATA_BYTES: usize = 9000;
 pub const LOAN_INFO_REPAYMENT_DATA_BYTES: usize = 800;
-// total bytes needed to store LoanInfoAccData = 116 + 9000 + 800 = 9916
-pub const LOAN_INFO_DATA_TOTAL_BYTES: usize =
-    LOAN_INFO_HEADER_DATA_BYTES + LOAN_INFO_LENDERS_DATA_BYTES + LOAN_INFO_REPAYMENT_DATA_BYTES;
+pub const LOAN_INFO_AMENDMENTS_DATA_BYTES: usize = 140;
+// total bytes needed to store LoanInfoAccData = 116 + 9000 + 800 + 83 + 140 = 10139
+pub const LOAN_INFO_DATA_TOTAL_BYTES: usize = LOAN_INFO_HEADER_DATA_BYTES
+    + LOAN_INFO_LENDERS_DATA_BYTES
+    + LOAN_INFO_REPAYMENT_DATA_BYTES
+    + LOAN_INFO_AMENDMENTS_DATA_BYTES;
 pub const LOAN_INFO_ACC_DATA_TYPE_INDEX: usize = 0;
 pub const LOAN_INFO_HEADER_START_INDEX: usize = 0;
 pub const LOAN_INFO_HEADER_END_INDEX: usize =
-    LOAN_INFO_HEADER_START_INDEX + LOAN_INFO_HEADER_DATA_BYTES;
+    LOAN_INFO_HEADER_START_INDEX + LOAN_INFO_HEADER_BASE_DATA_BYTES;
 pub const PUBKEY_DATA_BYTES: usize = 32;
 pub const TIMESTAMP_DATA_BYTES: usize = 8;
 pub const AMOUNT_DATA_BYTES: usize = 8;
@@ -425,15 +1062,47 @@ pub const LOAN_INFO_NEXT_INDEX_TO_STORE_REPAYMENT_DATA: usize =
     LOAN_INFO_NEXT_INDEX_TO_STORE_LENDER_DATA + U8_DATA_BYTES;
 pub const LOAN_INFO_NUMBER_EMIS_NEEDED_TO_REPAY_LOAN_EI: usize =
     LOAN_INFO_NEXT_INDEX_TO_STORE_REPAYMENT_DATA + U8_DATA_BYTES;
+pub const LOAN_INFO_LOAN_MINT_EI: usize = LOAN_INFO_ACC_HEADER_EXTENSION_SI + PUBKEY_DATA_BYTES;
+pub const LOAN_INFO_LOAN_STATUS_EI: usize = LOAN_INFO_LOAN_MINT_EI + U8_DATA_BYTES;
+pub const LOAN_INFO_PENALTY_PAID_AMOUNT_EI: usize = LOAN_INFO_LOAN_STATUS_EI + AMOUNT_DATA_BYTES;
+pub const LOAN_INFO_SCHEDULE_VERSION_EI: usize = LOAN_INFO_PENALTY_PAID_AMOUNT_EI + U8_DATA_BYTES;
+pub const LOAN_INFO_SCHEDULE_REPAID_AMOUNT_BASE_EI: usize =
+    LOAN_INFO_SCHEDULE_VERSION_EI + AMOUNT_DATA_BYTES;
+pub const LOAN_INFO_GUARANTOR_CLAIM_AMOUNT_EI: usize =
+    LOAN_INFO_SCHEDULE_REPAID_AMOUNT_BASE_EI + AMOUNT_DATA_BYTES;
+pub const LOAN_INFO_GUARANTOR_PAID_AMOUNT_EI: usize =
+    LOAN_INFO_GUARANTOR_CLAIM_AMOUNT_EI + AMOUNT_DATA_BYTES;
+pub const LOAN_INFO_MIN_FUNDING_AMOUNT_EI: usize =
+    LOAN_INFO_GUARANTOR_PAID_AMOUNT_EI + AMOUNT_DATA_BYTES;
+pub const LOAN_INFO_IS_RESERVE_COVERED_EI: usize = LOAN_INFO_MIN_FUNDING_AMOUNT_EI + U8_DATA_BYTES;
+pub const LOAN_INFO_DISBURSEMENT_TIMESTAMP_EI: usize =
+    LOAN_INFO_IS_RESERVE_COVERED_EI + TIMESTAMP_DATA_BYTES;
 
-pub const LOAN_INFO_ACC_LENDERS_DATA_START_INDEX: usize = LOAN_INFO_HEADER_DATA_BYTES;
+pub const LOAN_INFO_ACC_LENDERS_DATA_START_INDEX: usize = LOAN_INFO_HEADER_END_INDEX;
 pub const LOAN_INFO_ACC_LENDERS_DATA_END_INDEX: usize =
     LOAN_INFO_ACC_LENDERS_DATA_START_INDEX + LOAN_INFO_LENDERS_DATA_BYTES;
 pub const LOAN_INFO_ACC_REPAYMENT_DATA_SI: usize = LOAN_INFO_ACC_LENDERS_DATA_END_INDEX;
 pub const LOAN_INFO_ACC_REPAYMENT_DATA_EI: usize =
     LOAN_INFO_ACC_LENDERS_DATA_END_INDEX + LOAN_INFO_REPAYMENT_DATA_BYTES;
+pub const LOAN_INFO_ACC_REPAYMENT_DATA_BYTES: usize = 16;
+// lenders region can store 9000/45 = 200 lenders and repayment region can store 800/16 = 50 emis
+pub const LOAN_INFO_MAX_LENDERS: usize =
+    LOAN_INFO_LENDERS_DATA_BYTES / LOAN_INFO_ACC_LENDER_DATA_BYTES;
+pub const LOAN_INFO_MAX_REPAYMENTS: usize =
+    LOAN_INFO_REPAYMENT_DATA_BYTES / LOAN_INFO_ACC_REPAYMENT_DATA_BYTES;
+pub const LOAN_INFO_ACC_HEADER_EXTENSION_SI: usize = LOAN_INFO_ACC_REPAYMENT_DATA_EI;
+pub const LOAN_INFO_ACC_HEADER_EXTENSION_EI: usize =
+    LOAN_INFO_ACC_HEADER_EXTENSION_SI + LOAN_INFO_HEADER_EXTENSION_DATA_BYTES;
+pub const LOAN_INFO_ACC_AMENDMENTS_DATA_SI: usize = LOAN_INFO_ACC_HEADER_EXTENSION_EI;
+pub const LOAN_INFO_ACC_AMENDMENTS_DATA_EI: usize =
+    LOAN_INFO_ACC_AMENDMENTS_DATA_SI + LOAN_INFO_AMENDMENTS_DATA_BYTES;
+pub const LOAN_INFO_ACC_AMENDMENT_DATA_BYTES: usize = 35;
+// amendments region can store 140/35 = 4 restructurings of a loan
+pub const LOAN_INFO_MAX_AMENDMENTS: usize =
+    LOAN_INFO_AMENDMENTS_DATA_BYTES / LOAN_INFO_ACC_AMENDMENT_DATA_BYTES;
+pub const LOAN_INFO_LAYOUT_VERSION_INDEX: usize = LOAN_INFO_ACC_AMENDMENTS_DATA_EI;
 
-// total bytes needed to store LoanInfoAccData = 116 + 9000 + 800 = 9916
+// total bytes needed to store LoanInfoAccData = 116 + 9000 + 800 + 83 + 140 + 1 = 10140
 
 // If we take size of Lenders_data_storage_acc to be 10Mb, then it can accomodate 153_846 lenders data as each lender data consumes 65 bytes
 // So if in future we have a lot of users (for ex 1.6 Million lenders) then we will generate 10 Lenders_data_storage_acc address each of 10Mb,
@@ -512,11 +1181,459 @@ pub fn pack_to_lender_account_data(
 
 
 pub const LENDERS_ACC_DATA_TYPE_INDEX: usize = 0;
+pub const LENDERS_ACC_DATA_STORAGE_NUMBER_INDEX: usize = 1;
 pub const LENDER_ACC_DATA_SIZE: usize = 65;
-pub const LENDERS_ACC_DATA_STARTING_INDEX: usize = 1;
+// byte 0 is acc type and byte 1 is lenders_data_storage_acc_number, lenders data starts after them
+pub const LENDERS_ACC_DATA_STARTING_INDEX: usize = 2;
+// lender_id can vary from 0 to 49_999 included
+pub const LENDERS_STORAGE_MAX_LENDERS: usize =
+    (LENDERS_STORAGE_ACC_LEGACY_DATA_SIZE - LENDERS_ACC_DATA_STARTING_INDEX) / LENDER_ACC_DATA_SIZE;
+// layout version byte comes after the last lender
+pub const LENDERS_STORAGE_LAYOUT_VERSION_INDEX: usize = LENDERS_STORAGE_ACC_LEGACY_DATA_SIZE;
 
 pub struct LendersAccountDataArray {
     pub acc_type: u8,
     pub lenders_data_storage_acc_number: u8,
     pub lenders_acc_array_data: [LenderAccountData],
 }
+
+// LoanInfoAccount is a view over loan info account data, every region is reached through it
+// so processors never compute offsets themselves, indexes are checked against region capacity
+pub struct LoanInfoAccount<'a> {
+    data: &'a mut [u8],
+}
+
+impl<'a> LoanInfoAccount<'a> {
+    pub fn new(data: &'a mut [u8]) -> Result<Self, ProgramError> {
+        if data.len() != LOAN_INFO_ACC_DATA_SIZE {
+            return Err(Funds4GoodError::DataSizeNotMatched.into());
+        }
+        let loan_info = LoanInfoAccount { data };
+        if loan_info.layout_version() > LOAN_INFO_LAYOUT_VERSION {
+            return Err(Funds4GoodError::UnsupportedLayoutVersion.into());
+        }
+        Ok(loan_info)
+    }
+
+    pub fn layout_version(&self) -> u8 {
+        self.data[LOAN_INFO_LAYOUT_VERSION_INDEX]
+    }
+
+    pub fn set_layout_version(&mut self, layout_version: u8) {
+        self.data[LOAN_INFO_LAYOUT_VERSION_INDEX] = layout_version;
+    }
+
+    // fills header extension of a legacy loan info account that MigrateAccount has just grown with zeroed bytes.
+    // Legacy loans needed full funding and zeroed total_amount_lended once borrower withdrew the collected funds
+    pub fn migrate_legacy_header(&mut self, loan_mint_pubkey: Pubkey) -> Result<(), ProgramError> {
+        let mut header = self.header()?;
+
+        header.loan_status = if header.total_loan_amount > 0
+            && header.repaid_amount_by_borrower >= header.total_loan_amount
+        {
+            LoanStatus::Repaid as u8
+        } else if header.total_amount_lended == 0 && header.next_index_to_store_lender_data > 0 {
+            LoanStatus::Active as u8
+        } else {
+            LoanStatus::Fundraising as u8
+        };
+        header.loan_mint_pubkey = loan_mint_pubkey;
+        header.min_funding_amount = header.total_loan_amount;
+        // legacy accounts didn't record disbursement time, interest accrues from the end of fundraising
+        if header.loan_status != LoanStatus::Fundraising as u8 {
+            header.disbursement_timestamp = header.fundraising_period_ending_timestamp;
+        }
+        self.set_header(header)?;
+        self.set_layout_version(LOAN_INFO_LAYOUT_VERSION);
+        Ok(())
+    }
+
+    // joins header stored at start of account with its extension stored after repayments region
+    fn header_bytes(&self) -> [u8; LOAN_INFO_HEADER_DATA_BYTES] {
+        let mut header_bytes = [0u8; LOAN_INFO_HEADER_DATA_BYTES];
+        header_bytes[..LOAN_INFO_HEADER_BASE_DATA_BYTES]
+            .copy_from_slice(&self.data[LOAN_INFO_HEADER_START_INDEX..LOAN_INFO_HEADER_END_INDEX]);
+        header_bytes[LOAN_INFO_HEADER_BASE_DATA_BYTES..].copy_from_slice(
+            &self.data[LOAN_INFO_ACC_HEADER_EXTENSION_SI..LOAN_INFO_ACC_HEADER_EXTENSION_EI],
+        );
+        header_bytes
+    }
+
+    pub fn header(&self) -> Result<LoanInfoAccDataHeader, ProgramError> {
+        unpack_to_loan_info_header(&self.header_bytes())
+    }
+
+    pub fn header_unchecked(&self) -> Result<LoanInfoAccDataHeader, ProgramError> {
+        unpack_unchecked_to_loan_info_header(&self.header_bytes())
+    }
+
+    pub fn set_header(&mut self, header: LoanInfoAccDataHeader) -> Result<(), ProgramError> {
+        let mut header_bytes = [0u8; LOAN_INFO_HEADER_DATA_BYTES];
+        pack_to_loan_info_header(header, &mut header_bytes)?;
+        self.data[LOAN_INFO_HEADER_START_INDEX..LOAN_INFO_HEADER_END_INDEX]
+            .copy_from_slice(&header_bytes[..LOAN_INFO_HEADER_BASE_DATA_BYTES]);
+        self.data[LOAN_INFO_ACC_HEADER_EXTENSION_SI..LOAN_INFO_ACC_HEADER_EXTENSION_EI]
+            .copy_from_slice(&header_bytes[LOAN_INFO_HEADER_BASE_DATA_BYTES..]);
+        Ok(())
+    }
+
+    fn lender_range(index: usize) -> Result<std::ops::Range<usize>, ProgramError> {
+        if index >= LOAN_INFO_MAX_LENDERS {
+            return Err(Funds4GoodError::LoanLendersCapacityExceeded.into());
+        }
+        let si = LOAN_INFO_ACC_LENDERS_DATA_START_INDEX + index * LOAN_INFO_ACC_LENDER_DATA_BYTES;
+        Ok(si..si + LOAN_INFO_ACC_LENDER_DATA_BYTES)
+    }
+
+    pub fn lender(&self, index: usize) -> Result<LoanInfoAccLendersData, ProgramError> {
+        unpack_to_loan_info_acc_lender_data(&self.data[Self::lender_range(index)?])
+    }
+
+    pub fn set_lender(
+        &mut self,
+        index: usize,
+        lender: LoanInfoAccLendersData,
+    ) -> Result<(), ProgramError> {
+        pack_to_loan_info_acc_lender_data(lender, &mut self.data[Self::lender_range(index)?])
+    }
+
+    // stores lender at next_index_to_store_lender_data and moves the index forward,
+    // caller still has to write the header back
+    pub fn push_lender(
+        &mut self,
+        header: &mut LoanInfoAccDataHeader,
+        lender: LoanInfoAccLendersData,
+    ) -> Result<(), ProgramError> {
+        self.set_lender(header.next_index_to_store_lender_data as usize, lender)?;
+        header.next_index_to_store_lender_data += 1;
+        Ok(())
+    }
+
+    fn repayment_range(index: usize) -> Result<std::ops::Range<usize>, ProgramError> {
+        if index >= LOAN_INFO_MAX_REPAYMENTS {
+            return Err(Funds4GoodError::LoanRepaymentsCapacityExceeded.into());
+        }
+        let si = LOAN_INFO_ACC_REPAYMENT_DATA_SI + index * LOAN_INFO_ACC_REPAYMENT_DATA_BYTES;
+        Ok(si..si + LOAN_INFO_ACC_REPAYMENT_DATA_BYTES)
+    }
+
+    pub fn repayment(&self, index: usize) -> Result<LoanInfoAccRepaymentData, ProgramError> {
+        unpack_to_loan_info_acc_repayment_data(&self.data[Self::repayment_range(index)?])
+    }
+
+    // stores repayment at next_index_to_store_repayment_info and moves the index forward,
+    // caller still has to write the header back
+    pub fn push_repayment(
+        &mut self,
+        header: &mut LoanInfoAccDataHeader,
+        repayment: LoanInfoAccRepaymentData,
+    ) -> Result<(), ProgramError> {
+        let range = Self::repayment_range(header.next_index_to_store_repayment_info as usize)?;
+        pack_to_loan_info_acc_repayment_data(repayment, &mut self.data[range])?;
+        header.next_index_to_store_repayment_info += 1;
+        Ok(())
+    }
+
+    fn amendment_range(index: usize) -> Result<std::ops::Range<usize>, ProgramError> {
+        if index >= LOAN_INFO_MAX_AMENDMENTS {
+            return Err(Funds4GoodError::LoanAmendmentsCapacityExceeded.into());
+        }
+        let si = LOAN_INFO_ACC_AMENDMENTS_DATA_SI + index * LOAN_INFO_ACC_AMENDMENT_DATA_BYTES;
+        Ok(si..si + LOAN_INFO_ACC_AMENDMENT_DATA_BYTES)
+    }
+
+    pub fn amendment(&self, index: usize) -> Result<LoanInfoAccAmendmentData, ProgramError> {
+        unpack_to_loan_info_acc_amendment_data(&self.data[Self::amendment_range(index)?])
+    }
+
+    pub fn set_amendment(
+        &mut self,
+        index: usize,
+        amendment: LoanInfoAccAmendmentData,
+    ) -> Result<(), ProgramError> {
+        pack_to_loan_info_acc_amendment_data(amendment, &mut self.data[Self::amendment_range(index)?])
+    }
+}
+
+// LendersStorageAccount is a view over lenders storage account data, lender_id is the slot index
+pub struct LendersStorageAccount<'a> {
+    data: &'a mut [u8],
+}
+
+impl<'a> LendersStorageAccount<'a> {
+    pub fn new(data: &'a mut [u8]) -> Result<Self, ProgramError> {
+        if data.len() != LENDERS_STORAGE_ACC_DATA_SIZE
+            && data.len() != LENDERS_STORAGE_ACC_LEGACY_DATA_SIZE
+        {
+            return Err(Funds4GoodError::DataSizeNotMatched.into());
+        }
+        if data.len() == LENDERS_STORAGE_ACC_DATA_SIZE
+            && data[LENDERS_STORAGE_LAYOUT_VERSION_INDEX] > LENDERS_STORAGE_LAYOUT_VERSION
+        {
+            return Err(Funds4GoodError::UnsupportedLayoutVersion.into());
+        }
+        if data[LENDERS_ACC_DATA_TYPE_INDEX] != AccTypes::LendersAcc as u8 {
+            return Err(Funds4GoodError::ExpectedAccountTypeMismatched.into());
+        }
+        // currently for prototype every lenders_data_storage_acc_number is 1
+        if data[LENDERS_ACC_DATA_STORAGE_NUMBER_INDEX] != 1u8 {
+            return Err(Funds4GoodError::ExpectedLendersAccNumNotMatched.into());
+        }
+        Ok(LendersStorageAccount { data })
+    }
+
+    fn lender_range(lender_id: u32) -> Result<std::ops::Range<usize>, ProgramError> {
+        if lender_id as usize >= LENDERS_STORAGE_MAX_LENDERS {
+            return Err(Funds4GoodError::InvalidLenderIdInput.into());
+        }
+        let si = LENDERS_ACC_DATA_STARTING_INDEX + lender_id as usize * LENDER_ACC_DATA_SIZE;
+        Ok(si..si + LENDER_ACC_DATA_SIZE)
+    }
+
+    pub fn lender(&self, lender_id: u32) -> Result<LenderAccountData, ProgramError> {
+        unpack_to_lender_account_data(&self.data[Self::lender_range(lender_id)?])
+    }
+
+    pub fn set_lender(
+        &mut self,
+        lender_id: u32,
+        lender: LenderAccountData,
+    ) -> Result<(), ProgramError> {
+        pack_to_lender_account_data(lender, &mut self.data[Self::lender_range(lender_id)?])
+    }
+
+    // sum of amount_to_withdraw_or_lend over every lender slot, amounts are read in place
+    // without unpacking whole lender data as the sum walks all slots
+    pub fn total_free_balance(&self) -> Result<u64, ProgramError> {
+        let lenders_data_end_index =
+            LENDERS_ACC_DATA_STARTING_INDEX + LENDERS_STORAGE_MAX_LENDERS * LENDER_ACC_DATA_SIZE;
+        let mut total_free_balance: u64 = 0;
+        for lender_data in self.data[LENDERS_ACC_DATA_STARTING_INDEX..lenders_data_end_index]
+            .chunks_exact(LENDER_ACC_DATA_SIZE)
+        {
+            let amount_to_withdraw_or_lend = u64::from_le_bytes(*array_ref![
+                lender_data,
+                LENDER_ACC_DATA_SIZE - AMOUNT_DATA_BYTES,
+                AMOUNT_DATA_BYTES
+            ]);
+            total_free_balance = total_free_balance
+                .checked_add(amount_to_withdraw_or_lend)
+                .ok_or(Funds4GoodError::AmountOverflow)?;
+        }
+        Ok(total_free_balance)
+    }
+}
+
+// Lender can keep a withdrawal history account derived with seed "Funds4GoodFinanceWithdrawals" from lender main account
+// It stores last 32 withdrawals as a ring buffer, so lenders can reconcile their statements
+// header takes 38 bytes and each LenderWithdrawalRecord takes 24 bytes, so total bytes needed = 38 + 24*32 = 806
+pub const LENDER_WITHDRAWAL_HISTORY_HEADER_DATA_BYTES: usize = 38;
+pub const LENDER_WITHDRAWAL_RECORD_DATA_BYTES: usize = 24;
+pub const LENDER_WITHDRAWAL_HISTORY_CAPACITY: usize = 32;
+pub const LENDER_WITHDRAWAL_HISTORY_ACC_DATA_SIZE: usize = LENDER_WITHDRAWAL_HISTORY_HEADER_DATA_BYTES
+    + LENDER_WITHDRAWAL_RECORD_DATA_BYTES * LENDER_WITHDRAWAL_HISTORY_CAPACITY;
+
+pub struct LenderWithdrawalHistoryHeader {
+    pub acc_type: u8,
+    pub lender_main_acc_pubkey: Pubkey,
+    pub next_index_to_store_withdrawal: u8,
+    pub total_withdrawals_count: u32,
+}
+
+pub fn unpack_to_lender_withdrawal_history_header(
+    input: &[u8],
+) -> Result<LenderWithdrawalHistoryHeader, ProgramError> {
+    if input.len() != LENDER_WITHDRAWAL_HISTORY_HEADER_DATA_BYTES {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let input = array_ref![input, 0, LENDER_WITHDRAWAL_HISTORY_HEADER_DATA_BYTES];
+    let (
+        acc_type_src,
+        lender_main_acc_pubkey_src,
+        next_index_to_store_withdrawal_src,
+        total_withdrawals_count_src,
+    ) = array_refs![input, 1, 32, 1, 4];
+
+    Ok(LenderWithdrawalHistoryHeader {
+        acc_type: acc_type_src[0],
+        lender_main_acc_pubkey: Pubkey::new_from_array(*lender_main_acc_pubkey_src),
+        next_index_to_store_withdrawal: next_index_to_store_withdrawal_src[0],
+        total_withdrawals_count: u32::from_le_bytes(*total_withdrawals_count_src),
+    })
+}
+
+pub fn pack_to_lender_withdrawal_history_header(
+    src: LenderWithdrawalHistoryHeader,
+    dst: &mut [u8],
+) -> Result<(), ProgramError> {
+    if dst.len() != LENDER_WITHDRAWAL_HISTORY_HEADER_DATA_BYTES {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let dst = array_mut_ref![dst, 0, LENDER_WITHDRAWAL_HISTORY_HEADER_DATA_BYTES];
+    let (
+        acc_type_dst,
+        lender_main_acc_pubkey_dst,
+        next_index_to_store_withdrawal_dst,
+        total_withdrawals_count_dst,
+    ) = mut_array_refs![dst, 1, 32, 1, 4];
+    let LenderWithdrawalHistoryHeader {
+        acc_type,
+        lender_main_acc_pubkey,
+        next_index_to_store_withdrawal,
+        total_withdrawals_count,
+    } = src;
+    acc_type_dst[0] = acc_type;
+    lender_main_acc_pubkey_dst.copy_from_slice(lender_main_acc_pubkey.as_ref());
+    next_index_to_store_withdrawal_dst[0] = next_index_to_store_withdrawal;
+    *total_withdrawals_count_dst = total_withdrawals_count.to_le_bytes();
+    Ok(())
+}
+
+// free balance left after withdrawal is stored with every record, so statement can be reconciled without replaying EMIs
+pub struct LenderWithdrawalRecord {
+    pub withdrawal_timestamp: u64,
+    pub withdrawn_amount: u64,
+    pub free_balance_after_withdrawal: u64,
+}
+
+pub fn pack_to_lender_withdrawal_record(
+    src: LenderWithdrawalRecord,
+    dst: &mut [u8],
+) -> Result<(), ProgramError> {
+    if dst.len() != LENDER_WITHDRAWAL_RECORD_DATA_BYTES {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let dst = array_mut_ref![dst, 0, LENDER_WITHDRAWAL_RECORD_DATA_BYTES];
+    let (withdrawal_timestamp_dst, withdrawn_amount_dst, free_balance_after_withdrawal_dst) =
+        mut_array_refs![dst, 8, 8, 8];
+    let LenderWithdrawalRecord {
+        withdrawal_timestamp,
+        withdrawn_amount,
+        free_balance_after_withdrawal,
+    } = src;
+    *withdrawal_timestamp_dst = withdrawal_timestamp.to_le_bytes();
+    *withdrawn_amount_dst = withdrawn_amount.to_le_bytes();
+    *free_balance_after_withdrawal_dst = free_balance_after_withdrawal.to_le_bytes();
+    Ok(())
+}
+
+pub fn unpack_to_lender_withdrawal_record(
+    input: &[u8],
+) -> Result<LenderWithdrawalRecord, ProgramError> {
+    if input.len() != LENDER_WITHDRAWAL_RECORD_DATA_BYTES {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let input = array_ref![input, 0, LENDER_WITHDRAWAL_RECORD_DATA_BYTES];
+    let (withdrawal_timestamp_src, withdrawn_amount_src, free_balance_after_withdrawal_src) =
+        array_refs![input, 8, 8, 8];
+
+    Ok(LenderWithdrawalRecord {
+        withdrawal_timestamp: u64::from_le_bytes(*withdrawal_timestamp_src),
+        withdrawn_amount: u64::from_le_bytes(*withdrawn_amount_src),
+        free_balance_after_withdrawal: u64::from_le_bytes(*free_balance_after_withdrawal_src),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn legacy_header(
+        total_amount_lended: u64,
+        repaid_amount_by_borrower: u64,
+    ) -> LoanInfoAccDataHeader {
+        LoanInfoAccDataHeader {
+            acc_type: AccTypes::LoanInfoAcc as u8,
+            borrower_main_acc_pubkey: Pubkey::new_unique(),
+            guarantor_main_acc_pubkey: Pubkey::new_unique(),
+            loan_approval_timestamp: 1_000,
+            fundraising_period_ending_timestamp: 2_000,
+            first_repayment_last_date_timestamp: 3_000,
+            total_loan_amount: 200,
+            total_amount_lended,
+            repaid_amount_by_borrower,
+            next_index_to_store_lender_data: 1,
+            next_index_to_store_repayment_info: 0,
+            number_of_emis_needed_to_repay_the_loan: 2,
+            loan_mint_pubkey: Pubkey::default(),
+            loan_status: 0,
+            penalty_paid_amount: 0,
+            schedule_version: 0,
+            schedule_repaid_amount_base: 0,
+            guarantor_claim_amount: 0,
+            guarantor_paid_amount: 0,
+            min_funding_amount: 0,
+            is_reserve_covered: 0,
+            disbursement_timestamp: 0,
+        }
+    }
+
+    // legacy account grown by MigrateAccount, only the base header bytes are set
+    fn grown_legacy_data(header: LoanInfoAccDataHeader) -> Vec<u8> {
+        let mut header_bytes = [0u8; LOAN_INFO_HEADER_DATA_BYTES];
+        pack_to_loan_info_header(header, &mut header_bytes).unwrap();
+        let mut data = vec![0u8; LOAN_INFO_ACC_DATA_SIZE];
+        data[..LOAN_INFO_HEADER_BASE_DATA_BYTES]
+            .copy_from_slice(&header_bytes[..LOAN_INFO_HEADER_BASE_DATA_BYTES]);
+        data
+    }
+
+    fn migrated_header(header: LoanInfoAccDataHeader, loan_mint: Pubkey) -> LoanInfoAccDataHeader {
+        let mut data = grown_legacy_data(header);
+        let mut loan_info = LoanInfoAccount::new(&mut data).unwrap();
+        loan_info.migrate_legacy_header(loan_mint).unwrap();
+        assert_eq!(loan_info.layout_version(), LOAN_INFO_LAYOUT_VERSION);
+        loan_info.header().unwrap()
+    }
+
+    #[test]
+    fn test_loan_info_header_round_trip() {
+        let loan_mint = Pubkey::new_unique();
+        let mut header = legacy_header(150, 50);
+        header.loan_mint_pubkey = loan_mint;
+        header.loan_status = LoanStatus::Active as u8;
+        header.is_reserve_covered = 1;
+        header.disbursement_timestamp = 2_500;
+
+        let mut data = vec![0u8; LOAN_INFO_ACC_DATA_SIZE];
+        let mut loan_info = LoanInfoAccount::new(&mut data).unwrap();
+        loan_info.set_header(header).unwrap();
+        let unpacked = loan_info.header().unwrap();
+
+        assert_eq!(unpacked.loan_mint_pubkey, loan_mint);
+        assert_eq!(unpacked.total_amount_lended, 150);
+        assert_eq!(unpacked.repaid_amount_by_borrower, 50);
+        assert_eq!(unpacked.loan_status, LoanStatus::Active as u8);
+        assert_eq!(unpacked.is_reserve_covered, 1);
+        assert_eq!(unpacked.disbursement_timestamp, 2_500);
+    }
+
+    #[test]
+    fn test_loan_info_account_requires_migration() {
+        let mut data = vec![0u8; LOAN_INFO_ACC_LEGACY_DATA_SIZE];
+        assert_eq!(
+            LoanInfoAccount::new(&mut data).err(),
+            Some(Funds4GoodError::DataSizeNotMatched.into())
+        );
+    }
+
+    #[test]
+    fn test_migrate_legacy_header_derives_loan_status() {
+        let loan_mint = Pubkey::new_unique();
+
+        let fundraising = migrated_header(legacy_header(150, 0), loan_mint);
+        assert_eq!(fundraising.loan_status, LoanStatus::Fundraising as u8);
+        assert_eq!(fundraising.disbursement_timestamp, 0);
+        assert_eq!(fundraising.loan_mint_pubkey, loan_mint);
+        assert_eq!(fundraising.min_funding_amount, 200);
+
+        let active = migrated_header(legacy_header(0, 100), loan_mint);
+        assert_eq!(active.loan_status, LoanStatus::Active as u8);
+        assert_eq!(active.disbursement_timestamp, 2_000);
+
+        let repaid = migrated_header(legacy_header(0, 200), loan_mint);
+        assert_eq!(repaid.loan_status, LoanStatus::Repaid as u8);
+    }
+}
@@ -0,0 +1,2795 @@
+#![cfg(feature = "test-bpf")]
+
+use assert_matches::assert_matches;
+use funds4good_solana::{
+    audit,
+    error::Funds4GoodError,
+    event::{self, Funds4GoodEvent},
+    instruction,
+    processor::Processor,
+    state::{
+        self, AccTypes, BorrowerAccount, LoanInfoAccDataHeader, LoanInfoAccount, LoanStatus,
+        MintVaultRegistryAccount, ReservePoolAccount,
+    },
+};
+use solana_program::{
+    bpf_loader_upgradeable::{self, UpgradeableLoaderState},
+    instruction::{Instruction, InstructionError},
+    program_pack::Pack,
+    pubkey::Pubkey,
+    rent::Rent,
+    system_instruction,
+    sysvar::clock::Clock,
+};
+use solana_program_test::{processor, ProgramTest, ProgramTestBanksClientExt, ProgramTestContext};
+use solana_sdk::{
+    account::{Account, AccountSharedData},
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use spl_token::state::{Account as TokenAccount, Mint};
+
+// test mint uses 9 decimals like the Funds4Good coin, so one coin is 10^9 base units
+const MINT_DECIMALS: u8 = 9;
+const COIN: u64 = 1_000_000_000;
+const NUM_SECONDS_IN_ONE_DAY: i64 = 86400;
+const PLATFORM_FEE_BPS: u16 = 100;
+const RESERVE_FEE_BPS: u16 = 100;
+const INITIAL_CREDIT_SCORE: u64 = 500_000_000_000;
+
+fn program_test(program_id: Pubkey) -> ProgramTest {
+    ProgramTest::new(
+        "funds4good_solana",
+        program_id,
+        processor!(Processor::process),
+    )
+}
+
+async fn process_instructions(
+    context: &mut ProgramTestContext,
+    instructions: &[Instruction],
+    signers: &[&Keypair],
+) -> Result<(), TransactionError> {
+    // a fresh blockhash keeps repeated identical instructions from being deduplicated
+    let recent_blockhash = context
+        .banks_client
+        .get_new_latest_blockhash(&context.last_blockhash)
+        .await
+        .unwrap();
+    context.last_blockhash = recent_blockhash;
+
+    let mut all_signers = vec![&context.payer];
+    all_signers.extend_from_slice(signers);
+    let transaction = Transaction::new_signed_with_payer(
+        instructions,
+        Some(&context.payer.pubkey()),
+        &all_signers,
+        recent_blockhash,
+    );
+
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .map_err(|err| err.unwrap())
+}
+
+fn assert_funds4good_error(result: Result<(), TransactionError>, expected: Funds4GoodError) {
+    assert_matches!(
+        result,
+        Err(TransactionError::InstructionError(_, InstructionError::Custom(code))) if code == expected as u32
+    );
+}
+
+// program-test runs the program natively without deploying it, so its program data account is set up
+// the way upgradeable loader leaves it after authority deploys the program
+fn set_upgrade_authority(
+    context: &mut ProgramTestContext,
+    program_id: &Pubkey,
+    authority: &Pubkey,
+) {
+    let (program_data, _) =
+        Pubkey::find_program_address(&[program_id.as_ref()], &bpf_loader_upgradeable::id());
+    let program_data_account = Account::new_data(
+        1_000_000_000,
+        &UpgradeableLoaderState::ProgramData {
+            slot: 0,
+            upgrade_authority_address: Some(*authority),
+        },
+        &bpf_loader_upgradeable::id(),
+    )
+    .unwrap();
+    context.set_account(
+        &program_data,
+        &AccountSharedData::from(program_data_account),
+    );
+}
+
+async fn fund_account(context: &mut ProgramTestContext, pubkey: &Pubkey, lamports: u64) {
+    let payer_pubkey = context.payer.pubkey();
+    process_instructions(
+        context,
+        &[system_instruction::transfer(
+            &payer_pubkey,
+            pubkey,
+            lamports,
+        )],
+        &[],
+    )
+    .await
+    .unwrap();
+}
+
+async fn create_mint(context: &mut ProgramTestContext, authority: &Pubkey) -> Pubkey {
+    let mint = Keypair::new();
+    let payer_pubkey = context.payer.pubkey();
+    let rent = context.banks_client.get_rent().await.unwrap();
+
+    process_instructions(
+        context,
+        &[
+            system_instruction::create_account(
+                &payer_pubkey,
+                &mint.pubkey(),
+                rent.minimum_balance(Mint::LEN),
+                Mint::LEN as u64,
+                &spl_token::id(),
+            ),
+            spl_token::instruction::initialize_mint(
+                &spl_token::id(),
+                &mint.pubkey(),
+                authority,
+                None,
+                MINT_DECIMALS,
+            )
+            .unwrap(),
+        ],
+        &[&mint],
+    )
+    .await
+    .unwrap();
+
+    mint.pubkey()
+}
+
+async fn create_token_account(
+    context: &mut ProgramTestContext,
+    mint: &Pubkey,
+    owner: &Pubkey,
+) -> Pubkey {
+    let token_account = Keypair::new();
+    let payer_pubkey = context.payer.pubkey();
+    let rent = context.banks_client.get_rent().await.unwrap();
+
+    process_instructions(
+        context,
+        &[
+            system_instruction::create_account(
+                &payer_pubkey,
+                &token_account.pubkey(),
+                rent.minimum_balance(TokenAccount::LEN),
+                TokenAccount::LEN as u64,
+                &spl_token::id(),
+            ),
+            spl_token::instruction::initialize_account(
+                &spl_token::id(),
+                &token_account.pubkey(),
+                mint,
+                owner,
+            )
+            .unwrap(),
+        ],
+        &[&token_account],
+    )
+    .await
+    .unwrap();
+
+    token_account.pubkey()
+}
+
+async fn mint_to(
+    context: &mut ProgramTestContext,
+    mint: &Pubkey,
+    token_account: &Pubkey,
+    mint_authority: &Keypair,
+    amount: u64,
+) {
+    process_instructions(
+        context,
+        &[spl_token::instruction::mint_to(
+            &spl_token::id(),
+            mint,
+            token_account,
+            &mint_authority.pubkey(),
+            &[],
+            amount,
+        )
+        .unwrap()],
+        &[mint_authority],
+    )
+    .await
+    .unwrap();
+}
+
+fn create_program_account_ix(
+    payer_pubkey: &Pubkey,
+    rent: &Rent,
+    program_id: &Pubkey,
+    account_pubkey: &Pubkey,
+    data_len: usize,
+) -> Instruction {
+    system_instruction::create_account(
+        payer_pubkey,
+        account_pubkey,
+        rent.minimum_balance(data_len),
+        data_len as u64,
+        program_id,
+    )
+}
+
+fn create_program_account_with_seed_ix(
+    payer_pubkey: &Pubkey,
+    rent: &Rent,
+    program_id: &Pubkey,
+    base_pubkey: &Pubkey,
+    seed: &str,
+    data_len: usize,
+) -> Instruction {
+    system_instruction::create_account_with_seed(
+        payer_pubkey,
+        &Pubkey::create_with_seed(base_pubkey, seed, program_id).unwrap(),
+        base_pubkey,
+        seed,
+        rent.minimum_balance(data_len),
+        data_len as u64,
+        program_id,
+    )
+}
+
+async fn get_token_balance(context: &mut ProgramTestContext, token_account: &Pubkey) -> u64 {
+    let account = context
+        .banks_client
+        .get_account(*token_account)
+        .await
+        .unwrap()
+        .unwrap();
+    TokenAccount::unpack(&account.data).unwrap().amount
+}
+
+async fn get_loan_info_header(
+    context: &mut ProgramTestContext,
+    loan_info: &Pubkey,
+) -> LoanInfoAccDataHeader {
+    let mut account = context
+        .banks_client
+        .get_account(*loan_info)
+        .await
+        .unwrap()
+        .unwrap();
+    LoanInfoAccount::new(&mut account.data)
+        .unwrap()
+        .header()
+        .unwrap()
+}
+
+async fn get_borrower_account(
+    context: &mut ProgramTestContext,
+    borrower_storage: &Pubkey,
+) -> BorrowerAccount {
+    let account = context
+        .banks_client
+        .get_account(*borrower_storage)
+        .await
+        .unwrap()
+        .unwrap();
+    BorrowerAccount::unpack_versioned(&account.data).unwrap()
+}
+
+async fn get_lender_account(
+    context: &mut ProgramTestContext,
+    lenders_storage: &Pubkey,
+    lender_id: u32,
+) -> state::LenderAccountData {
+    let mut account = context
+        .banks_client
+        .get_account(*lenders_storage)
+        .await
+        .unwrap()
+        .unwrap();
+    state::LendersStorageAccount::new(&mut account.data)
+        .unwrap()
+        .lender(lender_id)
+        .unwrap()
+}
+
+async fn get_lender_free_balance(
+    context: &mut ProgramTestContext,
+    lenders_storage: &Pubkey,
+    lender_id: u32,
+) -> u64 {
+    get_lender_account(context, lenders_storage, lender_id)
+        .await
+        .amount_to_withdraw_or_lend
+}
+
+// moves the clock forward, program reads unix_timestamp for fundraising and emi deadlines
+async fn advance_clock(context: &mut ProgramTestContext, num_days: i64) {
+    let mut clock = context.banks_client.get_sysvar::<Clock>().await.unwrap();
+    clock.unix_timestamp += num_days * NUM_SECONDS_IN_ONE_DAY;
+    context.set_sysvar(&clock);
+}
+
+// program accounts of one mint, set up the way an admin deploys the platform:
+// lenders storage, vault ownership transfer (creates config and registry), treasury, platform fees and reserve pool
+struct Funds4GoodEnv {
+    program_id: Pubkey,
+    admin: Keypair,
+    mint: Pubkey,
+    vault: Pubkey,
+    vault_authority: Pubkey,
+    config: Pubkey,
+    mint_vault_registry: Pubkey,
+    lenders_storage: Pubkey,
+    treasury: Pubkey,
+    reserve_pool: Pubkey,
+    reserve_vault: Pubkey,
+}
+
+async fn setup_funds4good(context: &mut ProgramTestContext, program_id: Pubkey) -> Funds4GoodEnv {
+    let env = setup_funds4good_without_reserve_pool(context, program_id).await;
+    configure_reserve_pool(context, &env).await;
+    env
+}
+
+// reserve pool is optional, its address and reserve vault are known but the pool isn't configured yet
+async fn setup_funds4good_without_reserve_pool(
+    context: &mut ProgramTestContext,
+    program_id: Pubkey,
+) -> Funds4GoodEnv {
+    let admin = Keypair::new();
+    fund_account(context, &admin.pubkey(), 10_000_000_000).await;
+    set_upgrade_authority(context, &program_id, &admin.pubkey());
+
+    let mint = create_mint(context, &admin.pubkey()).await;
+
+    let payer_pubkey = context.payer.pubkey();
+    let rent = context.banks_client.get_rent().await.unwrap();
+    let lenders_storage = Keypair::new();
+    process_instructions(
+        context,
+        &[
+            create_program_account_ix(
+                &payer_pubkey,
+                &rent,
+                &program_id,
+                &lenders_storage.pubkey(),
+                state::LENDERS_STORAGE_ACC_DATA_SIZE,
+            ),
+            instruction::initialize_lenders_storage_account(
+                &program_id,
+                &admin.pubkey(),
+                &lenders_storage.pubkey(),
+            ),
+        ],
+        &[&lenders_storage, &admin],
+    )
+    .await
+    .unwrap();
+
+    let (vault_authority, _) = Pubkey::find_program_address(&[b"Funds4GoodFinance"], &program_id);
+    let (config, _) = Pubkey::find_program_address(&[b"Funds4GoodFinanceConfig"], &program_id);
+    let (mint_vault_registry, _) =
+        Pubkey::find_program_address(&[b"Funds4GoodFinanceVault", mint.as_ref()], &program_id);
+    let (reserve_pool, _) =
+        Pubkey::find_program_address(&[b"Funds4GoodFinanceReserve", mint.as_ref()], &program_id);
+
+    let vault = create_token_account(context, &mint, &admin.pubkey()).await;
+    process_instructions(
+        context,
+        &[instruction::transfer_Funds4Good_vault_account_ownership(
+            &program_id,
+            &admin.pubkey(),
+            &vault,
+            &config,
+            &mint,
+            &mint_vault_registry,
+            &lenders_storage.pubkey(),
+        )],
+        &[&admin],
+    )
+    .await
+    .unwrap();
+
+    let treasury = create_token_account(context, &mint, &admin.pubkey()).await;
+    process_instructions(
+        context,
+        &[
+            instruction::transfer_treasury_account_ownership(
+                &program_id,
+                &admin.pubkey(),
+                &treasury,
+                &config,
+                &mint_vault_registry,
+            ),
+            instruction::set_platform_fees(
+                &program_id,
+                &admin.pubkey(),
+                &config,
+                &mint_vault_registry,
+                PLATFORM_FEE_BPS,
+                PLATFORM_FEE_BPS,
+            ),
+        ],
+        &[&admin],
+    )
+    .await
+    .unwrap();
+
+    let reserve_vault = create_token_account(context, &mint, &admin.pubkey()).await;
+
+    Funds4GoodEnv {
+        program_id,
+        admin,
+        mint,
+        vault,
+        vault_authority,
+        config,
+        mint_vault_registry,
+        lenders_storage: lenders_storage.pubkey(),
+        treasury,
+        reserve_pool,
+        reserve_vault,
+    }
+}
+
+async fn configure_reserve_pool(context: &mut ProgramTestContext, env: &Funds4GoodEnv) {
+    process_instructions(
+        context,
+        &[instruction::configure_reserve_pool(
+            &env.program_id,
+            &env.admin.pubkey(),
+            &env.config,
+            &env.mint_vault_registry,
+            &env.reserve_pool,
+            &env.reserve_vault,
+            RESERVE_FEE_BPS,
+        )],
+        &[&env.admin],
+    )
+    .await
+    .unwrap();
+}
+
+struct TestUser {
+    keypair: Keypair,
+    token_account: Pubkey,
+}
+
+async fn create_user(
+    context: &mut ProgramTestContext,
+    env: &Funds4GoodEnv,
+    coins: u64,
+) -> TestUser {
+    let keypair = Keypair::new();
+    let token_account = create_token_account(context, &env.mint, &keypair.pubkey()).await;
+    if coins > 0 {
+        mint_to(context, &env.mint, &token_account, &env.admin, coins * COIN).await;
+    }
+    TestUser {
+        keypair,
+        token_account,
+    }
+}
+
+struct TestLoan {
+    borrower: TestUser,
+    guarantor: TestUser,
+    borrower_storage: Pubkey,
+    loan_info: Pubkey,
+}
+
+async fn create_borrower_and_guarantor(
+    context: &mut ProgramTestContext,
+    env: &Funds4GoodEnv,
+) -> (TestUser, TestUser, Pubkey) {
+    let borrower = create_user(context, env, 10).await;
+    let guarantor = create_user(context, env, 200).await;
+
+    let payer_pubkey = context.payer.pubkey();
+    let rent = context.banks_client.get_rent().await.unwrap();
+    let borrower_storage = Keypair::new();
+    let guarantor_storage = Keypair::new();
+    process_instructions(
+        context,
+        &[
+            create_program_account_ix(
+                &payer_pubkey,
+                &rent,
+                &env.program_id,
+                &borrower_storage.pubkey(),
+                BorrowerAccount::LEN,
+            ),
+            instruction::initialize_borrower_account(
+                &env.program_id,
+                &borrower.keypair.pubkey(),
+                &borrower_storage.pubkey(),
+            ),
+            create_program_account_ix(
+                &payer_pubkey,
+                &rent,
+                &env.program_id,
+                &guarantor_storage.pubkey(),
+                state::GuarantorAccount::LEN,
+            ),
+            instruction::initialize_guarantor_account(
+                &env.program_id,
+                &guarantor.keypair.pubkey(),
+                &guarantor_storage.pubkey(),
+            ),
+        ],
+        &[
+            &borrower_storage,
+            &borrower.keypair,
+            &guarantor_storage,
+            &guarantor.keypair,
+        ],
+    )
+    .await
+    .unwrap();
+
+    (borrower, guarantor, borrower_storage.pubkey())
+}
+
+#[allow(clippy::too_many_arguments)]
+fn initialize_loan_info_account_ix(
+    env: &Funds4GoodEnv,
+    borrower: &TestUser,
+    guarantor: &TestUser,
+    borrower_storage: &Pubkey,
+    loan_info: &Pubkey,
+    num_emis: u16,
+    num_days_for_fundraising: u16,
+    total_loan_coins: u64,
+) -> Instruction {
+    instruction::initialize_loan_info_account(
+        &env.program_id,
+        &guarantor.keypair.pubkey(),
+        &borrower.keypair.pubkey(),
+        &env.mint_vault_registry,
+        loan_info,
+        borrower_storage,
+        30,
+        num_emis,
+        num_days_for_fundraising,
+        total_loan_coins * COIN,
+        0,
+    )
+}
+
+async fn create_loan(
+    context: &mut ProgramTestContext,
+    env: &Funds4GoodEnv,
+    num_emis: u16,
+    total_loan_coins: u64,
+) -> TestLoan {
+    let (borrower, guarantor, borrower_storage) = create_borrower_and_guarantor(context, env).await;
+
+    let payer_pubkey = context.payer.pubkey();
+    let rent = context.banks_client.get_rent().await.unwrap();
+    let loan_info = Keypair::new();
+    process_instructions(
+        context,
+        &[
+            create_program_account_ix(
+                &payer_pubkey,
+                &rent,
+                &env.program_id,
+                &loan_info.pubkey(),
+                state::LOAN_INFO_ACC_DATA_SIZE,
+            ),
+            initialize_loan_info_account_ix(
+                env,
+                &borrower,
+                &guarantor,
+                &borrower_storage,
+                &loan_info.pubkey(),
+                num_emis,
+                10,
+                total_loan_coins,
+            ),
+        ],
+        &[&loan_info, &guarantor.keypair],
+    )
+    .await
+    .unwrap();
+
+    TestLoan {
+        borrower,
+        guarantor,
+        borrower_storage,
+        loan_info: loan_info.pubkey(),
+    }
+}
+
+async fn lend(
+    context: &mut ProgramTestContext,
+    env: &Funds4GoodEnv,
+    loan: &TestLoan,
+    lender: &TestUser,
+    lender_id: u32,
+    amount: u64,
+) -> Result<(), TransactionError> {
+    process_instructions(
+        context,
+        &[instruction::lend_to_borrower(
+            &env.program_id,
+            &lender.keypair.pubkey(),
+            &lender.token_account,
+            &env.vault,
+            &env.mint_vault_registry,
+            &loan.loan_info,
+            &env.lenders_storage,
+            amount,
+            lender_id,
+        )],
+        &[&lender.keypair],
+    )
+    .await
+}
+
+async fn withdraw_collected_loan_funds(
+    context: &mut ProgramTestContext,
+    env: &Funds4GoodEnv,
+    loan: &TestLoan,
+    borrower: &TestUser,
+) -> Result<(), TransactionError> {
+    process_instructions(
+        context,
+        &[instruction::withdraw_collected_loan_funds(
+            &env.program_id,
+            &borrower.keypair.pubkey(),
+            &borrower.token_account,
+            &env.vault,
+            &env.mint_vault_registry,
+            &loan.loan_info,
+            &env.vault_authority,
+            &env.reserve_pool,
+            &env.treasury,
+        )],
+        &[&borrower.keypair],
+    )
+    .await
+}
+
+async fn pay_emi(
+    context: &mut ProgramTestContext,
+    env: &Funds4GoodEnv,
+    loan: &TestLoan,
+    amount: u64,
+) -> Result<(), TransactionError> {
+    process_instructions(
+        context,
+        &[instruction::pay_emi_for_loan(
+            &env.program_id,
+            &loan.borrower.keypair.pubkey(),
+            &loan.borrower.token_account,
+            &env.vault,
+            &env.mint_vault_registry,
+            &loan.borrower_storage,
+            &env.reserve_pool,
+            &env.reserve_vault,
+            &loan.loan_info,
+            &env.lenders_storage,
+            &env.treasury,
+            amount,
+        )],
+        &[&loan.borrower.keypair],
+    )
+    .await
+}
+
+async fn guarantor_pay_overdue_emi(
+    context: &mut ProgramTestContext,
+    env: &Funds4GoodEnv,
+    loan: &TestLoan,
+    amount: u64,
+) -> Result<(), TransactionError> {
+    process_instructions(
+        context,
+        &[instruction::guarantor_pay_overdue_emi(
+            &env.program_id,
+            &loan.guarantor.keypair.pubkey(),
+            &loan.guarantor.token_account,
+            &env.vault,
+            &env.mint_vault_registry,
+            &loan.borrower_storage,
+            &env.reserve_pool,
+            &env.reserve_vault,
+            &loan.loan_info,
+            &env.lenders_storage,
+            &env.treasury,
+            amount,
+        )],
+        &[&loan.guarantor.keypair],
+    )
+    .await
+}
+
+async fn pay_off_loan(
+    context: &mut ProgramTestContext,
+    env: &Funds4GoodEnv,
+    loan: &TestLoan,
+    max_amount: u64,
+) -> Result<(), TransactionError> {
+    process_instructions(
+        context,
+        &[instruction::pay_off_loan(
+            &env.program_id,
+            &loan.borrower.keypair.pubkey(),
+            &loan.borrower.token_account,
+            &env.vault,
+            &env.mint_vault_registry,
+            &loan.borrower_storage,
+            &env.reserve_pool,
+            &env.reserve_vault,
+            &loan.loan_info,
+            &env.lenders_storage,
+            &env.treasury,
+            max_amount,
+        )],
+        &[&loan.borrower.keypair],
+    )
+    .await
+}
+
+async fn cancel_loan(
+    context: &mut ProgramTestContext,
+    env: &Funds4GoodEnv,
+    loan: &TestLoan,
+    signer: &Keypair,
+) -> Result<(), TransactionError> {
+    process_instructions(
+        context,
+        &[instruction::cancel_loan(
+            &env.program_id,
+            &signer.pubkey(),
+            &loan.loan_info,
+            &env.mint_vault_registry,
+            &env.lenders_storage,
+        )],
+        &[signer],
+    )
+    .await
+}
+
+async fn pay_emi_on_behalf(
+    context: &mut ProgramTestContext,
+    env: &Funds4GoodEnv,
+    loan: &TestLoan,
+    payer: &TestUser,
+    amount: u64,
+) -> Result<(), TransactionError> {
+    process_instructions(
+        context,
+        &[instruction::pay_emi_on_behalf(
+            &env.program_id,
+            &payer.keypair.pubkey(),
+            &payer.token_account,
+            &env.vault,
+            &env.mint_vault_registry,
+            &loan.borrower_storage,
+            &env.reserve_pool,
+            &env.reserve_vault,
+            &loan.loan_info,
+            &env.lenders_storage,
+            &env.treasury,
+            amount,
+        )],
+        &[&payer.keypair],
+    )
+    .await
+}
+
+async fn repay_guarantor(
+    context: &mut ProgramTestContext,
+    env: &Funds4GoodEnv,
+    loan: &TestLoan,
+    amount: u64,
+) -> Result<(), TransactionError> {
+    process_instructions(
+        context,
+        &[instruction::repay_guarantor(
+            &env.program_id,
+            &loan.borrower.keypair.pubkey(),
+            &loan.borrower.token_account,
+            &loan.guarantor.token_account,
+            &env.mint_vault_registry,
+            &loan.loan_info,
+            amount,
+        )],
+        &[&loan.borrower.keypair],
+    )
+    .await
+}
+
+async fn restructure_loan(
+    context: &mut ProgramTestContext,
+    env: &Funds4GoodEnv,
+    loan: &TestLoan,
+    guarantor: &Keypair,
+    new_number_of_emis: u16,
+    num_days_left_for_next_repayment: u16,
+) -> Result<(), TransactionError> {
+    process_instructions(
+        context,
+        &[instruction::restructure_loan(
+            &env.program_id,
+            &guarantor.pubkey(),
+            &loan.borrower.keypair.pubkey(),
+            &loan.loan_info,
+            &loan.borrower_storage,
+            new_number_of_emis,
+            num_days_left_for_next_repayment,
+        )],
+        &[guarantor, &loan.borrower.keypair],
+    )
+    .await
+}
+
+async fn withdraw_treasury(
+    context: &mut ProgramTestContext,
+    env: &Funds4GoodEnv,
+    admin: &Keypair,
+    admin_token_account: &Pubkey,
+    amount: u64,
+) -> Result<(), TransactionError> {
+    let (treasury_authority, _) =
+        Pubkey::find_program_address(&[b"Funds4GoodFinanceTreasury"], &env.program_id);
+    process_instructions(
+        context,
+        &[instruction::withdraw_treasury(
+            &env.program_id,
+            &admin.pubkey(),
+            &env.treasury,
+            admin_token_account,
+            &env.config,
+            &env.mint_vault_registry,
+            &treasury_authority,
+            amount,
+        )],
+        &[admin],
+    )
+    .await
+}
+
+async fn set_prepayment_discount(
+    context: &mut ProgramTestContext,
+    env: &Funds4GoodEnv,
+    admin: &Keypair,
+    prepayment_discount_bps: u16,
+) -> Result<(), TransactionError> {
+    process_instructions(
+        context,
+        &[instruction::set_prepayment_discount(
+            &env.program_id,
+            &admin.pubkey(),
+            &env.config,
+            &env.mint_vault_registry,
+            prepayment_discount_bps,
+        )],
+        &[admin],
+    )
+    .await
+}
+
+async fn set_interest_rate(
+    context: &mut ProgramTestContext,
+    env: &Funds4GoodEnv,
+    admin: &Keypair,
+    interest_rate_bps: u16,
+) -> Result<(), TransactionError> {
+    process_instructions(
+        context,
+        &[instruction::set_interest_rate(
+            &env.program_id,
+            &admin.pubkey(),
+            &env.config,
+            &env.mint_vault_registry,
+            interest_rate_bps,
+        )],
+        &[admin],
+    )
+    .await
+}
+
+async fn mark_loan_defaulted(
+    context: &mut ProgramTestContext,
+    env: &Funds4GoodEnv,
+    loan: &TestLoan,
+) -> Result<(), TransactionError> {
+    process_instructions(
+        context,
+        &[instruction::mark_loan_defaulted(
+            &env.program_id,
+            &env.admin.pubkey(),
+            &loan.loan_info,
+            &env.vault,
+            &env.mint_vault_registry,
+            &env.reserve_pool,
+            &env.reserve_vault,
+            &env.lenders_storage,
+        )],
+        &[&env.admin],
+    )
+    .await
+}
+
+async fn get_reserve_pool(
+    context: &mut ProgramTestContext,
+    reserve_pool: &Pubkey,
+) -> ReservePoolAccount {
+    let data = get_account_data(context, reserve_pool).await;
+    ReservePoolAccount::unpack(&data).unwrap()
+}
+
+fn lender_withdrawal_history_pubkey(env: &Funds4GoodEnv, lender: &TestUser) -> Pubkey {
+    Pubkey::create_with_seed(
+        &lender.keypair.pubkey(),
+        "Funds4GoodFinanceWithdrawals",
+        &env.program_id,
+    )
+    .unwrap()
+}
+
+// withdrawals are recorded in the history account from the next one on
+async fn create_lender_withdrawal_history(
+    context: &mut ProgramTestContext,
+    env: &Funds4GoodEnv,
+    lender: &TestUser,
+) -> Pubkey {
+    let payer_pubkey = context.payer.pubkey();
+    let rent = context.banks_client.get_rent().await.unwrap();
+    process_instructions(
+        context,
+        &[create_program_account_with_seed_ix(
+            &payer_pubkey,
+            &rent,
+            &env.program_id,
+            &lender.keypair.pubkey(),
+            "Funds4GoodFinanceWithdrawals",
+            state::LENDER_WITHDRAWAL_HISTORY_ACC_DATA_SIZE,
+        )],
+        &[&lender.keypair],
+    )
+    .await
+    .unwrap();
+    lender_withdrawal_history_pubkey(env, lender)
+}
+
+async fn withdraw_lender_free_funds(
+    context: &mut ProgramTestContext,
+    env: &Funds4GoodEnv,
+    lender: &TestUser,
+    lender_id: u32,
+    amount: u64,
+) -> Result<(), TransactionError> {
+    process_instructions(
+        context,
+        &[instruction::withdraw_lender_free_wallet_funds(
+            &env.program_id,
+            &lender.keypair.pubkey(),
+            &lender.token_account,
+            &env.vault,
+            &env.mint_vault_registry,
+            &env.lenders_storage,
+            &env.vault_authority,
+            &lender_withdrawal_history_pubkey(env, lender),
+            lender_id,
+            amount,
+        )],
+        &[&lender.keypair],
+    )
+    .await
+}
+
+async fn audit_vault(
+    context: &mut ProgramTestContext,
+    env: &Funds4GoodEnv,
+) -> Result<(), TransactionError> {
+    process_instructions(
+        context,
+        &[instruction::audit_vault(
+            &env.program_id,
+            &env.vault,
+            &env.mint_vault_registry,
+            &env.reserve_pool,
+            &env.reserve_vault,
+        )],
+        &[],
+    )
+    .await
+}
+
+async fn get_vault_liabilities(context: &mut ProgramTestContext, env: &Funds4GoodEnv) -> u64 {
+    let data = get_account_data(context, &env.mint_vault_registry).await;
+    MintVaultRegistryAccount::unpack(&data)
+        .unwrap()
+        .vault_liabilities
+}
+
+async fn get_account_data(context: &mut ProgramTestContext, pubkey: &Pubkey) -> Vec<u8> {
+    context
+        .banks_client
+        .get_account(*pubkey)
+        .await
+        .unwrap()
+        .unwrap()
+        .data
+}
+
+#[tokio::test]
+async fn test_loan_lifecycle() {
+    let program_id = Pubkey::new_unique();
+    let mut context = program_test(program_id).start_with_context().await;
+    let env = setup_funds4good(&mut context, program_id).await;
+
+    let loan = create_loan(&mut context, &env, 2, 200).await;
+    let header = get_loan_info_header(&mut context, &loan.loan_info).await;
+    assert_eq!(header.loan_status, LoanStatus::Fundraising as u8);
+    assert_eq!(
+        header.borrower_main_acc_pubkey,
+        loan.borrower.keypair.pubkey()
+    );
+    assert_eq!(
+        header.guarantor_main_acc_pubkey,
+        loan.guarantor.keypair.pubkey()
+    );
+    assert_eq!(header.total_loan_amount, 200 * COIN);
+
+    let first_lender = create_user(&mut context, &env, 100).await;
+    let second_lender = create_user(&mut context, &env, 100).await;
+    lend(&mut context, &env, &loan, &first_lender, 0, 100 * COIN)
+        .await
+        .unwrap();
+    lend(&mut context, &env, &loan, &second_lender, 1, 100 * COIN)
+        .await
+        .unwrap();
+
+    let header = get_loan_info_header(&mut context, &loan.loan_info).await;
+    assert_eq!(header.total_amount_lended, 200 * COIN);
+    assert_eq!(header.next_index_to_store_lender_data, 2);
+    assert_eq!(
+        get_token_balance(&mut context, &env.vault).await,
+        200 * COIN
+    );
+    assert_eq!(
+        get_token_balance(&mut context, &first_lender.token_account).await,
+        0
+    );
+
+    // 1% origination fee is kept for treasury
+    withdraw_collected_loan_funds(&mut context, &env, &loan, &loan.borrower)
+        .await
+        .unwrap();
+    let header = get_loan_info_header(&mut context, &loan.loan_info).await;
+    assert_eq!(header.loan_status, LoanStatus::Active as u8);
+    assert_eq!(get_token_balance(&mut context, &env.vault).await, 0);
+    assert_eq!(
+        get_token_balance(&mut context, &loan.borrower.token_account).await,
+        208 * COIN
+    );
+    assert_eq!(
+        get_token_balance(&mut context, &env.treasury).await,
+        2 * COIN
+    );
+
+    // 1% servicing fee goes to treasury and 1% to reserve vault, rest is split between lenders
+    pay_emi(&mut context, &env, &loan, 100 * COIN)
+        .await
+        .unwrap();
+    let header = get_loan_info_header(&mut context, &loan.loan_info).await;
+    assert_eq!(header.repaid_amount_by_borrower, 100 * COIN);
+    assert_eq!(header.next_index_to_store_repayment_info, 1);
+    assert_eq!(get_token_balance(&mut context, &env.vault).await, 98 * COIN);
+    assert_eq!(
+        get_token_balance(&mut context, &env.treasury).await,
+        3 * COIN
+    );
+    assert_eq!(
+        get_token_balance(&mut context, &env.reserve_vault).await,
+        COIN
+    );
+    assert_eq!(
+        get_lender_free_balance(&mut context, &env.lenders_storage, 0).await,
+        49 * COIN
+    );
+    assert_eq!(
+        get_lender_free_balance(&mut context, &env.lenders_storage, 1).await,
+        49 * COIN
+    );
+    let borrower_data = get_borrower_account(&mut context, &loan.borrower_storage).await;
+    assert!(borrower_data.credit_score > INITIAL_CREDIT_SCORE);
+
+    let withdrawal_history =
+        create_lender_withdrawal_history(&mut context, &env, &first_lender).await;
+    withdraw_lender_free_funds(&mut context, &env, &first_lender, 0, 49 * COIN)
+        .await
+        .unwrap();
+    let withdrawal_history_data = get_account_data(&mut context, &withdrawal_history).await;
+    let withdrawal_history_header = state::unpack_to_lender_withdrawal_history_header(
+        &withdrawal_history_data[0..state::LENDER_WITHDRAWAL_HISTORY_HEADER_DATA_BYTES],
+    )
+    .unwrap();
+    assert_eq!(withdrawal_history_header.total_withdrawals_count, 1);
+    assert_eq!(withdrawal_history_header.next_index_to_store_withdrawal, 1);
+    let withdrawal_record = state::unpack_to_lender_withdrawal_record(
+        &withdrawal_history_data[state::LENDER_WITHDRAWAL_HISTORY_HEADER_DATA_BYTES
+            ..state::LENDER_WITHDRAWAL_HISTORY_HEADER_DATA_BYTES
+                + state::LENDER_WITHDRAWAL_RECORD_DATA_BYTES],
+    )
+    .unwrap();
+    assert_eq!(withdrawal_record.withdrawn_amount, 49 * COIN);
+    assert_eq!(withdrawal_record.free_balance_after_withdrawal, 0);
+    assert_eq!(
+        get_token_balance(&mut context, &first_lender.token_account).await,
+        49 * COIN
+    );
+    assert_eq!(
+        get_lender_free_balance(&mut context, &env.lenders_storage, 0).await,
+        0
+    );
+    assert_eq!(get_token_balance(&mut context, &env.vault).await, 49 * COIN);
+
+    pay_emi(&mut context, &env, &loan, 100 * COIN)
+        .await
+        .unwrap();
+    let header = get_loan_info_header(&mut context, &loan.loan_info).await;
+    assert_eq!(header.repaid_amount_by_borrower, 200 * COIN);
+    assert_eq!(header.loan_status, LoanStatus::Repaid as u8);
+    assert_eq!(
+        get_token_balance(&mut context, &env.vault).await,
+        147 * COIN
+    );
+
+    assert_funds4good_error(
+        pay_emi(&mut context, &env, &loan, 100 * COIN).await,
+        Funds4GoodError::LoanAlreadyPaid,
+    );
+}
+
+#[tokio::test]
+async fn test_loan_lifecycle_without_reserve_pool() {
+    let program_id = Pubkey::new_unique();
+    let mut context = program_test(program_id).start_with_context().await;
+    let env = setup_funds4good_without_reserve_pool(&mut context, program_id).await;
+
+    let loan = create_loan(&mut context, &env, 2, 200).await;
+    let lender = create_user(&mut context, &env, 200).await;
+    lend(&mut context, &env, &loan, &lender, 0, 200 * COIN)
+        .await
+        .unwrap();
+    withdraw_collected_loan_funds(&mut context, &env, &loan, &loan.borrower)
+        .await
+        .unwrap();
+    let header = get_loan_info_header(&mut context, &loan.loan_info).await;
+    assert_eq!(header.loan_status, LoanStatus::Active as u8);
+    assert_eq!(header.is_reserve_covered, 0);
+
+    // without reserve pool only servicing fee is kept from the installment
+    pay_emi(&mut context, &env, &loan, 100 * COIN)
+        .await
+        .unwrap();
+    assert_eq!(get_token_balance(&mut context, &env.vault).await, 99 * COIN);
+    assert_eq!(get_token_balance(&mut context, &env.reserve_vault).await, 0);
+
+    // configuring the pool later doesn't make an already disbursed loan contribute to it
+    configure_reserve_pool(&mut context, &env).await;
+    pay_emi(&mut context, &env, &loan, 100 * COIN)
+        .await
+        .unwrap();
+    let header = get_loan_info_header(&mut context, &loan.loan_info).await;
+    assert_eq!(header.loan_status, LoanStatus::Repaid as u8);
+    assert_eq!(get_token_balance(&mut context, &env.reserve_vault).await, 0);
+    let reserve_pool = get_reserve_pool(&mut context, &env.reserve_pool).await;
+    assert_eq!(reserve_pool.pool_balance, 0);
+    assert_eq!(reserve_pool.total_outstanding_principal, 0);
+
+    let covered_loan = create_loan(&mut context, &env, 2, 200).await;
+    let covered_loan_lender = create_user(&mut context, &env, 200).await;
+    lend(
+        &mut context,
+        &env,
+        &covered_loan,
+        &covered_loan_lender,
+        1,
+        200 * COIN,
+    )
+    .await
+    .unwrap();
+    withdraw_collected_loan_funds(&mut context, &env, &covered_loan, &covered_loan.borrower)
+        .await
+        .unwrap();
+    let header = get_loan_info_header(&mut context, &covered_loan.loan_info).await;
+    assert_eq!(header.is_reserve_covered, 1);
+    let reserve_pool = get_reserve_pool(&mut context, &env.reserve_pool).await;
+    assert_eq!(reserve_pool.total_outstanding_principal, 200 * COIN);
+}
+
+#[tokio::test]
+async fn test_emi_distributed_pro_rata_to_lenders() {
+    let program_id = Pubkey::new_unique();
+    let mut context = program_test(program_id).start_with_context().await;
+    let env = setup_funds4good(&mut context, program_id).await;
+
+    let loan = create_loan(&mut context, &env, 2, 200).await;
+    let major_lender = create_user(&mut context, &env, 150).await;
+    let minor_lender = create_user(&mut context, &env, 50).await;
+    lend(&mut context, &env, &loan, &major_lender, 0, 150 * COIN)
+        .await
+        .unwrap();
+    lend(&mut context, &env, &loan, &minor_lender, 1, 50 * COIN)
+        .await
+        .unwrap();
+    withdraw_collected_loan_funds(&mut context, &env, &loan, &loan.borrower)
+        .await
+        .unwrap();
+    let major_before = get_lender_account(&mut context, &env.lenders_storage, 0).await;
+    let minor_before = get_lender_account(&mut context, &env.lenders_storage, 1).await;
+
+    // 98 coins reach lenders after servicing fee and reserve contribution, split 3:1
+    pay_emi(&mut context, &env, &loan, 100 * COIN)
+        .await
+        .unwrap();
+    let major_after = get_lender_account(&mut context, &env.lenders_storage, 0).await;
+    let minor_after = get_lender_account(&mut context, &env.lenders_storage, 1).await;
+    let major_share = 73 * COIN + COIN / 2;
+    let minor_share = 24 * COIN + COIN / 2;
+
+    assert_eq!(
+        major_after.amount_to_withdraw_or_lend - major_before.amount_to_withdraw_or_lend,
+        major_share
+    );
+    assert_eq!(
+        minor_after.amount_to_withdraw_or_lend - minor_before.amount_to_withdraw_or_lend,
+        minor_share
+    );
+    assert_eq!(
+        major_after.total_lending_amount - major_before.total_lending_amount,
+        major_share as u128
+    );
+    assert_eq!(
+        minor_after.total_lending_amount - minor_before.total_lending_amount,
+        minor_share as u128
+    );
+    assert_eq!(
+        major_after.total_unique_lending_amount - major_before.total_unique_lending_amount,
+        major_share
+    );
+    assert_eq!(
+        minor_after.total_unique_lending_amount - minor_before.total_unique_lending_amount,
+        minor_share
+    );
+}
+
+#[tokio::test]
+async fn test_default_coverage_limited_to_pool_balance() {
+    let program_id = Pubkey::new_unique();
+    let mut context = program_test(program_id).start_with_context().await;
+    let env = setup_funds4good(&mut context, program_id).await;
+
+    let loan = create_loan(&mut context, &env, 2, 200).await;
+    let lender = create_user(&mut context, &env, 200).await;
+    lend(&mut context, &env, &loan, &lender, 0, 200 * COIN)
+        .await
+        .unwrap();
+    withdraw_collected_loan_funds(&mut context, &env, &loan, &loan.borrower)
+        .await
+        .unwrap();
+    pay_emi(&mut context, &env, &loan, 100 * COIN)
+        .await
+        .unwrap();
+
+    assert_funds4good_error(
+        mark_loan_defaulted(&mut context, &env, &loan).await,
+        Funds4GoodError::LoanNotYetDefaulted,
+    );
+
+    // coins sent to reserve vault outside the program are not part of pool balance
+    mint_to(
+        &mut context,
+        &env.mint,
+        &env.reserve_vault,
+        &env.admin,
+        50 * COIN,
+    )
+    .await;
+    advance_clock(&mut context, 120).await;
+    mark_loan_defaulted(&mut context, &env, &loan)
+        .await
+        .unwrap();
+
+    let header = get_loan_info_header(&mut context, &loan.loan_info).await;
+    assert_eq!(header.loan_status, LoanStatus::Defaulted as u8);
+    assert_eq!(
+        get_lender_free_balance(&mut context, &env.lenders_storage, 0).await,
+        99 * COIN
+    );
+    assert_eq!(
+        get_token_balance(&mut context, &env.reserve_vault).await,
+        50 * COIN
+    );
+    let reserve_pool = get_reserve_pool(&mut context, &env.reserve_pool).await;
+    assert_eq!(reserve_pool.pool_balance, 0);
+    assert_eq!(reserve_pool.total_covered_amount, COIN);
+    assert_eq!(reserve_pool.total_outstanding_principal, 0);
+    // coverage moved from reserve vault is owed to lender as free balance
+    assert_eq!(get_vault_liabilities(&mut context, &env).await, 99 * COIN);
+    audit_vault(&mut context, &env).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_pay_off_loan_with_accrued_interest() {
+    let program_id = Pubkey::new_unique();
+    let mut context = program_test(program_id).start_with_context().await;
+    let env = setup_funds4good(&mut context, program_id).await;
+
+    set_interest_rate(&mut context, &env, &env.admin, 1_200)
+        .await
+        .unwrap();
+
+    let loan = create_loan(&mut context, &env, 2, 200).await;
+    let lender = create_user(&mut context, &env, 200).await;
+    lend(&mut context, &env, &loan, &lender, 0, 200 * COIN)
+        .await
+        .unwrap();
+    withdraw_collected_loan_funds(&mut context, &env, &loan, &loan.borrower)
+        .await
+        .unwrap();
+    mint_to(
+        &mut context,
+        &env.mint,
+        &loan.borrower.token_account,
+        &env.admin,
+        10 * COIN,
+    )
+    .await;
+
+    // 12% a year on 200 coins for 10 days
+    advance_clock(&mut context, 10).await;
+    let interest_amount = 200 * COIN * 1_200 * 10 / (10_000 * 365);
+    let payoff_amount = 200 * COIN + interest_amount;
+    assert_funds4good_error(
+        pay_off_loan(&mut context, &env, &loan, payoff_amount - 1).await,
+        Funds4GoodError::PayoffAmountExceedsMaximum,
+    );
+    pay_off_loan(&mut context, &env, &loan, payoff_amount)
+        .await
+        .unwrap();
+
+    let header = get_loan_info_header(&mut context, &loan.loan_info).await;
+    assert_eq!(header.loan_status, LoanStatus::Repaid as u8);
+    assert_eq!(header.repaid_amount_by_borrower, 200 * COIN);
+    assert_eq!(
+        get_token_balance(&mut context, &loan.borrower.token_account).await,
+        218 * COIN - payoff_amount
+    );
+    // servicing fee and reserve contribution are taken from principal, interest goes to lenders in full
+    assert_eq!(
+        get_lender_free_balance(&mut context, &env.lenders_storage, 0).await,
+        196 * COIN + interest_amount
+    );
+    assert_eq!(
+        get_vault_liabilities(&mut context, &env).await,
+        196 * COIN + interest_amount
+    );
+    let borrower_data = get_borrower_account(&mut context, &loan.borrower_storage).await;
+    assert!(borrower_data.credit_score > INITIAL_CREDIT_SCORE);
+
+    assert_funds4good_error(
+        pay_off_loan(&mut context, &env, &loan, payoff_amount).await,
+        Funds4GoodError::LoanStatusDoesNotMatched,
+    );
+}
+
+// baseline loan info account: 116 byte header, lenders and repayments regions, no extension or version byte
+fn legacy_loan_info_data(
+    header: LoanInfoAccDataHeader,
+    lender: state::LoanInfoAccLendersData,
+    repayment: state::LoanInfoAccRepaymentData,
+) -> Vec<u8> {
+    let mut header_bytes = [0u8; state::LOAN_INFO_HEADER_DATA_BYTES];
+    state::pack_to_loan_info_header(header, &mut header_bytes).unwrap();
+    let mut data = vec![0u8; state::LOAN_INFO_ACC_LEGACY_DATA_SIZE];
+    data[..state::LOAN_INFO_HEADER_BASE_DATA_BYTES]
+        .copy_from_slice(&header_bytes[..state::LOAN_INFO_HEADER_BASE_DATA_BYTES]);
+    state::pack_to_loan_info_acc_lender_data(
+        lender,
+        &mut data[state::LOAN_INFO_ACC_LENDERS_DATA_START_INDEX
+            ..state::LOAN_INFO_ACC_LENDERS_DATA_START_INDEX
+                + state::LOAN_INFO_ACC_LENDER_DATA_BYTES],
+    )
+    .unwrap();
+    state::pack_to_loan_info_acc_repayment_data(
+        repayment,
+        &mut data[state::LOAN_INFO_ACC_REPAYMENT_DATA_SI
+            ..state::LOAN_INFO_ACC_REPAYMENT_DATA_SI + state::LOAN_INFO_ACC_REPAYMENT_DATA_BYTES],
+    )
+    .unwrap();
+    data
+}
+
+#[tokio::test]
+async fn test_emi_collects_accrued_interest() {
+    let program_id = Pubkey::new_unique();
+    let mut context = program_test(program_id).start_with_context().await;
+    let env = setup_funds4good(&mut context, program_id).await;
+
+    let not_admin = Keypair::new();
+    fund_account(&mut context, &not_admin.pubkey(), 1_000_000_000).await;
+    assert_funds4good_error(
+        set_interest_rate(&mut context, &env, &not_admin, 1_200).await,
+        Funds4GoodError::AdminDoesNotMatched,
+    );
+    assert_funds4good_error(
+        set_interest_rate(&mut context, &env, &env.admin, 3_601).await,
+        Funds4GoodError::InvalidFeeBps,
+    );
+    set_interest_rate(&mut context, &env, &env.admin, 1_200)
+        .await
+        .unwrap();
+
+    let loan = create_loan(&mut context, &env, 2, 200).await;
+    let lender = create_user(&mut context, &env, 200).await;
+    lend(&mut context, &env, &loan, &lender, 0, 200 * COIN)
+        .await
+        .unwrap();
+    withdraw_collected_loan_funds(&mut context, &env, &loan, &loan.borrower)
+        .await
+        .unwrap();
+    mint_to(
+        &mut context,
+        &env.mint,
+        &loan.borrower.token_account,
+        &env.admin,
+        10 * COIN,
+    )
+    .await;
+
+    // 12% a year on 200 coins for 10 days is collected before the installment
+    advance_clock(&mut context, 10).await;
+    let interest_amount = 200 * COIN * 1_200 * 10 / (10_000 * 365);
+    assert_funds4good_error(
+        pay_emi(&mut context, &env, &loan, 100 * COIN).await,
+        Funds4GoodError::ExpectedAmountMismatch,
+    );
+    pay_emi(&mut context, &env, &loan, 100 * COIN + interest_amount)
+        .await
+        .unwrap();
+
+    let header = get_loan_info_header(&mut context, &loan.loan_info).await;
+    assert_eq!(header.repaid_amount_by_borrower, 100 * COIN);
+    assert_eq!(
+        get_token_balance(&mut context, &loan.borrower.token_account).await,
+        118 * COIN - interest_amount
+    );
+    // servicing fee and reserve contribution are taken from the installment, interest goes to lenders in full
+    assert_eq!(
+        get_lender_free_balance(&mut context, &env.lenders_storage, 0).await,
+        98 * COIN + interest_amount
+    );
+    assert_eq!(
+        get_vault_liabilities(&mut context, &env).await,
+        98 * COIN + interest_amount
+    );
+
+    // interest accrues again from the last repayment, nothing is due on the same day
+    pay_emi(&mut context, &env, &loan, 100 * COIN)
+        .await
+        .unwrap();
+    let header = get_loan_info_header(&mut context, &loan.loan_info).await;
+    assert_eq!(header.loan_status, LoanStatus::Repaid as u8);
+    assert_eq!(
+        get_lender_free_balance(&mut context, &env.lenders_storage, 0).await,
+        196 * COIN + interest_amount
+    );
+}
+
+#[tokio::test]
+async fn test_migrate_legacy_loan_info_account() {
+    let program_id = Pubkey::new_unique();
+    let mut context = program_test(program_id).start_with_context().await;
+    let env = setup_funds4good(&mut context, program_id).await;
+    fund_account(&mut context, &env.admin.pubkey(), 1_000_000_000).await;
+
+    // disbursed loan with one of two emis paid, baseline zeroed total_amount_lended on disbursement
+    let borrower = Pubkey::new_unique();
+    let lender = Pubkey::new_unique();
+    let data = legacy_loan_info_data(
+        LoanInfoAccDataHeader {
+            acc_type: AccTypes::LoanInfoAcc as u8,
+            borrower_main_acc_pubkey: borrower,
+            guarantor_main_acc_pubkey: Pubkey::new_unique(),
+            loan_approval_timestamp: 1_000,
+            fundraising_period_ending_timestamp: 2_000,
+            first_repayment_last_date_timestamp: 3_000,
+            total_loan_amount: 200 * COIN,
+            total_amount_lended: 0,
+            repaid_amount_by_borrower: 100 * COIN,
+            next_index_to_store_lender_data: 1,
+            next_index_to_store_repayment_info: 1,
+            number_of_emis_needed_to_repay_the_loan: 2,
+            loan_mint_pubkey: Pubkey::default(),
+            loan_status: 0,
+            penalty_paid_amount: 0,
+            schedule_version: 0,
+            schedule_repaid_amount_base: 0,
+            guarantor_claim_amount: 0,
+            guarantor_paid_amount: 0,
+            min_funding_amount: 0,
+            is_reserve_covered: 0,
+            disbursement_timestamp: 0,
+        },
+        state::LoanInfoAccLendersData {
+            lender_main_acc_pubkey: lender,
+            lenders_data_storage_acc_number: 1,
+            lender_id: 0,
+            lent_amount: 200 * COIN,
+        },
+        state::LoanInfoAccRepaymentData {
+            emi_repayment_timestamp: 2_500,
+            emi_repayment_amount: 100 * COIN,
+        },
+    );
+    let rent = context.banks_client.get_rent().await.unwrap();
+    let loan_info = Pubkey::new_unique();
+    let mut legacy_account =
+        Account::new(rent.minimum_balance(data.len()), data.len(), &program_id);
+    legacy_account.data = data;
+    context.set_account(&loan_info, &AccountSharedData::from(legacy_account));
+
+    let stranger = Keypair::new();
+    fund_account(&mut context, &stranger.pubkey(), 1_000_000_000).await;
+    assert_funds4good_error(
+        process_instructions(
+            &mut context,
+            &[instruction::migrate_loan_info_account(
+                &program_id,
+                &stranger.pubkey(),
+                &loan_info,
+                &env.config,
+                &env.mint_vault_registry,
+            )],
+            &[&stranger],
+        )
+        .await,
+        Funds4GoodError::AdminDoesNotMatched,
+    );
+
+    process_instructions(
+        &mut context,
+        &[instruction::migrate_loan_info_account(
+            &program_id,
+            &env.admin.pubkey(),
+            &loan_info,
+            &env.config,
+            &env.mint_vault_registry,
+        )],
+        &[&env.admin],
+    )
+    .await
+    .unwrap();
+
+    let mut account = context
+        .banks_client
+        .get_account(loan_info)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(account.data.len(), state::LOAN_INFO_ACC_DATA_SIZE);
+    assert!(account.lamports >= rent.minimum_balance(state::LOAN_INFO_ACC_DATA_SIZE));
+    let migrated = LoanInfoAccount::new(&mut account.data).unwrap();
+    assert_eq!(migrated.layout_version(), state::LOAN_INFO_LAYOUT_VERSION);
+    let header = migrated.header().unwrap();
+    assert_eq!(header.borrower_main_acc_pubkey, borrower);
+    assert_eq!(header.repaid_amount_by_borrower, 100 * COIN);
+    assert_eq!(header.loan_status, LoanStatus::Active as u8);
+    assert_eq!(header.loan_mint_pubkey, env.mint);
+    assert_eq!(header.min_funding_amount, 200 * COIN);
+    assert_eq!(header.disbursement_timestamp, 2_000);
+    assert_eq!(header.is_reserve_covered, 0);
+    assert_eq!(migrated.lender(0).unwrap().lender_main_acc_pubkey, lender);
+    assert_eq!(
+        migrated.repayment(0).unwrap().emi_repayment_amount,
+        100 * COIN
+    );
+
+    assert_funds4good_error(
+        process_instructions(
+            &mut context,
+            &[instruction::migrate_loan_info_account(
+                &program_id,
+                &env.admin.pubkey(),
+                &loan_info,
+                &env.config,
+                &env.mint_vault_registry,
+            )],
+            &[&env.admin],
+        )
+        .await,
+        Funds4GoodError::AccountAlreadyMigrated,
+    );
+}
+
+#[tokio::test]
+async fn test_only_upgrade_authority_creates_config() {
+    let program_id = Pubkey::new_unique();
+    let mut context = program_test(program_id).start_with_context().await;
+
+    // anyone front running the deployer with a vault of their own mint can't become admin
+    let attacker = Keypair::new();
+    fund_account(&mut context, &attacker.pubkey(), 1_000_000_000).await;
+    let attacker_mint = create_mint(&mut context, &attacker.pubkey()).await;
+    let attacker_vault =
+        create_token_account(&mut context, &attacker_mint, &attacker.pubkey()).await;
+    let (config, _) = Pubkey::find_program_address(&[b"Funds4GoodFinanceConfig"], &program_id);
+    let (attacker_mint_vault_registry, _) = Pubkey::find_program_address(
+        &[b"Funds4GoodFinanceVault", attacker_mint.as_ref()],
+        &program_id,
+    );
+    let attacker_ix = instruction::transfer_Funds4Good_vault_account_ownership(
+        &program_id,
+        &attacker.pubkey(),
+        &attacker_vault,
+        &config,
+        &attacker_mint,
+        &attacker_mint_vault_registry,
+        &Pubkey::new_unique(),
+    );
+
+    // program data account only exists for programs deployed with upgradeable loader
+    assert_funds4good_error(
+        process_instructions(
+            &mut context,
+            std::slice::from_ref(&attacker_ix),
+            &[&attacker],
+        )
+        .await,
+        Funds4GoodError::ProgramDataAccountDoesNotMatched,
+    );
+
+    let deployer = Keypair::new();
+    set_upgrade_authority(&mut context, &program_id, &deployer.pubkey());
+    assert_funds4good_error(
+        process_instructions(&mut context, &[attacker_ix], &[&attacker]).await,
+        Funds4GoodError::UpgradeAuthorityDoesNotMatched,
+    );
+    assert!(context
+        .banks_client
+        .get_account(config)
+        .await
+        .unwrap()
+        .is_none());
+
+    let env = setup_funds4good(&mut context, program_id).await;
+    let config_account = context
+        .banks_client
+        .get_account(env.config)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        state::Funds4GoodConfigAccount::unpack(&config_account.data)
+            .unwrap()
+            .admin_pubkey,
+        env.admin.pubkey()
+    );
+}
+
+#[tokio::test]
+async fn test_unsupported_instructions_fail() {
+    let program_id = Pubkey::new_unique();
+    let mut context = program_test(program_id).start_with_context().await;
+
+    assert_funds4good_error(
+        process_instructions(
+            &mut context,
+            &[instruction::return_funds_to_lenders(&program_id, 0)],
+            &[],
+        )
+        .await,
+        Funds4GoodError::InvalidInstruction,
+    );
+    assert_funds4good_error(
+        process_instructions(
+            &mut context,
+            &[instruction::close_loan_info_account(&program_id)],
+            &[],
+        )
+        .await,
+        Funds4GoodError::InvalidInstruction,
+    );
+}
+
+#[tokio::test]
+async fn test_vault_setup_errors() {
+    let program_id = Pubkey::new_unique();
+    let mut context = program_test(program_id).start_with_context().await;
+    let env = setup_funds4good(&mut context, program_id).await;
+
+    // registry of a mint can only be recorded once
+    let second_vault = create_token_account(&mut context, &env.mint, &env.admin.pubkey()).await;
+    assert_funds4good_error(
+        process_instructions(
+            &mut context,
+            &[instruction::transfer_Funds4Good_vault_account_ownership(
+                &program_id,
+                &env.admin.pubkey(),
+                &second_vault,
+                &env.config,
+                &env.mint,
+                &env.mint_vault_registry,
+                &env.lenders_storage,
+            )],
+            &[&env.admin],
+        )
+        .await,
+        Funds4GoodError::VaultAlreadyRecorded,
+    );
+
+    // lenders storage of a new mint is checked for its account type even when it has no data
+    let empty_lenders_storage = Keypair::new();
+    let payer_pubkey = context.payer.pubkey();
+    let rent = context.banks_client.get_rent().await.unwrap();
+    let new_mint = create_mint(&mut context, &env.admin.pubkey()).await;
+    let new_vault = create_token_account(&mut context, &new_mint, &env.admin.pubkey()).await;
+    let (new_mint_vault_registry, _) =
+        Pubkey::find_program_address(&[b"Funds4GoodFinanceVault", new_mint.as_ref()], &program_id);
+    assert_funds4good_error(
+        process_instructions(
+            &mut context,
+            &[
+                create_program_account_ix(
+                    &payer_pubkey,
+                    &rent,
+                    &program_id,
+                    &empty_lenders_storage.pubkey(),
+                    0,
+                ),
+                instruction::transfer_Funds4Good_vault_account_ownership(
+                    &program_id,
+                    &env.admin.pubkey(),
+                    &new_vault,
+                    &env.config,
+                    &new_mint,
+                    &new_mint_vault_registry,
+                    &empty_lenders_storage.pubkey(),
+                ),
+            ],
+            &[&empty_lenders_storage, &env.admin],
+        )
+        .await,
+        Funds4GoodError::ExpectedAccountTypeMismatched,
+    );
+
+    // only admin can register vaults of other mints
+    let imposter = Keypair::new();
+    fund_account(&mut context, &imposter.pubkey(), 1_000_000_000).await;
+    let other_mint = create_mint(&mut context, &imposter.pubkey()).await;
+    let other_vault = create_token_account(&mut context, &other_mint, &imposter.pubkey()).await;
+    let (other_mint_vault_registry, _) = Pubkey::find_program_address(
+        &[b"Funds4GoodFinanceVault", other_mint.as_ref()],
+        &program_id,
+    );
+    assert_funds4good_error(
+        process_instructions(
+            &mut context,
+            &[instruction::transfer_Funds4Good_vault_account_ownership(
+                &program_id,
+                &imposter.pubkey(),
+                &other_vault,
+                &env.config,
+                &other_mint,
+                &other_mint_vault_registry,
+                &env.lenders_storage,
+            )],
+            &[&imposter],
+        )
+        .await,
+        Funds4GoodError::AdminDoesNotMatched,
+    );
+
+    assert_funds4good_error(
+        process_instructions(
+            &mut context,
+            &[instruction::set_platform_fees(
+                &program_id,
+                &imposter.pubkey(),
+                &env.config,
+                &env.mint_vault_registry,
+                0,
+                0,
+            )],
+            &[&imposter],
+        )
+        .await,
+        Funds4GoodError::AdminDoesNotMatched,
+    );
+
+    assert_funds4good_error(
+        process_instructions(
+            &mut context,
+            &[instruction::set_platform_fees(
+                &program_id,
+                &env.admin.pubkey(),
+                &env.config,
+                &env.mint_vault_registry,
+                1_001,
+                0,
+            )],
+            &[&env.admin],
+        )
+        .await,
+        Funds4GoodError::InvalidFeeBps,
+    );
+
+    // lenders storage can't be initialized twice
+    assert_funds4good_error(
+        process_instructions(
+            &mut context,
+            &[instruction::initialize_lenders_storage_account(
+                &program_id,
+                &env.admin.pubkey(),
+                &env.lenders_storage,
+            )],
+            &[&env.admin],
+        )
+        .await,
+        Funds4GoodError::LendersStorageDataAlreadyInitialized,
+    );
+}
+
+#[tokio::test]
+async fn test_loan_initialization_errors() {
+    let program_id = Pubkey::new_unique();
+    let mut context = program_test(program_id).start_with_context().await;
+    let env = setup_funds4good(&mut context, program_id).await;
+
+    let (borrower, guarantor, borrower_storage) =
+        create_borrower_and_guarantor(&mut context, &env).await;
+
+    assert_funds4good_error(
+        process_instructions(
+            &mut context,
+            &[instruction::initialize_borrower_account(
+                &program_id,
+                &borrower.keypair.pubkey(),
+                &borrower_storage,
+            )],
+            &[&borrower.keypair],
+        )
+        .await,
+        Funds4GoodError::BorrowerAccountAlreadyInitialized,
+    );
+
+    let payer_pubkey = context.payer.pubkey();
+    let rent = context.banks_client.get_rent().await.unwrap();
+    let loan_info = Keypair::new();
+    process_instructions(
+        &mut context,
+        &[create_program_account_ix(
+            &payer_pubkey,
+            &rent,
+            &program_id,
+            &loan_info.pubkey(),
+            state::LOAN_INFO_ACC_DATA_SIZE,
+        )],
+        &[&loan_info],
+    )
+    .await
+    .unwrap();
+
+    // loan amount must be between 200 and 2000 coins in multiples of 100
+    for total_loan_coins in [100, 250, 2100] {
+        assert_funds4good_error(
+            process_instructions(
+                &mut context,
+                &[initialize_loan_info_account_ix(
+                    &env,
+                    &borrower,
+                    &guarantor,
+                    &borrower_storage,
+                    &loan_info.pubkey(),
+                    2,
+                    10,
+                    total_loan_coins,
+                )],
+                &[&guarantor.keypair],
+            )
+            .await,
+            Funds4GoodError::InvalidLoanAmount,
+        );
+    }
+
+    assert_funds4good_error(
+        process_instructions(
+            &mut context,
+            &[initialize_loan_info_account_ix(
+                &env,
+                &borrower,
+                &guarantor,
+                &borrower_storage,
+                &loan_info.pubkey(),
+                0,
+                10,
+                200,
+            )],
+            &[&guarantor.keypair],
+        )
+        .await,
+        Funds4GoodError::InvalidNumberOfEmis,
+    );
+
+    process_instructions(
+        &mut context,
+        &[initialize_loan_info_account_ix(
+            &env,
+            &borrower,
+            &guarantor,
+            &borrower_storage,
+            &loan_info.pubkey(),
+            2,
+            10,
+            200,
+        )],
+        &[&guarantor.keypair],
+    )
+    .await
+    .unwrap();
+
+    assert_funds4good_error(
+        process_instructions(
+            &mut context,
+            &[initialize_loan_info_account_ix(
+                &env,
+                &borrower,
+                &guarantor,
+                &borrower_storage,
+                &loan_info.pubkey(),
+                4,
+                10,
+                400,
+            )],
+            &[&guarantor.keypair],
+        )
+        .await,
+        Funds4GoodError::LoanInfoDataAlreadyInitialized,
+    );
+}
+
+#[tokio::test]
+async fn test_lending_and_disbursement_errors() {
+    let program_id = Pubkey::new_unique();
+    let mut context = program_test(program_id).start_with_context().await;
+    let env = setup_funds4good(&mut context, program_id).await;
+
+    let loan = create_loan(&mut context, &env, 2, 200).await;
+    let lender = create_user(&mut context, &env, 300).await;
+
+    assert_funds4good_error(
+        lend(&mut context, &env, &loan, &lender, 0, 5 * COIN).await,
+        Funds4GoodError::ExpectedAmountMismatch,
+    );
+    assert_funds4good_error(
+        lend(&mut context, &env, &loan, &lender, 0, 15 * COIN).await,
+        Funds4GoodError::LendingAmountNotInAllowedMultiple,
+    );
+    assert_funds4good_error(
+        lend(
+            &mut context,
+            &env,
+            &loan,
+            &lender,
+            state::LENDERS_STORAGE_MAX_LENDERS as u32,
+            10 * COIN,
+        )
+        .await,
+        Funds4GoodError::InvalidLenderIdInput,
+    );
+
+    lend(&mut context, &env, &loan, &lender, 0, 100 * COIN)
+        .await
+        .unwrap();
+
+    // lender id is bound to the lender who used it first
+    let other_lender = create_user(&mut context, &env, 100).await;
+    assert_funds4good_error(
+        lend(&mut context, &env, &loan, &other_lender, 0, 10 * COIN).await,
+        Funds4GoodError::InvalidLenderIdInput,
+    );
+
+    // borrower can't take a partially funded loan when no minimum funding was set
+    assert_funds4good_error(
+        withdraw_collected_loan_funds(&mut context, &env, &loan, &loan.borrower).await,
+        Funds4GoodError::CollectedLoanFundsAlreadyWithdrawn,
+    );
+
+    // lending above the remaining shortfall credits the excess to lender free balance
+    lend(&mut context, &env, &loan, &lender, 0, 200 * COIN)
+        .await
+        .unwrap();
+    assert_eq!(
+        get_lender_free_balance(&mut context, &env.lenders_storage, 0).await,
+        100 * COIN
+    );
+    assert_funds4good_error(
+        lend(&mut context, &env, &loan, &other_lender, 1, 10 * COIN).await,
+        Funds4GoodError::BorrowerAlreadyFunded,
+    );
+
+    assert_funds4good_error(
+        withdraw_collected_loan_funds(&mut context, &env, &loan, &other_lender).await,
+        Funds4GoodError::BorrowerAccountMismatched,
+    );
+
+    withdraw_collected_loan_funds(&mut context, &env, &loan, &loan.borrower)
+        .await
+        .unwrap();
+    assert_funds4good_error(
+        withdraw_collected_loan_funds(&mut context, &env, &loan, &loan.borrower).await,
+        Funds4GoodError::CollectedLoanFundsAlreadyWithdrawn,
+    );
+
+    assert_funds4good_error(
+        withdraw_lender_free_funds(&mut context, &env, &lender, 0, 0).await,
+        Funds4GoodError::InvalidWithdrawAmount,
+    );
+    assert_funds4good_error(
+        withdraw_lender_free_funds(&mut context, &env, &lender, 0, 110 * COIN).await,
+        Funds4GoodError::InsufficientFreeBalance,
+    );
+    assert_funds4good_error(
+        withdraw_lender_free_funds(&mut context, &env, &other_lender, 0, 10 * COIN).await,
+        Funds4GoodError::InvalidLenderIdInput,
+    );
+    // nothing is recorded while lender hasn't created withdrawal history account
+    withdraw_lender_free_funds(&mut context, &env, &lender, 0, 50 * COIN)
+        .await
+        .unwrap();
+    assert_eq!(
+        get_token_balance(&mut context, &lender.token_account).await,
+        50 * COIN
+    );
+
+    // history with its next slot out of range is rejected instead of written past its end
+    let withdrawal_history = create_lender_withdrawal_history(&mut context, &env, &lender).await;
+    let mut withdrawal_history_account = context
+        .banks_client
+        .get_account(withdrawal_history)
+        .await
+        .unwrap()
+        .unwrap();
+    let withdrawal_history_header_data =
+        &mut withdrawal_history_account.data[0..state::LENDER_WITHDRAWAL_HISTORY_HEADER_DATA_BYTES];
+    let mut withdrawal_history_header =
+        state::unpack_to_lender_withdrawal_history_header(withdrawal_history_header_data).unwrap();
+    withdrawal_history_header.next_index_to_store_withdrawal =
+        state::LENDER_WITHDRAWAL_HISTORY_CAPACITY as u8;
+    state::pack_to_lender_withdrawal_history_header(
+        withdrawal_history_header,
+        withdrawal_history_header_data,
+    )
+    .unwrap();
+    context.set_account(
+        &withdrawal_history,
+        &AccountSharedData::from(withdrawal_history_account),
+    );
+    assert_matches!(
+        withdraw_lender_free_funds(&mut context, &env, &lender, 0, 50 * COIN).await,
+        Err(TransactionError::InstructionError(
+            _,
+            InstructionError::InvalidAccountData
+        ))
+    );
+}
+
+#[tokio::test]
+async fn test_fundraising_period_expires() {
+    let program_id = Pubkey::new_unique();
+    let mut context = program_test(program_id).start_with_context().await;
+    let env = setup_funds4good(&mut context, program_id).await;
+
+    let loan = create_loan(&mut context, &env, 2, 200).await;
+    let lender = create_user(&mut context, &env, 200).await;
+    lend(&mut context, &env, &loan, &lender, 0, 100 * COIN)
+        .await
+        .unwrap();
+
+    advance_clock(&mut context, 11).await;
+
+    assert_funds4good_error(
+        lend(&mut context, &env, &loan, &lender, 0, 100 * COIN).await,
+        Funds4GoodError::FundraisingPeriodExpired,
+    );
+}
+
+#[tokio::test]
+async fn test_cancelled_loan() {
+    let program_id = Pubkey::new_unique();
+    let mut context = program_test(program_id).start_with_context().await;
+    let env = setup_funds4good(&mut context, program_id).await;
+
+    let loan = create_loan(&mut context, &env, 2, 200).await;
+    let lender = create_user(&mut context, &env, 200).await;
+    lend(&mut context, &env, &loan, &lender, 0, 100 * COIN)
+        .await
+        .unwrap();
+
+    let stranger = create_user(&mut context, &env, 0).await;
+    assert_funds4good_error(
+        cancel_loan(&mut context, &env, &loan, &stranger.keypair).await,
+        Funds4GoodError::AccountMismatched,
+    );
+    cancel_loan(&mut context, &env, &loan, &loan.borrower.keypair)
+        .await
+        .unwrap();
+
+    let header = get_loan_info_header(&mut context, &loan.loan_info).await;
+    assert_eq!(header.loan_status, LoanStatus::Cancelled as u8);
+    assert_eq!(
+        get_lender_free_balance(&mut context, &env.lenders_storage, 0).await,
+        100 * COIN
+    );
+    assert_funds4good_error(
+        withdraw_collected_loan_funds(&mut context, &env, &loan, &loan.borrower).await,
+        Funds4GoodError::LoanCancelled,
+    );
+    assert_funds4good_error(
+        lend(&mut context, &env, &loan, &lender, 0, 100 * COIN).await,
+        Funds4GoodError::LoanCancelled,
+    );
+}
+
+#[tokio::test]
+async fn test_emi_payment_errors_and_guarantor_payment() {
+    let program_id = Pubkey::new_unique();
+    let mut context = program_test(program_id).start_with_context().await;
+    let env = setup_funds4good(&mut context, program_id).await;
+
+    let loan = create_loan(&mut context, &env, 2, 200).await;
+    let lender = create_user(&mut context, &env, 200).await;
+
+    assert_funds4good_error(
+        pay_emi(&mut context, &env, &loan, 100 * COIN).await,
+        Funds4GoodError::LoanStatusDoesNotMatched,
+    );
+
+    lend(&mut context, &env, &loan, &lender, 0, 200 * COIN)
+        .await
+        .unwrap();
+    withdraw_collected_loan_funds(&mut context, &env, &loan, &loan.borrower)
+        .await
+        .unwrap();
+
+    assert_funds4good_error(
+        pay_emi(&mut context, &env, &loan, 50 * COIN).await,
+        Funds4GoodError::ExpectedAmountMismatch,
+    );
+    assert_funds4good_error(
+        guarantor_pay_overdue_emi(&mut context, &env, &loan, 100 * COIN).await,
+        Funds4GoodError::LoanInstallmentNotOverdue,
+    );
+
+    // first installment is due 30 days plus 5 days of grace after loan approval
+    advance_clock(&mut context, 36).await;
+
+    guarantor_pay_overdue_emi(&mut context, &env, &loan, 100 * COIN)
+        .await
+        .unwrap();
+    let header = get_loan_info_header(&mut context, &loan.loan_info).await;
+    assert_eq!(header.repaid_amount_by_borrower, 100 * COIN);
+    assert_eq!(header.guarantor_claim_amount, 100 * COIN);
+    assert_eq!(header.guarantor_paid_amount, 100 * COIN);
+    assert_eq!(
+        get_token_balance(&mut context, &loan.guarantor.token_account).await,
+        100 * COIN
+    );
+    let borrower_data = get_borrower_account(&mut context, &loan.borrower_storage).await;
+    assert!(borrower_data.credit_score < INITIAL_CREDIT_SCORE);
+
+    // borrower pays guarantor back at most what guarantor paid on their behalf
+    assert_funds4good_error(
+        repay_guarantor(&mut context, &env, &loan, 0).await,
+        Funds4GoodError::InvalidGuarantorClaimAmount,
+    );
+    assert_funds4good_error(
+        repay_guarantor(&mut context, &env, &loan, 101 * COIN).await,
+        Funds4GoodError::InvalidGuarantorClaimAmount,
+    );
+    repay_guarantor(&mut context, &env, &loan, 60 * COIN)
+        .await
+        .unwrap();
+    let header = get_loan_info_header(&mut context, &loan.loan_info).await;
+    assert_eq!(header.guarantor_claim_amount, 40 * COIN);
+    assert_eq!(
+        get_token_balance(&mut context, &loan.guarantor.token_account).await,
+        160 * COIN
+    );
+    repay_guarantor(&mut context, &env, &loan, 40 * COIN)
+        .await
+        .unwrap();
+    let header = get_loan_info_header(&mut context, &loan.loan_info).await;
+    assert_eq!(header.guarantor_claim_amount, 0);
+}
+
+#[tokio::test]
+async fn test_pay_emi_on_behalf() {
+    let program_id = Pubkey::new_unique();
+    let mut context = program_test(program_id).start_with_context().await;
+    let env = setup_funds4good(&mut context, program_id).await;
+
+    let loan = create_loan(&mut context, &env, 2, 200).await;
+    let lender = create_user(&mut context, &env, 200).await;
+    let payer = create_user(&mut context, &env, 100).await;
+
+    assert_funds4good_error(
+        pay_emi_on_behalf(&mut context, &env, &loan, &payer, 100 * COIN).await,
+        Funds4GoodError::LoanStatusDoesNotMatched,
+    );
+
+    lend(&mut context, &env, &loan, &lender, 0, 200 * COIN)
+        .await
+        .unwrap();
+    withdraw_collected_loan_funds(&mut context, &env, &loan, &loan.borrower)
+        .await
+        .unwrap();
+
+    assert_funds4good_error(
+        pay_emi_on_behalf(&mut context, &env, &loan, &payer, 50 * COIN).await,
+        Funds4GoodError::ExpectedAmountMismatch,
+    );
+    pay_emi_on_behalf(&mut context, &env, &loan, &payer, 100 * COIN)
+        .await
+        .unwrap();
+
+    let header = get_loan_info_header(&mut context, &loan.loan_info).await;
+    assert_eq!(header.repaid_amount_by_borrower, 100 * COIN);
+    assert_eq!(
+        get_token_balance(&mut context, &payer.token_account).await,
+        0
+    );
+    // borrower keeps own coins and the disbursed funds, someone else paid the installment
+    assert_eq!(
+        get_token_balance(&mut context, &loan.borrower.token_account).await,
+        208 * COIN
+    );
+}
+
+#[tokio::test]
+async fn test_restructure_loan() {
+    let program_id = Pubkey::new_unique();
+    let mut context = program_test(program_id).start_with_context().await;
+    let env = setup_funds4good(&mut context, program_id).await;
+
+    let loan = create_loan(&mut context, &env, 2, 200).await;
+    let lender = create_user(&mut context, &env, 200).await;
+    lend(&mut context, &env, &loan, &lender, 0, 200 * COIN)
+        .await
+        .unwrap();
+
+    assert_funds4good_error(
+        restructure_loan(&mut context, &env, &loan, &loan.guarantor.keypair, 4, 30).await,
+        Funds4GoodError::LoanStatusDoesNotMatched,
+    );
+
+    withdraw_collected_loan_funds(&mut context, &env, &loan, &loan.borrower)
+        .await
+        .unwrap();
+    pay_emi(&mut context, &env, &loan, 100 * COIN)
+        .await
+        .unwrap();
+
+    assert_funds4good_error(
+        restructure_loan(&mut context, &env, &loan, &loan.guarantor.keypair, 0, 30).await,
+        Funds4GoodError::InvalidNumberOfEmis,
+    );
+    let stranger = Keypair::new();
+    assert_funds4good_error(
+        restructure_loan(&mut context, &env, &loan, &stranger, 4, 30).await,
+        Funds4GoodError::AccountMismatched,
+    );
+
+    let credit_score_before = get_borrower_account(&mut context, &loan.borrower_storage)
+        .await
+        .credit_score;
+    restructure_loan(&mut context, &env, &loan, &loan.guarantor.keypair, 4, 30)
+        .await
+        .unwrap();
+
+    let header = get_loan_info_header(&mut context, &loan.loan_info).await;
+    assert_eq!(header.number_of_emis_needed_to_repay_the_loan, 4);
+    assert_eq!(header.schedule_version, 1);
+    assert_eq!(header.schedule_repaid_amount_base, 100 * COIN);
+    let borrower_data = get_borrower_account(&mut context, &loan.borrower_storage).await;
+    assert!(borrower_data.credit_score < credit_score_before);
+
+    // remaining 100 coins are spread over the 4 new installments
+    assert_funds4good_error(
+        pay_emi(&mut context, &env, &loan, 20 * COIN).await,
+        Funds4GoodError::ExpectedAmountMismatch,
+    );
+    pay_emi(&mut context, &env, &loan, 25 * COIN).await.unwrap();
+    let header = get_loan_info_header(&mut context, &loan.loan_info).await;
+    assert_eq!(header.repaid_amount_by_borrower, 125 * COIN);
+}
+
+#[tokio::test]
+async fn test_withdraw_treasury() {
+    let program_id = Pubkey::new_unique();
+    let mut context = program_test(program_id).start_with_context().await;
+    let env = setup_funds4good(&mut context, program_id).await;
+
+    let loan = create_loan(&mut context, &env, 2, 200).await;
+    let lender = create_user(&mut context, &env, 200).await;
+    lend(&mut context, &env, &loan, &lender, 0, 200 * COIN)
+        .await
+        .unwrap();
+    withdraw_collected_loan_funds(&mut context, &env, &loan, &loan.borrower)
+        .await
+        .unwrap();
+    assert_eq!(
+        get_token_balance(&mut context, &env.treasury).await,
+        2 * COIN
+    );
+
+    let admin_token_account =
+        create_token_account(&mut context, &env.mint, &env.admin.pubkey()).await;
+    let not_admin = Keypair::new();
+    assert_funds4good_error(
+        withdraw_treasury(&mut context, &env, &not_admin, &admin_token_account, COIN).await,
+        Funds4GoodError::AdminDoesNotMatched,
+    );
+    assert_funds4good_error(
+        withdraw_treasury(&mut context, &env, &env.admin, &admin_token_account, 0).await,
+        Funds4GoodError::InvalidWithdrawAmount,
+    );
+
+    withdraw_treasury(
+        &mut context,
+        &env,
+        &env.admin,
+        &admin_token_account,
+        2 * COIN,
+    )
+    .await
+    .unwrap();
+    assert_eq!(get_token_balance(&mut context, &env.treasury).await, 0);
+    assert_eq!(
+        get_token_balance(&mut context, &admin_token_account).await,
+        2 * COIN
+    );
+}
+
+#[tokio::test]
+async fn test_pay_off_loan_with_prepayment_discount() {
+    let program_id = Pubkey::new_unique();
+    let mut context = program_test(program_id).start_with_context().await;
+    let env = setup_funds4good(&mut context, program_id).await;
+
+    let not_admin = Keypair::new();
+    assert_funds4good_error(
+        set_prepayment_discount(&mut context, &env, &not_admin, 200).await,
+        Funds4GoodError::AdminDoesNotMatched,
+    );
+    assert_funds4good_error(
+        set_prepayment_discount(&mut context, &env, &env.admin, 501).await,
+        Funds4GoodError::InvalidFeeBps,
+    );
+    set_prepayment_discount(&mut context, &env, &env.admin, 200)
+        .await
+        .unwrap();
+
+    let loan = create_loan(&mut context, &env, 2, 200).await;
+    let lender = create_user(&mut context, &env, 200).await;
+    lend(&mut context, &env, &loan, &lender, 0, 200 * COIN)
+        .await
+        .unwrap();
+    withdraw_collected_loan_funds(&mut context, &env, &loan, &loan.borrower)
+        .await
+        .unwrap();
+
+    // 2% of the 200 coins still owed is waived when nothing is overdue
+    assert_funds4good_error(
+        pay_off_loan(&mut context, &env, &loan, 196 * COIN - 1).await,
+        Funds4GoodError::PayoffAmountExceedsMaximum,
+    );
+    pay_off_loan(&mut context, &env, &loan, 196 * COIN)
+        .await
+        .unwrap();
+
+    let header = get_loan_info_header(&mut context, &loan.loan_info).await;
+    assert_eq!(header.loan_status, LoanStatus::Repaid as u8);
+    assert_eq!(
+        get_token_balance(&mut context, &loan.borrower.token_account).await,
+        208 * COIN - 196 * COIN
+    );
+}
+
+#[tokio::test]
+async fn test_audit_vault() {
+    let program_id = Pubkey::new_unique();
+    let mut context = program_test(program_id).start_with_context().await;
+    let env = setup_funds4good(&mut context, program_id).await;
+
+    let first_loan = create_loan(&mut context, &env, 2, 200).await;
+    let second_loan = create_loan(&mut context, &env, 2, 200).await;
+    let lender = create_user(&mut context, &env, 300).await;
+    lend(&mut context, &env, &first_loan, &lender, 0, 200 * COIN)
+        .await
+        .unwrap();
+    lend(&mut context, &env, &second_loan, &lender, 0, 100 * COIN)
+        .await
+        .unwrap();
+    assert_eq!(get_vault_liabilities(&mut context, &env).await, 300 * COIN);
+    audit_vault(&mut context, &env).await.unwrap();
+
+    // disbursed funds leave the vault, only the loan still in fundraising is owed from it
+    withdraw_collected_loan_funds(&mut context, &env, &first_loan, &first_loan.borrower)
+        .await
+        .unwrap();
+    assert_eq!(get_vault_liabilities(&mut context, &env).await, 100 * COIN);
+    audit_vault(&mut context, &env).await.unwrap();
+
+    // lenders share of emi is owed as free balance, fees leave for treasury and reserve vault
+    pay_emi(&mut context, &env, &first_loan, 100 * COIN)
+        .await
+        .unwrap();
+    assert_eq!(get_vault_liabilities(&mut context, &env).await, 198 * COIN);
+
+    // cancelling only moves lent funds to free balance, withdrawing them leaves the vault
+    cancel_loan(
+        &mut context,
+        &env,
+        &second_loan,
+        &second_loan.borrower.keypair,
+    )
+    .await
+    .unwrap();
+    assert_eq!(get_vault_liabilities(&mut context, &env).await, 198 * COIN);
+    withdraw_lender_free_funds(&mut context, &env, &lender, 0, 48 * COIN)
+        .await
+        .unwrap();
+    assert_eq!(get_vault_liabilities(&mut context, &env).await, 150 * COIN);
+    audit_vault(&mut context, &env).await.unwrap();
+
+    let mut mint_vault_registry_data =
+        get_account_data(&mut context, &env.mint_vault_registry).await;
+    let mut lenders_storage_data = get_account_data(&mut context, &env.lenders_storage).await;
+    let mut loan_infos_data = [
+        get_account_data(&mut context, &first_loan.loan_info).await,
+        get_account_data(&mut context, &second_loan.loan_info).await,
+    ];
+    let vault_data = get_account_data(&mut context, &env.vault).await;
+    let reserve_pool_data = get_account_data(&mut context, &env.reserve_pool).await;
+    let reserve_vault_data = get_account_data(&mut context, &env.reserve_vault).await;
+    let report = audit::audit_vault_accounts(
+        &vault_data,
+        &mint_vault_registry_data,
+        &mut lenders_storage_data,
+        Some(&reserve_pool_data),
+        Some(&reserve_vault_data),
+        loan_infos_data.iter_mut().map(|data| data.as_mut_slice()),
+    )
+    .unwrap();
+    assert_eq!(report.vault_balance, 150 * COIN);
+    assert_eq!(report.vault_liabilities, 150 * COIN);
+    assert_eq!(report.ledger_liabilities, Some(150 * COIN));
+    assert_eq!(report.vault_discrepancy(), 0);
+    assert_eq!(report.reserve_discrepancy(), Some(0));
+    assert!(report.verify().is_ok());
+
+    // running total drifting from lenders storage and loan infos is caught off-chain
+    let mut mint_vault_registry =
+        MintVaultRegistryAccount::unpack(&mint_vault_registry_data).unwrap();
+    mint_vault_registry.vault_liabilities -= COIN;
+    MintVaultRegistryAccount::pack(mint_vault_registry, &mut mint_vault_registry_data).unwrap();
+    let report = audit::audit_vault_accounts(
+        &vault_data,
+        &mint_vault_registry_data,
+        &mut lenders_storage_data,
+        Some(&reserve_pool_data),
+        Some(&reserve_vault_data),
+        loan_infos_data.iter_mut().map(|data| data.as_mut_slice()),
+    )
+    .unwrap();
+    assert_eq!(
+        report.verify(),
+        Err(Funds4GoodError::VaultLiabilitiesDoNotMatchLedgers.into())
+    );
+
+    // vault losing coins outside the program is caught by the audit
+    let mut vault_account = context
+        .banks_client
+        .get_account(env.vault)
+        .await
+        .unwrap()
+        .unwrap();
+    let mut vault_data = TokenAccount::unpack(&vault_account.data).unwrap();
+    vault_data.amount -= 10 * COIN;
+    TokenAccount::pack(vault_data, &mut vault_account.data).unwrap();
+    context.set_account(&env.vault, &AccountSharedData::from(vault_account));
+
+    assert_funds4good_error(
+        audit_vault(&mut context, &env).await,
+        Funds4GoodError::VaultBalanceBelowLiabilities,
+    );
+}
+
+#[tokio::test]
+async fn test_audit_vault_without_reserve_pool() {
+    let program_id = Pubkey::new_unique();
+    let mut context = program_test(program_id).start_with_context().await;
+    let env = setup_funds4good_without_reserve_pool(&mut context, program_id).await;
+
+    let loan = create_loan(&mut context, &env, 2, 200).await;
+    let lender = create_user(&mut context, &env, 200).await;
+    lend(&mut context, &env, &loan, &lender, 0, 200 * COIN)
+        .await
+        .unwrap();
+    audit_vault(&mut context, &env).await.unwrap();
+
+    let mut lenders_storage_data = get_account_data(&mut context, &env.lenders_storage).await;
+    let mut loan_info_data = get_account_data(&mut context, &loan.loan_info).await;
+    let report = audit::audit_vault_accounts(
+        &get_account_data(&mut context, &env.vault).await,
+        &get_account_data(&mut context, &env.mint_vault_registry).await,
+        &mut lenders_storage_data,
+        None,
+        None,
+        [loan_info_data.as_mut_slice()],
+    )
+    .unwrap();
+    assert_eq!(report.vault_liabilities, 200 * COIN);
+    assert_eq!(report.ledger_liabilities, Some(200 * COIN));
+    assert_eq!(report.reserve_discrepancy(), None);
+    assert!(report.verify().is_ok());
+
+    // vault is still checked against its liabilities without reserve pool
+    let mut vault_account = context
+        .banks_client
+        .get_account(env.vault)
+        .await
+        .unwrap()
+        .unwrap();
+    let mut vault_data = TokenAccount::unpack(&vault_account.data).unwrap();
+    vault_data.amount -= 10 * COIN;
+    TokenAccount::pack(vault_data, &mut vault_account.data).unwrap();
+    context.set_account(&env.vault, &AccountSharedData::from(vault_account));
+
+    assert_funds4good_error(
+        audit_vault(&mut context, &env).await,
+        Funds4GoodError::VaultBalanceBelowLiabilities,
+    );
+}
+
+#[tokio::test]
+async fn test_airdrop() {
+    let program_id = Pubkey::new_unique();
+    let mut context = program_test(program_id).start_with_context().await;
+    let env = setup_funds4good(&mut context, program_id).await;
+
+    let airdrop_vault = create_token_account(&mut context, &env.mint, &env.admin.pubkey()).await;
+    mint_to(
+        &mut context,
+        &env.mint,
+        &airdrop_vault,
+        &env.admin,
+        5_000 * COIN,
+    )
+    .await;
+    process_instructions(
+        &mut context,
+        &[instruction::transfer_airdrop_vault_account_ownership(
+            &program_id,
+            &env.admin.pubkey(),
+            &airdrop_vault,
+            &env.config,
+        )],
+        &[&env.admin],
+    )
+    .await
+    .unwrap();
+
+    let (airdrop_authority, _) =
+        Pubkey::find_program_address(&[b"Funds4GoodFinanceAirdrop"], &program_id);
+    let user = create_user(&mut context, &env, 0).await;
+    let user_pubkey = user.keypair.pubkey();
+    let airdrop_user_storage =
+        Pubkey::create_with_seed(&user_pubkey, "Funds4GoodFinanceAirdrop", &program_id).unwrap();
+    let payer_pubkey = context.payer.pubkey();
+    let rent = context.banks_client.get_rent().await.unwrap();
+    process_instructions(
+        &mut context,
+        &[create_program_account_with_seed_ix(
+            &payer_pubkey,
+            &rent,
+            &program_id,
+            &user_pubkey,
+            "Funds4GoodFinanceAirdrop",
+            8,
+        )],
+        &[&user.keypair],
+    )
+    .await
+    .unwrap();
+
+    let airdrop_ix = |airdrop_vault: &Pubkey| {
+        instruction::airdrop_users_with_Funds4Good_test_coins(
+            &program_id,
+            &user_pubkey,
+            &airdrop_user_storage,
+            &user.token_account,
+            airdrop_vault,
+            &env.config,
+            &airdrop_authority,
+        )
+    };
+
+    assert_funds4good_error(
+        process_instructions(&mut context, &[airdrop_ix(&env.vault)], &[&user.keypair]).await,
+        Funds4GoodError::AirdropVaultAccountDoesNotMatched,
+    );
+
+    // every user can be airdropped 500 coins at a time up to 2500 coins in total
+    for _ in 0..5 {
+        process_instructions(
+            &mut context,
+            &[airdrop_ix(&airdrop_vault)],
+            &[&user.keypair],
+        )
+        .await
+        .unwrap();
+    }
+    assert_eq!(
+        get_token_balance(&mut context, &user.token_account).await,
+        2_500 * COIN
+    );
+
+    assert_funds4good_error(
+        process_instructions(
+            &mut context,
+            &[airdrop_ix(&airdrop_vault)],
+            &[&user.keypair],
+        )
+        .await,
+        Funds4GoodError::UserAlreadyAirdroped,
+    );
+
+    // storage of any other size than the u64 total is rejected instead of panicking
+    let other_user = create_user(&mut context, &env, 0).await;
+    let other_user_pubkey = other_user.keypair.pubkey();
+    let other_airdrop_user_storage =
+        Pubkey::create_with_seed(&other_user_pubkey, "Funds4GoodFinanceAirdrop", &program_id)
+            .unwrap();
+    assert_funds4good_error(
+        process_instructions(
+            &mut context,
+            &[
+                create_program_account_with_seed_ix(
+                    &payer_pubkey,
+                    &rent,
+                    &program_id,
+                    &other_user_pubkey,
+                    "Funds4GoodFinanceAirdrop",
+                    16,
+                ),
+                instruction::airdrop_users_with_Funds4Good_test_coins(
+                    &program_id,
+                    &other_user_pubkey,
+                    &other_airdrop_user_storage,
+                    &other_user.token_account,
+                    &airdrop_vault,
+                    &env.config,
+                    &airdrop_authority,
+                ),
+            ],
+            &[&other_user.keypair],
+        )
+        .await,
+        Funds4GoodError::DataSizeNotMatched,
+    );
+}
+
+fn program_data_log(event: &Funds4GoodEvent) -> String {
+    format!(
+        "{}{}",
+        event::PROGRAM_DATA_LOG_PREFIX,
+        base64::encode(event.pack())
+    )
+}
+
+// natively processed instructions print sol_log_data to stdout instead of transaction logs,
+// so decoding is checked against logs in the format the runtime writes for a deployed program
+#[test]
+fn test_decode_events_from_logs() {
+    let program_id = Pubkey::new_unique();
+    let other_program_id = Pubkey::new_unique();
+    let loan_info_pubkey = Pubkey::new_unique();
+    let lender_pubkey = Pubkey::new_unique();
+
+    let lent_event = Funds4GoodEvent::Lent {
+        loan_info_pubkey,
+        lender_pubkey,
+        lender_id: 0,
+        lent_amount: 200 * COIN,
+        excess_amount: 50 * COIN,
+        total_amount_lended: 200 * COIN,
+    };
+    let emi_paid_event = Funds4GoodEvent::EmiPaid {
+        loan_info_pubkey,
+        payer_pubkey: Pubkey::new_unique(),
+        installment_amount: 100 * COIN,
+        late_fee_amount: 0,
+        repaid_amount_by_borrower: 100 * COIN,
+        is_on_time: true,
+        loan_status: LoanStatus::Active as u8,
+    };
+    let other_program_event = Funds4GoodEvent::Withdrawn {
+        lender_pubkey,
+        lender_id: 0,
+        amount: COIN,
+        free_balance_after_withdrawal: 0,
+    };
+    assert_eq!(
+        Funds4GoodEvent::unpack(&lent_event.pack()).unwrap(),
+        lent_event
+    );
+
+    // data logged while another program is executing in a cpi or an earlier instruction is skipped
+    let log_messages = vec![
+        format!("Program {} invoke [1]", other_program_id),
+        program_data_log(&other_program_event),
+        format!("Program {} success", other_program_id),
+        format!("Program {} invoke [1]", program_id),
+        "Program log: Instruction: Lend".to_string(),
+        // program log messages that read like runtime lines don't change which program is executing
+        "Program log: invoke [2]".to_string(),
+        "Program log: success".to_string(),
+        "Program log: failed: to parse memo".to_string(),
+        program_data_log(&lent_event),
+        format!("Program {} invoke [2]", other_program_id),
+        program_data_log(&other_program_event),
+        format!(
+            "Program {} failed: custom program error: 0x0",
+            other_program_id
+        ),
+        program_data_log(&emi_paid_event),
+        format!(
+            "Program {} consumed 20000 of 200000 compute units",
+            program_id
+        ),
+        format!("Program {} success", program_id),
+    ];
+    assert_eq!(
+        event::decode_events_from_logs(&program_id, &log_messages).unwrap(),
+        vec![lent_event, emi_paid_event]
+    );
+
+    let mut wrong_schema_event_data = other_program_event.pack();
+    wrong_schema_event_data[0] = event::EVENT_SCHEMA_VERSION + 1;
+    for invalid_log in [
+        format!(
+            "{}{}",
+            event::PROGRAM_DATA_LOG_PREFIX,
+            base64::encode(wrong_schema_event_data)
+        ),
+        format!("{}not base64", event::PROGRAM_DATA_LOG_PREFIX),
+    ] {
+        assert_eq!(
+            event::decode_events_from_logs(
+                &program_id,
+                &[format!("Program {} invoke [1]", program_id), invalid_log],
+            ),
+            Err(Funds4GoodError::InvalidEventData.into())
+        );
+    }
+}